@@ -1,10 +1,19 @@
-use crate::application::cli::{Cli, Interactive, Metadata};
+use crate::application::cli::{Cli, Command, Interactive, Metadata};
+use crate::application::walk::{self, Walk};
 use crate::application::Error;
+use crate::matcher::pattern::{with_time_delimiter, DateBounds};
+use crate::matcher::{self, dir_name, sidecar, Case, Naming, Position};
+use crate::processing;
+use crate::replacement::{
+    self, Extensions, Normalization, RenameRetries, Sanitize,
+};
 
 use std::collections::VecDeque;
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use chrono::{DateTime, Local, NaiveDate};
 use toml::{Table, Value};
 
 #[derive(Debug)]
@@ -15,12 +24,46 @@ pub struct Arguments {
     pub(in crate::application) time: bool,
 
     default_date_format: String,
+    default_time_format: String,
+    /// Delimiter between the date and time portions of the default format
+    time_delimiter: String,
     default_date_time_format: String,
 
     pub(in crate::application) today: bool,
+    pub(in crate::application) date_offset: Option<i64>,
     pub(in crate::application) metadata: Metadata,
+    pub(in crate::application) dir_date: bool,
+    dir_date_regex: Option<String>,
+    pub(in crate::application) sidecar: bool,
+    sidecar_json_field: Option<String>,
+    sidecar_xmp_field: Option<String>,
+
+    pub(in crate::application) auto_accept: Vec<String>,
+    customize_extension: bool,
+    canonicalize: bool,
+    collapse_whitespace: bool,
+    prefix_separator: Option<String>,
+    position: Position,
+    normalization: Normalization,
+    date_bounds: DateBounds,
+    rename_retries: RenameRetries,
+    case: Case,
+    skip_if_dated_within_days: Option<u64>,
+    journal_progress_interval: Option<usize>,
+    #[cfg(feature = "notif")]
+    notif_summary_only: bool,
 
     pub(in crate::application) patterns: Option<Table>,
+    pub(in crate::application) composites: Option<Table>,
+
+    extensions: Extensions,
+    sanitize: Sanitize,
+
+    /// Paths to process, with directories expanded when recursive
+    paths: Vec<PathBuf>,
+
+    /// Directory the config was read from
+    config_dir: Option<PathBuf>,
 
     pub(in crate::application) init_errors: VecDeque<Error>,
 }
@@ -31,10 +74,37 @@ impl Default for Arguments {
             cli: Cli::default(),
             time: false,
             default_date_format: String::from(DEFAULT_DATE_FORMAT),
+            default_time_format: String::from(DEFAULT_TIME_FORMAT),
+            time_delimiter: String::from(DEFAULT_TIME_DELIMITER),
             default_date_time_format: String::from(DEFAULT_DATE_TIME_FORMAT),
             today: false,
+            date_offset: None,
             metadata: Metadata::default(),
+            dir_date: false,
+            dir_date_regex: None,
+            sidecar: false,
+            sidecar_json_field: None,
+            sidecar_xmp_field: None,
+            auto_accept: Vec::new(),
+            customize_extension: false,
+            canonicalize: true,
+            collapse_whitespace: true,
+            prefix_separator: None,
+            position: Position::default(),
+            normalization: Normalization::default(),
+            date_bounds: DateBounds::default(),
+            rename_retries: RenameRetries::default(),
+            case: Case::default(),
+            skip_if_dated_within_days: None,
+            journal_progress_interval: None,
+            #[cfg(feature = "notif")]
+            notif_summary_only: false,
             patterns: None,
+            composites: None,
+            extensions: Extensions::default(),
+            sanitize: Sanitize::default(),
+            paths: Vec::new(),
+            config_dir: None,
             init_errors: VecDeque::<Error>::default(),
         }
     }
@@ -42,38 +112,102 @@ impl Default for Arguments {
 
 pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
 pub const DEFAULT_DATE_TIME_FORMAT: &str = "%Y-%m-%d %Hh%Mm%S";
+const DEFAULT_TIME_FORMAT: &str = "%Hh%Mm%S";
+const DEFAULT_TIME_DELIMITER: &str = " ";
 
 impl Arguments {
     pub fn parse() -> Self {
-        match Self::try_parse_from(std::env::args_os()) {
+        let dir = std::env::current_dir().ok();
+
+        match Self::try_parse_from(std::env::args_os(), dir.as_deref()) {
             Ok(args) => args,
+            // Invalid arguments exit like an invalid config would
+            Err(error) if error.use_stderr() => {
+                let _ = error.print();
+                std::process::exit(super::EXIT_CONFIG.into())
+            }
             Err(error) => error.exit(),
         }
     }
 
-    fn try_parse_from<I, T>(iter: I) -> std::result::Result<Self, clap::Error>
+    /// Parse the arguments, looking for the local config from the given
+    /// directory if any
+    fn try_parse_from<I, T>(
+        iter: I,
+        dir: Option<&Path>,
+    ) -> std::result::Result<Self, clap::Error>
     where
         I: IntoIterator<Item = T>,
         T: Into<OsString> + Clone,
     {
         use clap::Parser;
 
-        let mut instance = Arguments::default();
-        instance.cli.try_update_from(iter)?;
-        instance.apply_config("config.toml");
+        let mut instance = Arguments {
+            cli: Cli::try_parse_from(iter)?,
+            ..Arguments::default()
+        };
+        instance.apply_config("config.toml", dir);
         instance.apply_cli();
 
         Ok(instance)
     }
 
+    /// Level of the logs to print
+    ///
+    /// When quiet, warnings and errors are still printed, as that's where
+    /// the failures to process paths are reported
     pub fn log_level_filter(&self) -> log::LevelFilter {
-        self.cli.verbose.log_level_filter()
+        let level = self.cli.verbose.log_level_filter();
+        let level = match self.quiet() {
+            true => level.max(log::LevelFilter::Warn),
+            false => level,
+        };
+
+        // The explanations are logged as info
+        match self.explain() {
+            true => level.max(log::LevelFilter::Info),
+            false => level,
+        }
+    }
+
+    /// Log the outcome of every matcher for each path
+    pub fn explain(&self) -> bool {
+        self.cli.explain
+    }
+
+    /// Separator of the records of the machine outputs
+    pub fn record_separator(&self) -> char {
+        if self.cli.print0 {
+            '\0'
+        } else {
+            '\n'
+        }
+    }
+
+    /// Hide the progress and the summary and keep the logs to warnings and
+    /// errors, e.g. when running from cron
+    pub fn quiet(&self) -> bool {
+        self.cli.verbose.is_silent()
     }
 
     pub fn interactive(&self) -> Interactive {
         self.cli.interactive
     }
 
+    pub fn command(&self) -> Option<Command> {
+        self.cli.command.clone()
+    }
+
+    /// Directory to sample to report the files each matcher would claim
+    pub fn sample(&self) -> Option<&Path> {
+        self.cli.sample.as_deref()
+    }
+
+    /// Directory holding the journals of the renames
+    pub fn journal_dir(&self) -> Option<PathBuf> {
+        self.config_dir.as_ref().map(|dir| dir.join("journal"))
+    }
+
     /// Prefix by date and time if true, date only otherwise
     pub fn time(&self) -> bool {
         self.time
@@ -81,7 +215,12 @@ impl Arguments {
 
     /// Default format string to format date
     pub fn default_format(&self) -> &str {
-        match self.time() {
+        self.format(self.time())
+    }
+
+    /// Default format string to format date, with or without time
+    pub fn format(&self, time: bool) -> &str {
+        match time {
             true => &self.default_date_time_format,
             false => &self.default_date_format,
         }
@@ -92,13 +231,165 @@ impl Arguments {
         self.today
     }
 
+    /// Use pre-determined date matcher with the given date
+    pub fn date(&self) -> Option<DateTime<Local>> {
+        self.cli.date
+    }
+
+    /// Use pre-determined date matcher with the date the given number of days
+    /// from today
+    pub fn date_offset(&self) -> Option<i64> {
+        self.date_offset
+    }
+
+    /// Regex capturing the date in directory names, if the matcher using it
+    /// is enabled
+    pub fn dir_date(&self) -> Option<&str> {
+        self.dir_date.then(|| {
+            self.dir_date_regex
+                .as_deref()
+                .unwrap_or(dir_name::DEFAULT_REGEX)
+        })
+    }
+
+    /// Fields of the JSON and XMP sidecars holding the date, if the matcher
+    /// reading them is enabled
+    pub fn sidecar(&self) -> Option<(&str, &str)> {
+        self.sidecar.then(|| {
+            (
+                self.sidecar_json_field
+                    .as_deref()
+                    .unwrap_or(sidecar::DEFAULT_JSON_FIELD),
+                self.sidecar_xmp_field
+                    .as_deref()
+                    .unwrap_or(sidecar::DEFAULT_XMP_FIELD),
+            )
+        })
+    }
+
     /// Use metadata matchers (creation and modification time)
     pub fn metadata(&self) -> Metadata {
         self.metadata
     }
 
+    /// Use the ID3 matcher, and whether to name files after their tag
+    pub fn id3(&self) -> Option<bool> {
+        self.cli.id3.then_some(self.cli.id3_name)
+    }
+
+    /// Use the PDF metadata matcher
+    #[cfg(feature = "pdf")]
+    pub fn pdf_meta(&self) -> bool {
+        self.cli.pdf_meta
+    }
+
+    /// Use the git commit date matcher
+    #[cfg(feature = "git")]
+    pub fn git_date(&self) -> bool {
+        self.cli.git_date
+    }
+
+    /// Case transformation of the names captured by patterns not configuring
+    /// their own
+    pub fn case(&self) -> Case {
+        self.case
+    }
+
+    /// Range of the dates plausible enough for patterns to match
+    pub fn date_bounds(&self) -> DateBounds {
+        self.date_bounds
+    }
+
+    /// Rules to split file names between stem and extension
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// How the matchers compose the new file stems
+    pub fn naming(&self) -> Naming {
+        Naming {
+            position: self.position,
+            prefix_separator: self.prefix_separator.clone(),
+            collapse_whitespace: self.collapse_whitespace,
+            keep_timezone: self.cli.keep_timezone,
+        }
+    }
+
+    /// Settings of the replacements created from the paths and executed
+    pub fn replacement_settings(&self) -> replacement::Settings {
+        replacement::Settings {
+            extensions: self.extensions.clone(),
+            normalization: self.normalization,
+            canonicalize: self.canonicalize,
+            rename_retries: self.rename_retries,
+            sanitize: self.sanitize.clone(),
+        }
+    }
+
+    /// Filter on the extensions of the files to process
+    pub fn extension_filter(&self) -> walk::ExtensionFilter {
+        walk::ExtensionFilter {
+            include: self.cli.extensions.clone(),
+            exclude: self.cli.exclude_ext.clone(),
+        }
+    }
+
+    /// Expansion of the directories, if running recursively
+    fn walk(&self) -> Option<Walk> {
+        self.cli.recursive.then_some(Walk {
+            max_depth: self.cli.max_depth,
+            follow_symlinks: self.cli.follow_symlinks,
+            hidden: self.cli.hidden,
+        })
+    }
+
     pub fn paths(&self) -> &[PathBuf] {
-        &self.cli.paths
+        &self.paths
+    }
+
+    /// Options to use when processing the paths
+    pub fn processing_options(&self) -> processing::Options {
+        processing::Options {
+            transforms: self.cli.transforms.clone(),
+            lower_ext: self.cli.lower_ext,
+            normalize_separators: self.cli.normalize_separators,
+            customize_extension: self.customize_extension,
+            skip_if_dated_within_days: self.skip_if_dated_within_days,
+            no_metadata_fallback: self.cli.no_metadata_fallback,
+            dry_run: self.cli.dry_run,
+            limit: self.cli.limit,
+            fail_fast: self.cli.fail_fast,
+            since: self.cli.since,
+            until: self.cli.until,
+            default_rescue: self
+                .cli
+                .default_rescue
+                .map(|rescue| rescue.matcher(self.default_format())),
+            rescue_alternatives: vec![
+                Box::new(matcher::Metadata::new_created(self.default_format())),
+                Box::new(matcher::Metadata::new_modified(
+                    self.default_format(),
+                )),
+            ],
+            stats_json: self.cli.stats_json.clone(),
+            output_dir: self.cli.output_dir.clone(),
+            organize: self.cli.organize,
+            on_conflict: self.cli.on_conflict,
+            touch: self.cli.touch,
+            skip_vanished: self.cli.skip_vanished,
+            allow_special: self.cli.allow_special,
+            journal: self.journal_dir(),
+            dir_log: self.cli.dir_log,
+            auto_accept: self.auto_accept.clone(),
+            walk: self.walk(),
+            extension_filter: self.extension_filter(),
+            quiet: self.quiet(),
+            journal_progress_interval: self.journal_progress_interval,
+            #[cfg(feature = "notif")]
+            notif_summary_only: self.notif_summary_only,
+            naming: self.naming(),
+            replacement: self.replacement_settings(),
+        }
     }
 
     fn apply_cli(&mut self) {
@@ -111,22 +402,116 @@ impl Arguments {
         }
 
         self.today = self.cli.today;
+        self.dir_date |= self.cli.dir_date;
+        self.sidecar |= self.cli.sidecar;
+        self.auto_accept = self.cli.auto_accept.clone();
+        self.customize_extension |= self.cli.customize_extension;
+        self.canonicalize &= !self.cli.no_canonicalize;
+        if let Some(position) = self.cli.position {
+            self.position = position;
+        }
+        if let Some(normalization) = self.cli.normalize {
+            self.normalization = normalization;
+        }
+        if let Some(separator) = self.cli.prefix_separator.clone() {
+            self.set_prefix_separator(&separator);
+        }
+
+        if let Some(offset) = self.cli.date_offset {
+            self.date_offset = Some(offset);
+        }
+
+        let mut paths = self.cli.paths.clone();
+        if let Some(Command::Preview { paths: preview }) = &self.cli.command {
+            paths.extend(preview.iter().cloned());
+        }
+        if self.cli.stdin {
+            match walk::read_paths(std::io::stdin().lock(), self.cli.null) {
+                Ok(read) => paths.extend(read),
+                Err(error) => self.init_errors.push_back(error.into()),
+            }
+        }
+
+        let paths = match self.walk() {
+            Some(walk) => walk.expand(&paths),
+            None => paths,
+        };
+        let paths = walk::dedup(paths);
+        self.paths = self.extension_filter().apply(paths, &self.extensions);
     }
 
-    fn apply_config(&mut self, filename: &str) {
-        let dir = self.cli.config.take().unwrap_or_else(config_home);
-        let path = dir.join(filename);
+    /// Apply the config files of the config directories, layered in order,
+    /// overridden per top-level key by the local config file found in the
+    /// given directory or its ancestors, if any
+    fn apply_config(&mut self, filename: &str, dir: Option<&Path>) {
+        let mut dirs = std::mem::take(&mut self.cli.config);
+        if dirs.is_empty() {
+            dirs.push(config_home());
+        }
+        self.config_dir = dirs.first().cloned();
 
-        match std::fs::read_to_string(path) {
-            Ok(content) => match content.parse::<Table>() {
-                Ok(config_table) => self.apply_config_table(config_table),
+        let mut config_table = None::<Table>;
+        for dir in dirs {
+            let Some(layer) = self.read_config(&dir.join(filename)) else {
+                continue;
+            };
+            match &mut config_table {
+                Some(config_table) => merge_config(config_table, layer),
+                None => config_table = Some(layer),
+            }
+        }
+
+        if let Some(local) = dir.and_then(local_config) {
+            log::debug!("Using local config {:?}", local);
+
+            let local_table = std::fs::read_to_string(&local)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    content.parse::<Table>().map_err(|e| e.to_string())
+                });
+            match local_table {
+                Ok(local_table) => config_table
+                    .get_or_insert_with(Table::new)
+                    .extend(local_table),
                 Err(e) => self.init_errors.push_back(
-                    format!("Unable to parse config file: {:?}", e).into(),
+                    format!("Unable to load local config {:?}: {}", local, e)
+                        .into(),
                 ),
+            }
+        }
+
+        if let Some(config_table) = config_table {
+            self.apply_config_table(config_table);
+        }
+    }
+
+    fn read_config(&mut self, path: &Path) -> Option<Table> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => match content.parse::<Table>() {
+                Ok(config_table) => Some(config_table),
+                Err(e) => {
+                    self.init_errors.push_back(
+                        format!("Unable to parse config file: {:?}", e).into(),
+                    );
+                    None
+                }
             },
-            Err(e) => self.init_errors.push_back(
-                format!("Unable to read config file: {:?}", e).into(),
-            ),
+            Err(e) => {
+                self.init_errors.push_back(
+                    format!("Unable to read config file: {:?}", e).into(),
+                );
+                None
+            }
+        }
+    }
+
+    fn set_prefix_separator(&mut self, separator: &str) {
+        if separator.contains(std::path::is_separator) {
+            self.init_errors.push_back(
+                format!("Invalid prefix separator: {:?}", separator).into(),
+            );
+        } else {
+            self.prefix_separator = Some(separator.to_string());
         }
     }
 
@@ -135,15 +520,192 @@ impl Arguments {
             self.time = value;
         }
 
+        if let Some(value) = config_table
+            .get("customize_extension")
+            .and_then(Value::as_bool)
+        {
+            self.customize_extension = value;
+        }
+
+        if let Some(value) =
+            config_table.get("canonicalize").and_then(Value::as_bool)
+        {
+            self.canonicalize = value;
+        }
+
+        if let Some(value) = config_table
+            .get("collapse_whitespace")
+            .and_then(Value::as_bool)
+        {
+            self.collapse_whitespace = value;
+        }
+
+        if let Some(separator) =
+            config_table.get("prefix_separator").and_then(Value::as_str)
+        {
+            self.set_prefix_separator(separator);
+        }
+
+        if let Some(position) =
+            config_table.get("position").and_then(Value::as_str)
+        {
+            match position.parse() {
+                Ok(position) => self.position = position,
+                Err(error) => self
+                    .init_errors
+                    .push_back(format!("Invalid position: {}", error).into()),
+            }
+        }
+
+        if let Some(normalize) =
+            config_table.get("normalize").and_then(Value::as_str)
+        {
+            match normalize.parse() {
+                Ok(normalization) => self.normalization = normalization,
+                Err(error) => self
+                    .init_errors
+                    .push_back(format!("Invalid normalize: {}", error).into()),
+            }
+        }
+
+        if let Some(case) = config_table.get("case").and_then(Value::as_str) {
+            match case.parse() {
+                Ok(case) => self.case = case,
+                Err(error) => self
+                    .init_errors
+                    .push_back(format!("Invalid case: {}", error).into()),
+            }
+        }
+
+        for (key, bound) in [
+            ("min_date", &mut self.date_bounds.min),
+            ("max_date", &mut self.date_bounds.max),
+        ] {
+            let Some(value) = config_table.get(key) else {
+                continue;
+            };
+            match value.as_str().and_then(|date| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+            }) {
+                Some(date) => *bound = Some(date),
+                None => self
+                    .init_errors
+                    .push_back(format!("Invalid {} {}", key, value).into()),
+            }
+        }
+
+        match config_table.get("skip_if_dated_within_days") {
+            None => {}
+            Some(Value::Integer(days)) if *days >= 0 => {
+                self.skip_if_dated_within_days = Some(*days as u64);
+            }
+            Some(value) => self.init_errors.push_back(
+                format!("Invalid skip_if_dated_within_days {}", value).into(),
+            ),
+        }
+
+        match config_table.get("rename_retries") {
+            None => {}
+            Some(Value::Integer(count)) if *count >= 0 => {
+                self.rename_retries.count =
+                    u32::try_from(*count).unwrap_or(u32::MAX);
+            }
+            Some(value) => self
+                .init_errors
+                .push_back(format!("Invalid rename_retries {}", value).into()),
+        }
+
+        match config_table.get("rename_retry_delay_ms") {
+            None => {}
+            Some(Value::Integer(delay)) if *delay >= 0 => {
+                self.rename_retries.delay =
+                    Duration::from_millis(*delay as u64);
+            }
+            Some(value) => self.init_errors.push_back(
+                format!("Invalid rename_retry_delay_ms {}", value).into(),
+            ),
+        }
+
+        match config_table.get("journal_progress_interval") {
+            None => {}
+            Some(Value::Integer(interval)) if *interval > 0 => {
+                self.journal_progress_interval = Some(*interval as usize);
+            }
+            Some(value) => self.init_errors.push_back(
+                format!("Invalid journal_progress_interval {}", value).into(),
+            ),
+        }
+
+        if let Some(Value::Array(keep_in_stem)) =
+            config_table.remove("keep_in_stem")
+        {
+            self.extensions.keep_in_stem = strings(keep_in_stem);
+        }
+
+        if let Some(Value::Array(compound)) =
+            config_table.remove("compound_extensions")
+        {
+            self.extensions.compound = strings(compound);
+        }
+
+        #[cfg(feature = "notif")]
+        if let Some(summary_only) = config_table
+            .get("notification")
+            .and_then(|notif| notif.get("summary_only"))
+            .and_then(Value::as_bool)
+        {
+            self.notif_summary_only = summary_only;
+        }
+
+        if let Some(Value::Table(sanitize)) = config_table.remove("sanitize") {
+            for (from, to) in sanitize {
+                let mut chars = from.chars();
+                match (chars.next(), chars.next(), to) {
+                    (Some(c), None, Value::String(to)) => {
+                        self.sanitize.replacements.push((c, to))
+                    }
+                    (_, _, to) => self.init_errors.push_back(
+                        format!(
+                            "Invalid sanitize replacement {:?} = {}",
+                            from, to
+                        )
+                        .into(),
+                    ),
+                }
+            }
+        }
+
         if let Some(Value::Table(mut formats)) =
             config_table.remove("default_format")
         {
             if let Some(Value::String(format)) = formats.remove("date") {
                 self.default_date_format = format;
             }
-            if let Some(Value::String(format)) = formats.remove("date_time") {
-                self.default_date_time_format = format;
+            if let Some(Value::String(format)) = formats.remove("time") {
+                self.default_time_format = format;
             }
+            let time_delimiter = match formats.remove("time_delimiter") {
+                Some(Value::String(delimiter)) => Some(delimiter),
+                _ => None,
+            };
+            if let Some(delimiter) = &time_delimiter {
+                self.time_delimiter = delimiter.clone();
+            }
+
+            // An explicit date_time format takes precedence over the one
+            // composed of the date and time portions
+            self.default_date_time_format = match formats.remove("date_time") {
+                Some(Value::String(format)) => match time_delimiter {
+                    Some(delimiter) => with_time_delimiter(&format, &delimiter),
+                    None => format,
+                },
+                _ => format!(
+                    "{}{}{}",
+                    self.default_date_format,
+                    self.time_delimiter,
+                    self.default_time_format
+                ),
+            };
         }
 
         if let Some(Value::Table(mut matchers)) =
@@ -157,6 +719,11 @@ impl Arguments {
                 {
                     self.today = today;
                 }
+                if let Some(offset) =
+                    predet.get("offset").and_then(Value::as_integer)
+                {
+                    self.date_offset = Some(offset);
+                }
             }
 
             if let Some(Value::Table(metadata)) = matchers.remove("metadata") {
@@ -184,13 +751,94 @@ impl Arguments {
                 }
             }
 
+            if let Some(Value::Table(dir_name)) = matchers.remove("dir_name") {
+                if let Some(enabled) =
+                    dir_name.get("enabled").and_then(Value::as_bool)
+                {
+                    self.dir_date = enabled;
+                }
+                if let Some(regex) =
+                    dir_name.get("regex").and_then(Value::as_str)
+                {
+                    self.dir_date_regex = Some(regex.to_string());
+                }
+            }
+
+            if let Some(Value::Table(sidecar)) = matchers.remove("sidecar") {
+                if let Some(enabled) =
+                    sidecar.get("enabled").and_then(Value::as_bool)
+                {
+                    self.sidecar = enabled;
+                }
+                if let Some(field) =
+                    sidecar.get("json_field").and_then(Value::as_str)
+                {
+                    self.sidecar_json_field = Some(field.to_string());
+                }
+                if let Some(field) =
+                    sidecar.get("xmp_field").and_then(Value::as_str)
+                {
+                    self.sidecar_xmp_field = Some(field.to_string());
+                }
+            }
+
             if let Some(Value::Table(patterns)) = matchers.remove("patterns") {
                 self.patterns = Some(patterns);
             }
+
+            if let Some(Value::Table(composites)) = matchers.remove("composite")
+            {
+                self.composites = Some(composites);
+            }
         }
     }
 }
 
+/// Keep only the strings of a config array
+fn strings(values: Vec<Value>) -> Vec<String> {
+    values
+        .into_iter()
+        .filter_map(|value| match value {
+            Value::String(string) => Some(string),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Merge a config layer over the base one, key by key
+///
+/// Tables are merged rather than replaced, down to the matchers themselves,
+/// so a layer can add a pattern or replace one by name
+fn merge_config(base: &mut Table, layer: Table) {
+    for (key, value) in layer {
+        let depth = if key == "matchers" { 2 } else { 1 };
+        merge_value(base, key, value, depth);
+    }
+}
+
+fn merge_value(table: &mut Table, key: String, value: Value, depth: usize) {
+    match (table.get_mut(&key), value) {
+        (Some(Value::Table(base)), Value::Table(layer)) if depth > 0 => {
+            for (key, value) in layer {
+                merge_value(base, key, value, depth - 1);
+            }
+        }
+        (_, value) => {
+            table.insert(key, value);
+        }
+    }
+}
+
+/// Name of the config files applying to a directory and its descendants
+const LOCAL_CONFIG: &str = ".prefix-by-date.toml";
+
+/// Find the closest local config file in the directory or its ancestors
+fn local_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(LOCAL_CONFIG))
+        .find(|path| path.is_file())
+}
+
 fn config_home() -> PathBuf {
     match std::env::var("PREFIX_BY_DATE_CONFIG") {
         Ok(val) if !val.is_empty() => PathBuf::from(val),
@@ -204,14 +852,16 @@ fn config_home() -> PathBuf {
 mod tests {
     use super::*;
     use crate::test::{
-        assert_eq, test, with_config, with_config_copied, with_config_dir,
+        assert_eq, assert_fs::*, test, with_config, with_config_copied,
+        with_config_dir, with_temp_dir,
     };
+    use std::ffi::OsStr;
 
     fn arguments_with_config(config: &str) -> Arguments {
         let mut arguments = Arguments::default();
 
         with_config_copied(&[config], || {
-            arguments.apply_config(config);
+            arguments.apply_config(config, None);
         });
 
         arguments
@@ -233,6 +883,31 @@ mod tests {
         });
     }
 
+    #[test]
+    fn local_config() {
+        use crate::test::{assert_fs::*, with_temp_dir};
+
+        with_temp_dir(|temp| {
+            let nested = temp.child("foo/bar");
+            nested.create_dir_all().unwrap();
+            assert_eq!(None, super::local_config(nested.path()));
+
+            let config = temp.child(LOCAL_CONFIG);
+            config.touch().unwrap();
+            assert_eq!(
+                Some(config.to_path_buf()),
+                super::local_config(nested.path())
+            );
+
+            let closer = temp.child("foo").child(LOCAL_CONFIG);
+            closer.touch().unwrap();
+            assert_eq!(
+                Some(closer.to_path_buf()),
+                super::local_config(nested.path())
+            );
+        });
+    }
+
     #[test]
     fn default_format() {
         let mut arguments = Arguments::default();
@@ -246,7 +921,7 @@ mod tests {
     #[test]
     fn invalid_cli_args() {
         assert!(matches!(
-            Arguments::try_parse_from(&["arg0", "--foo"]),
+            Arguments::try_parse_from(&["arg0", "--foo"], None),
             Err(_)
         ));
     }
@@ -254,39 +929,66 @@ mod tests {
     #[test]
     fn try_parse_from() {
         let arguments = with_config(|| {
-            Arguments::try_parse_from(&["arg0", "--time"]).unwrap()
+            Arguments::try_parse_from(&["arg0", "--time"], None).unwrap()
         });
         assert!(arguments.time());
 
         let arguments = with_config(|| {
-            Arguments::try_parse_from(&["arg0", "--no-time"]).unwrap()
+            Arguments::try_parse_from(&["arg0", "--no-time"], None).unwrap()
         });
         assert!(!arguments.time());
 
         let arguments = with_config(|| {
-            Arguments::try_parse_from(&["arg0", "--today"]).unwrap()
+            Arguments::try_parse_from(&["arg0", "--today"], None).unwrap()
         });
         assert!(arguments.today());
 
         let arguments = with_config(|| {
-            Arguments::try_parse_from(&["arg0", "--metadata", "both"]).unwrap()
+            Arguments::try_parse_from(&["arg0", "--metadata", "both"], None)
+                .unwrap()
         });
         assert!(matches!(arguments.metadata(), Metadata::Both));
 
         let arguments = with_config(|| {
-            Arguments::try_parse_from(&["arg0", "--metadata=created"]).unwrap()
+            Arguments::try_parse_from(&["arg0", "--metadata=created"], None)
+                .unwrap()
         });
         assert!(matches!(arguments.metadata(), Metadata::Created));
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(["arg0", "--dir-date"], None).unwrap()
+        });
+        assert_eq!(Some(dir_name::DEFAULT_REGEX), arguments.dir_date());
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(["arg0", "--sidecar"], None).unwrap()
+        });
+        assert_eq!(
+            Some((sidecar::DEFAULT_JSON_FIELD, sidecar::DEFAULT_XMP_FIELD)),
+            arguments.sidecar()
+        );
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(["arg0", "--prefix-separator=__"], None)
+                .unwrap()
+        });
+        assert_eq!(Some("__"), arguments.naming().prefix_separator.as_deref());
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(["arg0", "--prefix-separator=/"], None)
+                .unwrap()
+        });
+        assert_eq!(None, arguments.naming().prefix_separator.as_deref());
+        assert_eq!(1, arguments.init_errors.len());
     }
 
     #[test]
     fn parse_with_cli_config() {
         let mut arguments = with_config_dir(|dir| {
-            Arguments::try_parse_from(&[
-                "arg0",
-                "-C",
-                dir.path().to_str().unwrap(),
-            ])
+            Arguments::try_parse_from(
+                &["arg0", "-C", dir.path().to_str().unwrap()],
+                None,
+            )
             .unwrap()
         });
 
@@ -304,10 +1006,129 @@ mod tests {
         };
     }
 
+    #[test]
+    fn parse_with_layered_config() {
+        with_temp_dir(|temp| {
+            let base = temp.child("base");
+            base.child("config.toml")
+                .write_str(
+                    r#"
+                    time = false
+
+                    [default_format]
+                    date = "%Y-%m-%d"
+
+                    [matchers.metadata]
+                    created = true
+
+                    [matchers.patterns.base]
+                    regex = '(?<year>\d{4})'
+                    format = "%Y"
+                    "#,
+                )
+                .unwrap();
+            let layer = temp.child("override");
+            layer
+                .child("config.toml")
+                .write_str(
+                    r#"
+                    time = true
+
+                    [matchers.metadata]
+                    modified = true
+
+                    [matchers.patterns.added]
+                    regex = '(?<year>\d{4})-(?<month>\d{2})'
+                    "#,
+                )
+                .unwrap();
+
+            let arguments = Arguments::try_parse_from(
+                [
+                    "arg0",
+                    "-C",
+                    base.path().to_str().unwrap(),
+                    "-C",
+                    layer.path().to_str().unwrap(),
+                ],
+                None,
+            )
+            .unwrap();
+
+            assert!(arguments.init_errors.is_empty());
+            assert_eq!(true, arguments.time());
+            assert!(matches!(arguments.metadata(), Metadata::Both));
+            assert_eq!("%Y-%m-%d", arguments.default_date_format);
+            let patterns = arguments.patterns.as_ref().unwrap();
+            assert_eq!(
+                vec!["base", "added"],
+                patterns.keys().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                Some(base.path().join("journal")),
+                arguments.journal_dir()
+            );
+        });
+    }
+
+    #[test]
+    fn merge_config() {
+        let mut base = toml::toml! {
+            time = false
+            [sanitize]
+            ":" = "-"
+            [matchers.patterns.foo]
+            regex = "foo"
+            format = "%Y"
+        };
+        super::merge_config(
+            &mut base,
+            toml::toml! {
+                [sanitize]
+                "?" = ""
+                [matchers.patterns.foo]
+                regex = "bar"
+            },
+        );
+
+        assert_eq!(
+            toml::toml! {
+                time = false
+                [sanitize]
+                ":" = "-"
+                "?" = ""
+                // Patterns are replaced as a whole
+                [matchers.patterns.foo]
+                regex = "bar"
+            },
+            base
+        );
+    }
+
+    #[test]
+    fn journal_dir() {
+        assert!(Arguments::default().journal_dir().is_none());
+
+        let arguments = with_config_dir(|dir| {
+            let arguments = Arguments::try_parse_from(["arg0"], None).unwrap();
+            assert_eq!(
+                Some(dir.path().join("journal")),
+                arguments.journal_dir()
+            );
+            arguments
+        });
+        assert!(arguments.command().is_none());
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(["arg0", "undo"], None).unwrap()
+        });
+        assert!(matches!(arguments.command(), Some(Command::Undo)));
+    }
+
     #[test]
     fn paths() {
         let arguments = with_config(|| {
-            Arguments::try_parse_from(&["arg0", "foo", "bar"]).unwrap()
+            Arguments::try_parse_from(&["arg0", "foo", "bar"], None).unwrap()
         });
 
         assert_eq!(
@@ -316,6 +1137,195 @@ mod tests {
         );
     }
 
+    #[test]
+    fn duplicate_paths() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("dir/foo").unwrap();
+            let dir = temp.child("dir");
+
+            // The file is also part of the expanded directory
+            let arguments = with_config(|| {
+                Arguments::try_parse_from(
+                    [
+                        OsStr::new("arg0"),
+                        OsStr::new("-r"),
+                        foo.path().as_os_str(),
+                        foo.path().as_os_str(),
+                        dir.path().as_os_str(),
+                    ],
+                    None,
+                )
+                .unwrap()
+            });
+            assert_eq!([foo.to_path_buf()], arguments.paths());
+        });
+    }
+
+    #[test]
+    fn recursive_paths() {
+        with_temp_dir(|temp| {
+            temp.existing_child("dir/foo").unwrap();
+            let dir = temp.child("dir");
+
+            let arguments = with_config(|| {
+                Arguments::try_parse_from(
+                    [OsStr::new("arg0"), dir.path().as_os_str()],
+                    None,
+                )
+                .unwrap()
+            });
+            assert_eq!([dir.to_path_buf()], arguments.paths());
+
+            let arguments = with_config(|| {
+                Arguments::try_parse_from(
+                    [
+                        OsStr::new("arg0"),
+                        OsStr::new("-r"),
+                        dir.path().as_os_str(),
+                    ],
+                    None,
+                )
+                .unwrap()
+            });
+            assert_eq!([dir.child("foo").to_path_buf()], arguments.paths());
+        });
+    }
+
+    #[test]
+    fn extensions_paths() {
+        with_temp_dir(|temp| {
+            for name in ["a.jpg", "b.JPG", "c.png", "d.txt", "e", "sub/f.mp4"] {
+                temp.existing_child(name).unwrap();
+            }
+
+            let arguments = with_config(|| {
+                Arguments::try_parse_from(
+                    [
+                        OsStr::new("arg0"),
+                        OsStr::new("-r"),
+                        OsStr::new("--extensions=jpg,mp4"),
+                        temp.path().as_os_str(),
+                    ],
+                    None,
+                )
+                .unwrap()
+            });
+            assert_eq!(
+                [
+                    temp.child("a.jpg").to_path_buf(),
+                    temp.child("b.JPG").to_path_buf(),
+                    temp.child("sub/f.mp4").to_path_buf(),
+                ],
+                arguments.paths()
+            );
+
+            let arguments = with_config(|| {
+                Arguments::try_parse_from(
+                    [
+                        OsStr::new("arg0"),
+                        OsStr::new("--extensions=png"),
+                        temp.child("c.png").as_os_str(),
+                        temp.child("d.txt").as_os_str(),
+                    ],
+                    None,
+                )
+                .unwrap()
+            });
+            assert_eq!([temp.child("c.png").to_path_buf()], arguments.paths());
+
+            let arguments = with_config(|| {
+                Arguments::try_parse_from(
+                    [
+                        OsStr::new("arg0"),
+                        OsStr::new("-r"),
+                        OsStr::new("--include-ext=jpg,png"),
+                        OsStr::new("--exclude-ext=PNG"),
+                        temp.path().as_os_str(),
+                    ],
+                    None,
+                )
+                .unwrap()
+            });
+            assert_eq!(
+                [
+                    temp.child("a.jpg").to_path_buf(),
+                    temp.child("b.JPG").to_path_buf(),
+                ],
+                arguments.paths()
+            );
+        });
+    }
+
+    #[test]
+    fn quiet() {
+        use log::LevelFilter;
+
+        let arguments =
+            with_config(|| Arguments::try_parse_from(["arg0"], None).unwrap());
+        assert!(!arguments.quiet());
+        assert!(!arguments.processing_options().quiet);
+        assert_eq!(LevelFilter::Error, arguments.log_level_filter());
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(["arg0", "-q"], None).unwrap()
+        });
+        assert!(arguments.quiet());
+        assert!(arguments.processing_options().quiet);
+        assert_eq!(LevelFilter::Warn, arguments.log_level_filter());
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(["arg0", "--quiet", "--quiet"], None)
+                .unwrap()
+        });
+        assert!(arguments.quiet());
+        assert_eq!(LevelFilter::Warn, arguments.log_level_filter());
+    }
+
+    #[test]
+    fn processing_options() {
+        use processing::Transform;
+
+        let arguments = with_config(|| {
+            Arguments::try_parse_from(
+                [
+                    "arg0",
+                    "--transform",
+                    "lowercase",
+                    "--transform",
+                    "slugify",
+                    "--dry-run",
+                    "--default-rescue",
+                    "modified",
+                    "--auto-accept",
+                    "foo",
+                    "--auto-accept=bar",
+                    "--keep-timezone",
+                    "--customize-extension",
+                    "--no-metadata-fallback",
+                    "--touch=modified",
+                ],
+                None,
+            )
+            .unwrap()
+        });
+
+        let options = arguments.processing_options();
+        assert_eq!(
+            vec![Transform::Lowercase, Transform::Slugify],
+            options.transforms
+        );
+        assert!(options.dry_run);
+        assert_eq!(
+            Some(crate::matcher::metadata::MODIFIED),
+            options.default_rescue.as_ref().map(|m| m.name())
+        );
+        assert_eq!(vec!["foo", "bar"], options.auto_accept);
+        assert!(options.naming.keep_timezone);
+        assert!(options.customize_extension);
+        assert!(options.no_metadata_fallback);
+        assert_eq!(crate::application::Touch::Modified, options.touch);
+    }
+
     mod apply_config {
         use super::*;
         use crate::test::{assert_eq, test};
@@ -325,7 +1335,7 @@ mod tests {
             let mut arguments = Arguments::default();
 
             with_config_dir(|_| {
-                arguments.apply_config("config.toml");
+                arguments.apply_config("config.toml", None);
             });
 
             match arguments.init_errors.pop_front() {
@@ -343,6 +1353,62 @@ mod tests {
             };
         }
 
+        #[test]
+        fn local_overrides() {
+            use crate::test::{assert_fs::*, with_temp_dir};
+
+            with_temp_dir(|temp| {
+                let local = temp.child(LOCAL_CONFIG);
+                local
+                    .write_str(
+                        r#"
+                        time = true
+
+                        [matchers.patterns.local]
+                        regex = '(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+                        "#,
+                    )
+                    .unwrap();
+
+                // Found in the ancestors of the directory
+                let dir = temp.child("foo/bar");
+                dir.create_dir_all().unwrap();
+
+                let mut arguments = Arguments::default();
+                with_config(|| {
+                    arguments.apply_config("config.toml", Some(dir.path()))
+                });
+
+                assert!(arguments.init_errors.is_empty());
+                assert_eq!(true, arguments.time());
+                // Merged per top-level key, so the local matchers replace the
+                // global ones altogether
+                let patterns = arguments.patterns.as_ref().unwrap();
+                assert_eq!(vec!["local"], patterns.keys().collect::<Vec<_>>());
+                assert!(matches!(arguments.metadata(), Metadata::None));
+                // Keys only in the global config are kept
+                assert_eq!("%Y-%m-%d", arguments.default_date_format);
+            });
+        }
+
+        #[test]
+        fn local_without_global() {
+            use crate::test::{assert_fs::*, with_config_dir, with_temp_dir};
+
+            with_temp_dir(|temp| {
+                let local = temp.child(LOCAL_CONFIG);
+                local.write_str("time = true").unwrap();
+
+                let mut arguments = Arguments::default();
+                with_config_dir(|_| {
+                    arguments.apply_config("config.toml", Some(temp.path()))
+                });
+
+                assert_eq!(1, arguments.init_errors.len());
+                assert_eq!(true, arguments.time());
+            });
+        }
+
         #[test]
         fn fails_silently_on_incorrect_config() {
             let mut arguments = arguments_with_config("configs/non_toml");
@@ -375,7 +1441,21 @@ mod tests {
             );
             assert_eq!(false, arguments.today());
             assert!(matches!(arguments.metadata(), Metadata::None));
+            assert_eq!(None, arguments.dir_date());
+            assert_eq!(None, arguments.sidecar());
+            assert_eq!(None, arguments.naming().prefix_separator.as_deref());
+            assert_eq!(Position::Prefix, arguments.naming().position);
+            assert_eq!(
+                Normalization::Nfc,
+                arguments.replacement_settings().normalization
+            );
+            assert_eq!(DateBounds::default(), arguments.date_bounds());
+            assert_eq!(
+                RenameRetries::default(),
+                arguments.replacement_settings().rename_retries
+            );
             assert!(arguments.patterns.is_none());
+            assert!(arguments.composites.is_none());
         }
 
         #[test]
@@ -394,17 +1474,229 @@ mod tests {
             assert_eq!(true, arguments.time());
         }
 
+        #[test]
+        fn keep_in_stem() {
+            let arguments =
+                arguments_with_config("configs/extensions/keep_in_stem.toml");
+
+            assert!(arguments.init_errors.is_empty());
+            assert_eq!(
+                vec![String::from("min"), String::from("map")],
+                arguments.extensions().keep_in_stem
+            );
+        }
+
+        #[test]
+        fn compound_extensions() {
+            let arguments =
+                arguments_with_config("configs/extensions/compound.toml");
+
+            assert!(arguments.init_errors.is_empty());
+            assert_eq!(
+                vec![String::from("tar.gz"), String::from("tar.bz2")],
+                arguments.extensions().compound
+            );
+        }
+
+        #[test]
+        fn sanitize() {
+            let mut arguments = arguments_with_config("configs/sanitize.toml");
+
+            assert_eq!(
+                vec![(':', String::from("h")), ('?', String::new())],
+                arguments.replacement_settings().sanitize.replacements
+            );
+            match arguments.init_errors.pop_front() {
+                Some(Error::Custom(message)) => assert_eq!(
+                    "Invalid sanitize replacement \"ab\" = \"c\"",
+                    message
+                ),
+                error => panic!("Unexpected error {:?}", error),
+            }
+            match arguments.init_errors.pop_front() {
+                Some(Error::Custom(message)) => assert_eq!(
+                    "Invalid sanitize replacement \"|\" = 42",
+                    message
+                ),
+                error => panic!("Unexpected error {:?}", error),
+            }
+            assert!(arguments.init_errors.is_empty());
+        }
+
+        #[test]
+        fn time_delimiter() {
+            let arguments =
+                arguments_with_config("configs/time/delimiter.toml");
+
+            assert!(arguments.init_errors.is_empty());
+            assert_eq!("_", arguments.time_delimiter);
+            assert_eq!("%d.%m.%Y_%H%M", arguments.default_date_time_format);
+        }
+
+        #[test]
+        fn time_delimiter_with_date_time() {
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                [default_format]
+                date_time = "%Y-%m-%d %Hh%Mm%S"
+                time_delimiter = "_"
+            });
+
+            assert_eq!("%Y-%m-%d_%Hh%Mm%S", arguments.default_date_time_format);
+        }
+
+        #[test]
+        fn skip_if_dated_within_days() {
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                skip_if_dated_within_days = 3
+            });
+            assert_eq!(Some(3), arguments.skip_if_dated_within_days);
+            assert!(arguments.init_errors.is_empty());
+
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                skip_if_dated_within_days = -1
+            });
+            assert_eq!(None, arguments.skip_if_dated_within_days);
+            assert_eq!(1, arguments.init_errors.len());
+        }
+
+        #[test]
+        fn rename_retries() {
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                rename_retries = 0
+            });
+            assert_eq!(
+                0,
+                arguments.replacement_settings().rename_retries.count
+            );
+            assert!(arguments.init_errors.is_empty());
+
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                rename_retries = -1
+                rename_retry_delay_ms = "fast"
+            });
+            assert_eq!(
+                RenameRetries::default(),
+                arguments.replacement_settings().rename_retries
+            );
+            assert_eq!(2, arguments.init_errors.len());
+        }
+
+        #[test]
+        fn date_bounds() {
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                max_date = "2030-01-01"
+            });
+            assert_eq!(None, arguments.date_bounds().min);
+            assert_eq!(
+                NaiveDate::from_ymd_opt(2030, 1, 1),
+                arguments.date_bounds().max
+            );
+            assert!(arguments.init_errors.is_empty());
+
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                min_date = "yesterday"
+                max_date = 2030
+            });
+            assert_eq!(DateBounds::default(), arguments.date_bounds());
+            assert_eq!(2, arguments.init_errors.len());
+        }
+
+        #[test]
+        fn case() {
+            let mut arguments = Arguments::default();
+            assert_eq!(Case::None, arguments.case());
+            arguments.apply_config_table(toml::toml! {
+                case = "lower"
+            });
+            assert_eq!(Case::Lower, arguments.case());
+            assert!(arguments.init_errors.is_empty());
+
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                case = "camel"
+            });
+            assert_eq!(Case::None, arguments.case());
+            assert_eq!(1, arguments.init_errors.len());
+        }
+
+        #[test]
+        fn journal_progress_interval() {
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                journal_progress_interval = 10
+            });
+            assert_eq!(Some(10), arguments.journal_progress_interval);
+            assert_eq!(
+                Some(10),
+                arguments.processing_options().journal_progress_interval
+            );
+            assert!(arguments.init_errors.is_empty());
+
+            let mut arguments = Arguments::default();
+            arguments.apply_config_table(toml::toml! {
+                journal_progress_interval = 0
+            });
+            assert_eq!(None, arguments.journal_progress_interval);
+            assert_eq!(1, arguments.init_errors.len());
+        }
+
         #[test]
         fn different_config() {
             let arguments = arguments_with_config("configs/different.toml");
 
             assert!(arguments.init_errors.is_empty());
             assert_eq!(true, arguments.time());
+            assert_eq!(true, arguments.customize_extension);
+            assert_eq!(false, arguments.replacement_settings().canonicalize);
+            assert_eq!(false, arguments.naming().collapse_whitespace);
+            assert_eq!(
+                Some("__"),
+                arguments.naming().prefix_separator.as_deref()
+            );
+            assert_eq!(Position::Suffix, arguments.naming().position);
+            assert_eq!(
+                Normalization::Nfd,
+                arguments.replacement_settings().normalization
+            );
+            assert_eq!(
+                DateBounds {
+                    min: NaiveDate::from_ymd_opt(2000, 1, 1),
+                    max: NaiveDate::from_ymd_opt(2099, 12, 31),
+                },
+                arguments.date_bounds()
+            );
+            assert_eq!(
+                RenameRetries {
+                    count: 5,
+                    delay: Duration::from_millis(200),
+                },
+                arguments.replacement_settings().rename_retries
+            );
+            assert_eq!(Some(2), arguments.skip_if_dated_within_days);
+            #[cfg(feature = "notif")]
+            assert_eq!(true, arguments.notif_summary_only);
             assert_eq!("%m-%d %Y", arguments.default_date_format);
             assert_eq!("%m-%d %Hh%Mm%S %Y", arguments.default_date_time_format);
             assert_eq!(true, arguments.today());
+            assert_eq!(Some(-1), arguments.date_offset());
             assert!(matches!(arguments.metadata(), Metadata::Both));
-            assert_eq!(2, arguments.patterns.unwrap().len());
+            assert_eq!(
+                Some(r"\A(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})\z"),
+                arguments.dir_date()
+            );
+            assert_eq!(
+                Some(("photoTakenTime.timestamp", "xmp:CreateDate")),
+                arguments.sidecar()
+            );
+            assert_eq!(3, arguments.patterns.unwrap().len());
+            assert_eq!(1, arguments.composites.unwrap().len());
         }
     }
 }
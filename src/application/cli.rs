@@ -1,10 +1,18 @@
+use crate::matcher::{self, Matcher, Position};
+use crate::processing::Transform;
+use crate::replacement::Normalization;
+
 use std::path::PathBuf;
 
-use clap::{builder::ArgAction, Parser, ValueEnum};
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use clap::builder::{ArgAction, PossibleValuesParser, TypedValueParser};
+use clap::{Parser, Subcommand, ValueEnum};
 
-#[derive(Default, Debug, Copy, Clone, ValueEnum)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, ValueEnum)]
 pub enum Interactive {
+    /// Text in a terminal, else the GUI if a display is available, else off
     #[default]
+    Auto,
     Off,
     Text,
     Gui,
@@ -29,6 +37,88 @@ impl Metadata {
     }
 }
 
+/// What to do when the target of a replacement already exists
+#[derive(Default, Debug, Copy, Clone, PartialEq, ValueEnum)]
+pub enum OnConflict {
+    /// Refuse to overwrite the target and report an error
+    #[default]
+    Refuse,
+    /// Append a numeric suffix like ` (1)` until a free name is found
+    Suffix,
+}
+
+/// Time of the renamed files to set to the date they are prefixed by
+#[derive(Default, Debug, Copy, Clone, PartialEq, ValueEnum)]
+pub enum Touch {
+    /// Keep the times of the files
+    #[default]
+    None,
+    /// Set the creation time, where the platform allows it
+    Created,
+    /// Set the modification time
+    Modified,
+}
+
+/// Subdirectories to move the renamed files into, derived from their date
+#[derive(Debug, Copy, Clone, PartialEq, ValueEnum)]
+pub enum Organize {
+    /// Year, month then day, e.g. 2024/01/20
+    Ymd,
+    /// Year then month, e.g. 2024/01
+    Ym,
+    /// Year only, e.g. 2024
+    Y,
+}
+
+impl Organize {
+    /// Relative path of the subdirectories for the given date
+    pub fn subdir<D: Datelike>(&self, date: &D) -> PathBuf {
+        let year = format!("{:04}", date.year());
+        let month = format!("{:02}", date.month());
+        let day = format!("{:02}", date.day());
+
+        match self {
+            Self::Ymd => [year, month, day].iter().collect(),
+            Self::Ym => [year, month].iter().collect(),
+            Self::Y => PathBuf::from(year),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum Rescue {
+    Today,
+    Created,
+    Modified,
+}
+
+impl Rescue {
+    /// Build the matcher used to rescue paths with the given date format
+    pub fn matcher(&self, format: &str) -> Box<dyn Matcher> {
+        match self {
+            Self::Today => Box::new(matcher::PredeterminedDate::new(format)),
+            Self::Created => Box::new(matcher::Metadata::new_created(format)),
+            Self::Modified => Box::new(matcher::Metadata::new_modified(format)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Revert the renames of the most recent run
+    Undo,
+    /// List the active matchers in the order they are tried
+    ListMatchers,
+    /// Check the config file, exiting with an error if it has any
+    CheckConfig,
+    /// Print the new path and the matcher of each path as a tab-separated
+    /// table, without renaming anything
+    Preview {
+        /// Paths to preview
+        paths: Vec<PathBuf>,
+    },
+}
+
 /// Prefix files by date
 #[derive(Default, Debug, Parser)]
 #[command(version)]
@@ -36,17 +126,62 @@ pub struct Cli {
     #[clap(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
 
-    /// Sets a custom config directory
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Sets a custom config directory, repeat to layer their config files,
+    /// each overriding the previous ones
     ///
     /// The default value is $PREFIX_BY_DATE_CONFIG if it is set, or
-    /// $XDG_CONFIG_HOME/prefix-by-date otherwise
+    /// $XDG_CONFIG_HOME/prefix-by-date otherwise. The journal is kept in the
+    /// first directory
     #[arg(short = 'C', long, value_name = "DIR")]
-    pub config: Option<PathBuf>,
+    pub config: Vec<PathBuf>,
 
     /// Prefix by today's date
     #[arg(long)]
     pub today: bool,
 
+    /// Prefix by the given date, formatted as YYYY-MM-DD
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub date: Option<DateTime<Local>>,
+
+    /// Prefix by the date the given number of days from today, e.g. -1 for
+    /// yesterday
+    #[arg(long, value_name = "N", allow_negative_numbers = true)]
+    pub date_offset: Option<i64>,
+
+    /// Prefix files by the date in the name of their directory, e.g.
+    /// 2024-01-20/scan001.pdf
+    #[arg(long)]
+    pub dir_date: bool,
+
+    /// Prefix files by the date found in their JSON or XMP sidecar, e.g.
+    /// photo.json or photo.jpg.xmp, renaming the sidecars along
+    #[arg(long)]
+    pub sidecar: bool,
+
+    /// Separator between the date and the name, e.g. "__", instead of the
+    /// delimiter of each matcher
+    #[arg(long, value_name = "SEP")]
+    pub prefix_separator: Option<String>,
+
+    /// Place the date before or after the name
+    #[arg(
+        long,
+        value_parser = PossibleValuesParser::new(["prefix", "suffix"])
+            .try_map(|position| position.parse::<Position>())
+    )]
+    pub position: Option<Position>,
+
+    /// Unicode normalization of the file names before matching
+    #[arg(
+        long,
+        value_parser = PossibleValuesParser::new(["nfc", "nfd", "none"])
+            .try_map(|normalize| normalize.parse::<Normalization>())
+    )]
+    pub normalize: Option<Normalization>,
+
     /// Prefix by date and time
     #[arg(long = "time", overrides_with = "time")]
     pub no_time: bool,
@@ -55,14 +190,208 @@ pub struct Cli {
     #[arg(long = "no-time", action = ArgAction::SetFalse)]
     pub time: bool,
 
+    /// Format the dates in the timezone of their source, e.g. captured by a
+    /// pattern, instead of converting them to the local one
+    #[arg(long)]
+    pub keep_timezone: bool,
+
     /// Start the program interactively or not
-    #[arg(short, long, value_enum, default_value_t = Interactive::Off)]
+    #[arg(short, long, value_enum, default_value_t = Interactive::Auto)]
     pub interactive: Interactive,
 
     /// Metadata matchers to enable
     #[arg(short, long, value_enum)]
     pub metadata: Option<Metadata>,
 
+    /// Prefix audio files by the recording date of their ID3 tag
+    #[arg(long)]
+    pub id3: bool,
+
+    /// Use the artist and title of the ID3 tag as name
+    #[arg(long, requires = "id3")]
+    pub id3_name: bool,
+
+    /// Prefix PDF files by the creation date of their metadata
+    #[cfg(feature = "pdf")]
+    #[arg(long)]
+    pub pdf_meta: bool,
+
+    /// Prefix files tracked by git by the author date of their last commit
+    #[cfg(feature = "git")]
+    #[arg(long)]
+    pub git_date: bool,
+
+    /// Transform to apply to the new file name, repeat to chain them in order
+    ///
+    /// Possible values: lowercase, uppercase, slugify, ascii, append=<TEXT>
+    #[arg(short, long = "transform", value_name = "TRANSFORM")]
+    pub transforms: Vec<Transform>,
+
+    /// Convert the extension of the new file names to lowercase, leaving the
+    /// rest of the name untouched
+    #[arg(long)]
+    pub lower_ext: bool,
+
+    /// Edit the whole file name, extension included, when customizing a
+    /// rename
+    #[arg(long)]
+    pub customize_extension: bool,
+
+    /// Replace path separators typed in customized names with a dash instead
+    /// of refusing them
+    #[arg(long)]
+    pub normalize_separators: bool,
+
+    /// Keep the directories of the paths as given instead of resolving them,
+    /// so files reached through a symlink are reported through it
+    #[arg(long)]
+    pub no_canonicalize: bool,
+
+    /// Report the files whose metadata can't be read, e.g. creation time
+    /// unsupported by the filesystem, as errors instead of not matching
+    #[arg(long)]
+    pub no_metadata_fallback: bool,
+
+    /// Only report what would be renamed, without touching any file. With
+    /// the text interface, preview every rename without prompting
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Stop once the given number of files are renamed, leaving the remaining
+    /// paths untouched
+    ///
+    /// Paths skipped or failing to be renamed don't count toward the limit
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Stop at the first path failing to be processed, exiting with an error
+    ///
+    /// Paths skipped or without match don't stop the processing
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Skip the paths whose matched date is before the given one, formatted
+    /// as YYYY-MM-DD
+    #[arg(long, value_name = "DATE", value_parser = parse_day)]
+    pub since: Option<NaiveDate>,
+
+    /// Skip the paths whose matched date is after the given one, formatted
+    /// as YYYY-MM-DD
+    #[arg(long, value_name = "DATE", value_parser = parse_day)]
+    pub until: Option<NaiveDate>,
+
+    /// Log the outcome of every matcher for each path before processing it,
+    /// i.e. the new name or the reason it doesn't match
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Report how many files of the given directory each matcher would claim,
+    /// instead of processing paths
+    #[arg(long, value_name = "DIR", requires = "dry_run")]
+    pub sample: Option<PathBuf>,
+
+    /// Matcher used to rescue paths no other matcher could handle when not
+    /// running interactively
+    ///
+    /// Paths this matcher cannot handle either are reported as unmatched
+    #[arg(long, value_enum, value_name = "MATCHER")]
+    pub default_rescue: Option<Rescue>,
+
+    /// Move the renamed files into the given directory, created if needed,
+    /// instead of keeping them in their own
+    ///
+    /// Files from different directories may end up with the same name, see
+    /// --on-conflict=suffix
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Move the renamed files into subdirectories of their date, inside the
+    /// output directory or their own, e.g. 2024/01/2024-01-20 foo.jpg
+    ///
+    /// Files without a known date, e.g. renamed by hand, are left in place
+    #[arg(long, value_enum, value_name = "LAYOUT")]
+    pub organize: Option<Organize>,
+
+    /// What to do when the new name of a file is already taken
+    #[arg(long, value_enum, default_value_t = OnConflict::Refuse)]
+    pub on_conflict: OnConflict,
+
+    /// Set a time of the renamed files to the date they are prefixed by
+    #[arg(long, value_enum, default_value_t = Touch::None)]
+    pub touch: Touch,
+
+    /// Skip files removed while the run is in progress instead of reporting
+    /// them as errors
+    #[arg(long)]
+    pub skip_vanished: bool,
+
+    /// Process special files, like FIFOs, sockets or devices, instead of
+    /// skipping them
+    #[arg(long)]
+    pub allow_special: bool,
+
+    /// Accept the replacements of the given matcher without confirmation,
+    /// like answering always to the first one
+    #[arg(long, value_name = "NAME")]
+    pub auto_accept: Vec<String>,
+
+    /// Record the renames in a `.prefix-by-date.log` file inside each
+    /// directory where they happen
+    #[arg(long)]
+    pub dir_log: bool,
+
+    /// Write statistics of the run as JSON to the given file
+    #[arg(long, value_name = "PATH")]
+    pub stats_json: Option<PathBuf>,
+
+    /// Process the files inside the directories given as paths, instead of
+    /// the directories themselves
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Maximum depth to descend into when processing directories recursively
+    ///
+    /// A depth of 1 only processes the files directly inside the directories
+    #[arg(long, value_name = "N", requires = "recursive")]
+    pub max_depth: Option<usize>,
+
+    /// Descend into symlinked directories when processing recursively
+    #[arg(long, requires = "recursive")]
+    pub follow_symlinks: bool,
+
+    /// Include hidden files and directories when processing recursively
+    #[arg(long, requires = "recursive")]
+    pub hidden: bool,
+
+    /// Only process files with one of the given extensions, ignoring case,
+    /// e.g. jpg,png,mp4
+    #[arg(
+        long,
+        visible_alias = "include-ext",
+        value_name = "EXT",
+        value_delimiter = ','
+    )]
+    pub extensions: Vec<String>,
+
+    /// Don't process files with one of the given extensions, ignoring case,
+    /// even if included
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    pub exclude_ext: Vec<String>,
+
+    /// Also read the paths to process from the standard input, one per line
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Separate the paths read from the standard input with NUL characters
+    /// instead of newlines, e.g. for `find -print0`
+    #[arg(short = '0', long, requires = "stdin")]
+    pub null: bool,
+
+    /// Separate the records of the machine outputs, like preview, with NUL
+    /// characters instead of newlines, e.g. for `xargs -0`
+    #[arg(long)]
+    pub print0: bool,
+
     /// Paths to process
     pub paths: Vec<PathBuf>,
 }
@@ -86,6 +415,21 @@ impl Cli {
     }
 }
 
+/// Parse a YYYY-MM-DD date
+fn parse_day(string: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(string, "%Y-%m-%d")
+        .map_err(|error| format!("{} (expected YYYY-MM-DD)", error))
+}
+
+/// Parse a YYYY-MM-DD date as midnight local time
+fn parse_date(string: &str) -> Result<DateTime<Local>, String> {
+    let date = parse_day(string)?;
+
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|date_time| Local.from_local_datetime(&date_time).earliest())
+        .ok_or_else(|| format!("{} does not exist in local time", string))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +449,296 @@ mod tests {
         let args = vec!["arg0", "--no-time"];
         assert_eq!(Some(false), Cli::parse_from(&args).time());
     }
+
+    #[test]
+    fn config() {
+        assert!(Cli::parse_from(["arg0"]).config.is_empty());
+        assert_eq!(
+            vec![PathBuf::from("base"), PathBuf::from("override")],
+            Cli::parse_from(["arg0", "-C", "base", "--config=override"]).config
+        );
+    }
+
+    #[test]
+    fn transforms() {
+        let args = vec!["arg0"];
+        assert!(Cli::parse_from(&args).transforms.is_empty());
+
+        let args = vec!["arg0", "-t", "slugify", "--transform=append=-foo"];
+        assert_eq!(
+            vec![Transform::Slugify, Transform::Append(String::from("-foo"))],
+            Cli::parse_from(&args).transforms
+        );
+
+        let args = vec!["arg0", "--transform", "foo"];
+        assert!(Cli::try_parse_from(&args).is_err());
+    }
+
+    #[test]
+    fn default_rescue() {
+        assert!(Cli::parse_from(["arg0"]).default_rescue.is_none());
+        assert!(matches!(
+            Cli::parse_from(["arg0", "--default-rescue", "created"])
+                .default_rescue,
+            Some(Rescue::Created)
+        ));
+        assert!(Cli::try_parse_from(["arg0", "--default-rescue=foo"]).is_err());
+    }
+
+    #[test]
+    fn on_conflict() {
+        assert_eq!(OnConflict::Refuse, Cli::parse_from(["arg0"]).on_conflict);
+        assert_eq!(
+            OnConflict::Suffix,
+            Cli::parse_from(["arg0", "--on-conflict=suffix"]).on_conflict
+        );
+    }
+
+    #[test]
+    fn output_dir() {
+        assert!(Cli::parse_from(["arg0"]).output_dir.is_none());
+        assert_eq!(
+            Some(PathBuf::from("out")),
+            Cli::parse_from(["arg0", "--output-dir", "out"]).output_dir
+        );
+    }
+
+    #[test]
+    fn organize() {
+        assert!(Cli::parse_from(["arg0"]).organize.is_none());
+        assert_eq!(
+            Some(Organize::Ym),
+            Cli::parse_from(["arg0", "--organize=ym"]).organize
+        );
+        assert!(Cli::try_parse_from(["arg0", "--organize=md"]).is_err());
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 20).unwrap();
+        assert_eq!(PathBuf::from("2024/01/20"), Organize::Ymd.subdir(&date));
+        assert_eq!(PathBuf::from("2024/01"), Organize::Ym.subdir(&date));
+        assert_eq!(PathBuf::from("2024"), Organize::Y.subdir(&date));
+    }
+
+    #[test]
+    fn touch() {
+        assert_eq!(Touch::None, Cli::parse_from(["arg0"]).touch);
+        assert_eq!(
+            Touch::Modified,
+            Cli::parse_from(["arg0", "--touch=modified"]).touch
+        );
+    }
+
+    #[test]
+    fn undo() {
+        assert!(Cli::parse_from(["arg0"]).command.is_none());
+        assert!(matches!(
+            Cli::parse_from(["arg0", "undo"]).command,
+            Some(Command::Undo)
+        ));
+
+        let cli = Cli::parse_from(["arg0", "foo", "undo"]);
+        assert!(cli.command.is_none());
+        assert_eq!(
+            vec![PathBuf::from("foo"), PathBuf::from("undo")],
+            cli.paths
+        );
+    }
+
+    #[test]
+    fn list_matchers() {
+        assert!(matches!(
+            Cli::parse_from(["arg0", "list-matchers"]).command,
+            Some(Command::ListMatchers)
+        ));
+    }
+
+    #[test]
+    fn check_config() {
+        assert!(matches!(
+            Cli::parse_from(["arg0", "check-config"]).command,
+            Some(Command::CheckConfig)
+        ));
+    }
+
+    #[test]
+    fn preview() {
+        let cli = Cli::parse_from(["arg0", "--today", "preview", "foo", "bar"]);
+        assert!(cli.today);
+        assert!(cli.paths.is_empty());
+        assert!(matches!(
+            cli.command,
+            Some(Command::Preview { paths }) if paths == [PathBuf::from("foo"), PathBuf::from("bar")]
+        ));
+    }
+
+    #[test]
+    fn recursive() {
+        let cli = Cli::parse_from(["arg0"]);
+        assert!(!cli.recursive);
+        assert!(cli.max_depth.is_none());
+
+        let cli = Cli::parse_from(["arg0", "-r", "--max-depth=2", "--hidden"]);
+        assert!(cli.recursive);
+        assert_eq!(Some(2), cli.max_depth);
+        assert!(cli.hidden);
+        assert!(!cli.follow_symlinks);
+
+        assert!(Cli::try_parse_from(["arg0", "--follow-symlinks"]).is_err());
+    }
+
+    #[test]
+    fn extensions() {
+        assert!(Cli::parse_from(["arg0"]).extensions.is_empty());
+        assert_eq!(
+            vec!["jpg", "png", "mp4"],
+            Cli::parse_from([
+                "arg0",
+                "--extensions",
+                "jpg,png",
+                "--extensions=mp4"
+            ])
+            .extensions
+        );
+        assert_eq!(
+            vec!["jpg", "png"],
+            Cli::parse_from([
+                "arg0",
+                "--include-ext",
+                "jpg",
+                "--include-ext=png"
+            ])
+            .extensions
+        );
+        assert_eq!(
+            vec!["txt", "log"],
+            Cli::parse_from(["arg0", "--exclude-ext", "txt,log"]).exclude_ext
+        );
+    }
+
+    #[test]
+    fn id3() {
+        let cli = Cli::parse_from(["arg0"]);
+        assert!(!cli.id3);
+        assert!(!cli.id3_name);
+
+        let cli = Cli::parse_from(["arg0", "--id3", "--id3-name"]);
+        assert!(cli.id3);
+        assert!(cli.id3_name);
+
+        assert!(Cli::try_parse_from(["arg0", "--id3-name"]).is_err());
+    }
+
+    #[test]
+    fn sample() {
+        assert!(Cli::parse_from(["arg0"]).sample.is_none());
+        assert_eq!(
+            Some(PathBuf::from("foo")),
+            Cli::parse_from(["arg0", "-n", "--sample", "foo"]).sample
+        );
+        assert!(Cli::try_parse_from(["arg0", "--sample", "foo"]).is_err());
+    }
+
+    #[test]
+    fn date() {
+        assert!(Cli::parse_from(["arg0"]).date.is_none());
+        assert_eq!(
+            Local.with_ymd_and_hms(2024, 1, 20, 0, 0, 0).earliest(),
+            Cli::parse_from(["arg0", "--date", "2024-01-20"]).date
+        );
+        assert!(Cli::try_parse_from(["arg0", "--date", "2024-13-20"]).is_err());
+        assert!(Cli::try_parse_from(["arg0", "--date", "yesterday"]).is_err());
+    }
+
+    #[test]
+    fn prefix_separator() {
+        assert!(Cli::parse_from(["arg0"]).prefix_separator.is_none());
+        assert_eq!(
+            Some(String::from("__")),
+            Cli::parse_from(["arg0", "--prefix-separator=__"]).prefix_separator
+        );
+    }
+
+    #[test]
+    fn position() {
+        assert!(Cli::parse_from(["arg0"]).position.is_none());
+        assert_eq!(
+            Some(Position::Suffix),
+            Cli::parse_from(["arg0", "--position=suffix"]).position
+        );
+        assert!(Cli::try_parse_from(["arg0", "--position=middle"]).is_err());
+    }
+
+    #[test]
+    fn normalize() {
+        assert!(Cli::parse_from(["arg0"]).normalize.is_none());
+        assert_eq!(
+            Some(Normalization::Nfd),
+            Cli::parse_from(["arg0", "--normalize=nfd"]).normalize
+        );
+        assert!(Cli::try_parse_from(["arg0", "--normalize=nfkc"]).is_err());
+    }
+
+    #[test]
+    fn sidecar() {
+        assert!(!Cli::parse_from(["arg0"]).sidecar);
+        assert!(Cli::parse_from(["arg0", "--sidecar"]).sidecar);
+    }
+
+    #[test]
+    fn since_until() {
+        let cli = Cli::parse_from(["arg0"]);
+        assert!(cli.since.is_none());
+        assert!(cli.until.is_none());
+
+        let cli = Cli::parse_from([
+            "arg0",
+            "--since",
+            "2024-01-15",
+            "--until=2024-01-25",
+        ]);
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 1, 15), cli.since);
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 1, 25), cli.until);
+        assert!(Cli::try_parse_from(["arg0", "--since", "2024-01"]).is_err());
+    }
+
+    #[test]
+    fn date_offset() {
+        assert!(Cli::parse_from(["arg0"]).date_offset.is_none());
+        assert_eq!(
+            Some(-1),
+            Cli::parse_from(["arg0", "--date-offset", "-1"]).date_offset
+        );
+        assert_eq!(
+            Some(2),
+            Cli::parse_from(["arg0", "--date-offset=2"]).date_offset
+        );
+    }
+
+    #[test]
+    fn customize_extension() {
+        assert!(!Cli::parse_from(["arg0"]).customize_extension);
+        assert!(
+            Cli::parse_from(["arg0", "--customize-extension"])
+                .customize_extension
+        );
+    }
+
+    #[test]
+    fn no_canonicalize() {
+        assert!(!Cli::parse_from(["arg0"]).no_canonicalize);
+        assert!(Cli::parse_from(["arg0", "--no-canonicalize"]).no_canonicalize);
+    }
+
+    #[test]
+    fn limit() {
+        assert!(Cli::parse_from(["arg0"]).limit.is_none());
+        assert_eq!(Some(2), Cli::parse_from(["arg0", "--limit", "2"]).limit);
+        assert!(Cli::try_parse_from(["arg0", "--limit=-1"]).is_err());
+    }
+
+    #[test]
+    fn dry_run() {
+        assert!(!Cli::parse_from(["arg0"]).dry_run);
+        assert!(Cli::parse_from(["arg0", "--dry-run"]).dry_run);
+        assert!(Cli::parse_from(["arg0", "-n"]).dry_run);
+    }
 }
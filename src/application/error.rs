@@ -9,6 +9,8 @@ pub enum Error {
     Io(io::Error),
     SetLogger(LogError),
     Processing(ProcessingError),
+    /// The arguments or the config are invalid
+    Config(String),
     Custom(String),
 }
 
@@ -50,7 +52,46 @@ impl fmt::Display for Error {
             Self::Io(error) => fmt::Display::fmt(&error, f),
             Self::SetLogger(error) => fmt::Display::fmt(&error, f),
             Self::Processing(error) => fmt::Display::fmt(&error, f),
+            Self::Config(error) => fmt::Display::fmt(&error, f),
             Self::Custom(error) => fmt::Display::fmt(&error, f),
         }
     }
 }
+
+impl Error {
+    /// Exit code of the program failing with this error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Processing(ProcessingError::Abort) => EXIT_ABORT,
+            Self::Config(_) => EXIT_CONFIG,
+            _ => EXIT_FAILURES,
+        }
+    }
+}
+
+/// Exit code when some paths failed to be processed, or on unexpected errors
+pub const EXIT_FAILURES: u8 = 1;
+/// Exit code when the processing was aborted
+pub const EXIT_ABORT: u8 = 2;
+/// Exit code when the arguments or the config are invalid
+pub const EXIT_CONFIG: u8 = 3;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    #[test]
+    fn exit_code() {
+        assert_eq!(
+            EXIT_ABORT,
+            Error::Processing(ProcessingError::Abort).exit_code()
+        );
+        assert_eq!(EXIT_CONFIG, Error::Config(String::from("foo")).exit_code());
+        assert_eq!(
+            EXIT_FAILURES,
+            Error::Processing(ProcessingError::Skip("foo".into())).exit_code()
+        );
+        assert_eq!(EXIT_FAILURES, Error::from("foo").exit_code());
+    }
+}
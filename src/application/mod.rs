@@ -1,10 +1,16 @@
-use crate::matcher::{Matcher, Metadata, Pattern, PredeterminedDate};
+use crate::matcher::{
+    Composite, DirName, Id3, Matcher, Metadata, Pattern, PredeterminedDate,
+    Sidecar,
+};
+use crate::processing::Outcome;
 use crate::ui;
 
 use std::boxed::Box;
+use std::path::Path;
 
 mod cli;
-pub use cli::Interactive;
+use cli::Command;
+pub use cli::{Interactive, OnConflict, Organize, Touch};
 
 mod arguments;
 use arguments::Arguments;
@@ -17,7 +23,10 @@ pub use arguments::DEFAULT_DATE_FORMAT;
 pub use arguments::DEFAULT_DATE_TIME_FORMAT;
 
 mod error;
-pub use error::Error;
+pub use error::{Error, EXIT_ABORT, EXIT_CONFIG, EXIT_FAILURES};
+
+pub(crate) mod walk;
+pub use walk::{ExtensionFilter, Walk};
 pub type Result<T> = std::result::Result<T, Error>;
 
 type LogResult = std::result::Result<(), log::SetLoggerError>;
@@ -58,10 +67,6 @@ impl Application {
         self.setup_log()?;
         log::set_max_level(self.arguments.log_level_filter());
 
-        while let Some(error) = self.arguments.init_errors.pop_front() {
-            log::info!("Init error: {}", error);
-        }
-
         log::debug!("Arguments: {:?}", self.arguments);
 
         let format = self.arguments.default_format().to_string();
@@ -69,14 +74,36 @@ impl Application {
         if self.arguments.today() {
             self.add_matcher(PredeterminedDate::new(format.as_str()));
         }
+        if let Some(date_time) = self.arguments.date() {
+            self.add_matcher(PredeterminedDate::with_date_time(
+                date_time,
+                format.as_str(),
+            ));
+        }
+        if let Some(offset) = self.arguments.date_offset() {
+            self.add_matcher(PredeterminedDate::with_offset(
+                format.as_str(),
+                offset,
+            ));
+        }
+
+        let patterns = self.setup_patterns(format.as_str());
 
-        if let Some(patterns) = self.arguments.patterns.take() {
-            patterns.iter().for_each(|(name, value)| {
+        // Composite matchers always provide a time, like time patterns, so
+        // they default to the date-time format whatever the global `time`
+        if let Some(composites) = self.arguments.composites.take() {
+            let format = self.arguments.format(true).to_string();
+            composites.iter().for_each(|(name, value)| {
                 if let toml::Value::Table(table) = value {
-                    if let Some(pattern) =
-                        Pattern::deserialize(name, table, format.as_str())
-                    {
-                        self.add_pattern_matcher(pattern);
+                    if let Some(composite) = Composite::deserialize(
+                        name,
+                        table,
+                        &patterns,
+                        format.as_str(),
+                    ) {
+                        if !RESERVED_MATCHER_NAMES.contains(&name.as_str()) {
+                            self.add_matcher(composite);
+                        }
                     }
                 }
             });
@@ -88,23 +115,319 @@ impl Application {
         if self.arguments.metadata().modified() {
             self.add_matcher(Metadata::new_modified(format.as_str()));
         }
+        if let Some(tag_name) = self.arguments.id3() {
+            self.add_matcher(Id3::new(format.as_str(), tag_name));
+        }
+        #[cfg(feature = "pdf")]
+        if self.arguments.pdf_meta() {
+            self.add_matcher(crate::matcher::PdfMeta::new(format.as_str()));
+        }
+        #[cfg(feature = "git")]
+        if self.arguments.git_date() {
+            self.add_matcher(crate::matcher::GitDate::new(format.as_str()));
+        }
+        if let Some((json_field, xmp_field)) = self.arguments.sidecar() {
+            self.add_matcher(Sidecar::new(
+                format.as_str(),
+                json_field,
+                xmp_field,
+            ));
+        }
+        if let Some(regex) = self.arguments.dir_date() {
+            match DirName::new(format.as_str(), regex) {
+                Some(matcher) => self.add_matcher(
+                    matcher.with_date_bounds(self.arguments.date_bounds()),
+                ),
+                None => self.arguments.init_errors.push_back(
+                    format!("Invalid dir_name regex: {}", regex).into(),
+                ),
+            }
+        }
+
+        // Stable sort, so matchers of the same priority keep their order
+        self.matchers.sort_by_key(|matcher| matcher.priority());
+
+        self.check_auto_accept();
+
+        // check-config reports the errors itself
+        if !matches!(self.arguments.command(), Some(Command::CheckConfig)) {
+            while let Some(error) = self.arguments.init_errors.pop_front() {
+                log::info!("Init error: {}", error);
+            }
+        }
 
         Ok(())
     }
 
-    pub fn run(&mut self) -> Result<()> {
+    /// Add the configured pattern matchers, returning all the valid patterns
+    /// for composites to use
+    ///
+    /// Patterns unable to determine a date are reported and left aside,
+    /// unless they are the time source of a composite, in which case they are
+    /// only handed to the composites
+    ///
+    /// Patterns with their own `time` setting default to the date-time or date
+    /// format accordingly, the others follow the global `time`
+    fn setup_patterns(&mut self, format: &str) -> Vec<Pattern> {
+        let mut patterns = Vec::<Pattern>::default();
+        let Some(table) = self.arguments.patterns.take() else {
+            return patterns;
+        };
+
+        let time_sources: Vec<String> = self
+            .arguments
+            .composites
+            .iter()
+            .flat_map(|composites| composites.values())
+            .filter_map(|composite| composite.get("time_source"))
+            .filter_map(toml::Value::as_str)
+            .map(String::from)
+            .collect();
+        let mut errors = Vec::<Error>::default();
+
+        for (name, value) in &table {
+            let pattern = match value {
+                toml::Value::Table(table) => Pattern::builder()
+                    .case(self.arguments.case())
+                    .time(self.arguments.time())
+                    .date_bounds(self.arguments.date_bounds())
+                    .deserialize(
+                        name,
+                        table,
+                        match table.get("time").and_then(toml::Value::as_bool) {
+                            Some(time) => self.arguments.format(time),
+                            None => format,
+                        },
+                    ),
+                _ => Err(String::from("not a table")),
+            };
+            let pattern = match pattern {
+                Ok(pattern) => pattern,
+                Err(error) => {
+                    errors.push(
+                        format!("Invalid pattern {}: {}", name, error).into(),
+                    );
+                    continue;
+                }
+            };
+
+            let missing = pattern.missing_date_captures();
+            if !missing.is_empty() {
+                match time_sources.contains(name) {
+                    true => patterns.push(pattern),
+                    false => errors.push(
+                        format!(
+                            "Invalid pattern {}: no {} capture",
+                            name,
+                            missing.join(", ")
+                        )
+                        .into(),
+                    ),
+                }
+                continue;
+            }
+
+            patterns.push(pattern.clone());
+            self.add_pattern_matcher(pattern);
+        }
+
+        self.arguments.init_errors.extend(errors);
+        patterns
+    }
+
+    /// Report the matchers to auto-accept that don't exist
+    fn check_auto_accept(&mut self) {
+        for name in &self.arguments.auto_accept {
+            if !self.matchers.iter().any(|m| m.name() == name) {
+                self.arguments.init_errors.push_back(
+                    format!("Unknown matcher to auto-accept: {}", name).into(),
+                );
+            }
+        }
+    }
+
+    pub fn run(&mut self) -> Result<Outcome> {
+        match self.arguments.command() {
+            Some(Command::Undo) => {
+                self.undo()?;
+                return Ok(Outcome::Success);
+            }
+            Some(Command::CheckConfig) => {
+                self.check_config()?;
+                return Ok(Outcome::Success);
+            }
+            Some(Command::ListMatchers) => {
+                self.list_matchers();
+                return Ok(Outcome::Success);
+            }
+            Some(Command::Preview { .. }) => {
+                self.preview();
+                return Ok(Outcome::Success);
+            }
+            None => {}
+        }
+
+        if let Some(dir) = self.arguments.sample() {
+            self.sample(dir);
+            return Ok(Outcome::Success);
+        }
+
         log::debug!(
             "Matchers: {:?}",
             self.matchers.iter().map(|m| m.name()).collect::<Vec<_>>()
         );
         log::debug!("Paths: {:?}", self.arguments.paths());
-        self.ui.process(&self.matchers, self.arguments.paths())
+        if self.arguments.explain() {
+            self.explain();
+        }
+        self.ui.process(
+            &self.matchers,
+            self.arguments.paths(),
+            &self.arguments.processing_options(),
+        )
     }
 
-    pub(crate) fn add_pattern_matcher(&mut self, pattern: Pattern) {
-        if pattern.time() == self.arguments.time()
-            && !RESERVED_MATCHER_NAMES.contains(&pattern.name())
+    /// Print how many files of the directory each matcher would claim
+    fn sample(&self, dir: &Path) {
+        use crate::processing::plan;
+
+        let paths = self.arguments.extension_filter().apply(
+            walk::Walk::default().expand(&[dir.to_path_buf()]),
+            self.arguments.extensions(),
+        );
+        let counts = plan::count(
+            &self.matchers,
+            &paths,
+            &self.arguments.naming(),
+            &self.arguments.replacement_settings(),
+        );
+        let width = counts.iter().map(|(name, _)| name.len()).max();
+
+        println!("Sample of {} files in {}:", paths.len(), dir.display());
+        for (name, count) in counts {
+            println!(
+                "  {:width$}  {}",
+                name,
+                count,
+                width = width.unwrap_or(0)
+            );
+        }
+    }
+
+    /// Print the new path and the matcher of every path as a TSV table,
+    /// leaving the new path empty and the matcher as `-` when unmatched
+    fn preview(&self) {
+        use crate::processing::plan;
+
+        let paths = self.arguments.paths();
+        let separator = self.arguments.record_separator();
+        let options = self.arguments.processing_options();
+
+        print!("old_path\tnew_path\tmatcher_name{}", separator);
+        for (path, claim) in
+            paths
+                .iter()
+                .zip(plan::preview(&self.matchers, paths, &options))
         {
+            match claim {
+                Some((new_path, name)) => print!(
+                    "{}\t{}\t{}{}",
+                    path.display(),
+                    new_path.display(),
+                    name,
+                    separator
+                ),
+                None => print!("{}\t\t-{}", path.display(), separator),
+            }
+        }
+    }
+
+    /// Log the outcome of every matcher for each path
+    fn explain(&self) {
+        use crate::processing::plan;
+
+        let naming = self.arguments.naming();
+        let settings = self.arguments.replacement_settings();
+
+        for path in self.arguments.paths() {
+            log::info!("Explaining {:?}", path);
+            for (name, result) in
+                plan::explain(&self.matchers, path, &naming, &settings)
+            {
+                match result {
+                    Ok(replacement) => log::info!(
+                        "  {}: {}",
+                        name,
+                        replacement.new_file_name()
+                    ),
+                    Err(reason) => log::info!("  {}: {}", name, reason),
+                }
+            }
+        }
+    }
+
+    /// Print the errors found while loading the config, failing if any
+    fn check_config(&mut self) -> Result<()> {
+        let errors: Vec<Error> = self.arguments.init_errors.drain(..).collect();
+
+        for error in &errors {
+            println!("error: {}", error);
+        }
+
+        if errors.is_empty() {
+            println!("Config OK: {} matchers", self.matchers.len());
+            Ok(())
+        } else {
+            Err(Error::Config(format!("{} config error(s)", errors.len())))
+        }
+    }
+
+    /// Print the matchers in the order they are tried, with their settings
+    fn list_matchers(&self) {
+        for matcher in &self.matchers {
+            let auto_accept = matcher.auto_accept()
+                || self
+                    .arguments
+                    .auto_accept
+                    .iter()
+                    .any(|n| n == matcher.name());
+
+            println!("{} (priority {})", matcher.name(), matcher.priority());
+            println!("  format:      {}", matcher.date_format());
+            println!("  delimiter:   {:?}", matcher.delimiter());
+            println!("  auto-accept: {}", auto_accept);
+            if let Some(source) = matcher.source() {
+                println!("  regex:       {}", source.trim());
+            }
+        }
+    }
+
+    /// Revert the renames recorded in the most recent journal
+    fn undo(&self) -> Result<()> {
+        use crate::fs::RealFs;
+        use crate::processing::{Journal, LogReporter};
+
+        let Some(dir) = self.arguments.journal_dir() else {
+            return Err("Unable to locate the journal directory".into());
+        };
+
+        match Journal::latest(&dir)? {
+            Some(journal) => {
+                log::info!("Undoing renames from {:?}", journal.path());
+                journal.undo(
+                    &RealFs,
+                    &LogReporter::default(),
+                    &self.arguments.replacement_settings(),
+                )?;
+            }
+            None => log::warn!("No journal found in {:?}", dir),
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn add_pattern_matcher(&mut self, pattern: Pattern) {
+        if !RESERVED_MATCHER_NAMES.contains(&pattern.name()) {
             self.add_matcher(pattern);
         }
     }
@@ -144,10 +467,19 @@ impl Application {
     }
 }
 
-const RESERVED_MATCHER_NAMES: [&str; 3] = [
+const RESERVED_MATCHER_NAMES: &[&str] = &[
     crate::matcher::predetermined_date::TODAY,
+    crate::matcher::predetermined_date::OFFSET,
+    crate::matcher::predetermined_date::FIXED,
     crate::matcher::metadata::CREATED,
     crate::matcher::metadata::MODIFIED,
+    crate::matcher::id3::ID3,
+    crate::matcher::dir_name::DIR_NAME,
+    crate::matcher::sidecar::SIDECAR,
+    #[cfg(feature = "pdf")]
+    crate::matcher::pdf_meta::PDF_META,
+    #[cfg(feature = "git")]
+    crate::matcher::git_date::GIT_DATE,
 ];
 
 #[cfg(test)]
@@ -155,6 +487,8 @@ mod tests {
     use super::*;
     use crate::test::{assert_eq, test};
 
+    use std::path::PathBuf;
+
     #[test]
     fn add_matcher_with_same_name() {
         let mut app = Application::default();
@@ -167,6 +501,111 @@ mod tests {
         assert_eq!(1, app.matchers.len());
     }
 
+    #[test]
+    fn check_auto_accept() {
+        let mut app = Application::default();
+        app.add_pattern_matcher(
+            Pattern::builder().regex(".").name("foo").build().unwrap(),
+        );
+        app.arguments.auto_accept = vec!["foo".into(), "bar".into()];
+
+        app.check_auto_accept();
+
+        assert_eq!(1, app.arguments.init_errors.len());
+        assert_eq!(
+            "Unknown matcher to auto-accept: bar",
+            app.arguments.init_errors[0].to_string()
+        );
+    }
+
+    #[test]
+    fn setup_patterns() {
+        let config: toml::Table = toml::from_str(
+            r#"
+            [patterns.ymd]
+            regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+
+            [patterns.md]
+            regex = '(?<start>.+)\s+(?<month>\d{2})(?<day>\d{2})'
+
+            [patterns.broken]
+            regex = '(('
+
+            [patterns.time]
+            regex = '(?<start>.+)_(?<hour>\d{2})(?<min>\d{2})(?<sec>\d{2})'
+            time = true
+
+            [composite.modified_time]
+            date_source = "modified"
+            time_source = "time"
+            "#,
+        )
+        .unwrap();
+
+        let mut app = Application::default();
+        app.arguments.patterns = config["patterns"].as_table().cloned();
+        app.arguments.composites = config["composite"].as_table().cloned();
+
+        let patterns = app.setup_patterns("%Y-%m-%d");
+
+        assert_eq!(
+            vec!["ymd", "time"],
+            patterns.iter().map(|p| p.name()).collect::<Vec<_>>()
+        );
+        // Neither the invalid patterns nor the time source match on their own
+        assert_eq!(
+            vec!["ymd"],
+            app.matchers.iter().map(|m| m.name()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                "Invalid pattern md: no year capture",
+                "Invalid pattern broken: invalid regex",
+            ],
+            app.arguments
+                .init_errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn setup_patterns_with_and_without_time() {
+        let config: toml::Table = toml::from_str(
+            r#"
+            [patterns.ymd]
+            regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})\z'
+
+            [patterns.ymd_hms]
+            regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})_(?<hour>\d{2})(?<min>\d{2})(?<sec>\d{2})'
+            time = true
+            "#,
+        )
+        .unwrap();
+
+        let mut app = Application::default();
+        app.arguments.patterns = config["patterns"].as_table().cloned();
+        app.setup_patterns(DEFAULT_DATE_FORMAT);
+
+        assert_eq!(
+            vec!["ymd", "ymd_hms"],
+            app.matchers.iter().map(|m| m.name()).collect::<Vec<_>>()
+        );
+
+        let check = |name: &str| {
+            app.matchers
+                .iter()
+                .find_map(|m| m.check(&PathBuf::from(name)))
+                .map(|replacement| replacement.new_file_stem)
+        };
+        assert_eq!(Some("2024-01-20 foo".into()), check("foo 20240120"));
+        assert_eq!(
+            Some("2024-01-20 10h30m00 foo".into()),
+            check("foo 20240120_103000")
+        );
+    }
+
     mod add_pattern_matcher {
         use super::*;
         use crate::test::{assert_eq, test};
@@ -191,10 +630,10 @@ mod tests {
         }
 
         #[test]
-        fn refuse_different_time_values() {
+        fn accept_different_time_values() {
             let mut app = Application::default();
 
-            // Test failure cases
+            // Patterns are added whatever their time value
             app.arguments.time = false;
             app.add_pattern_matcher(
                 Pattern::builder()
@@ -204,20 +643,20 @@ mod tests {
                     .build()
                     .unwrap(),
             );
-            assert_eq!(0, app.matchers.len());
+            assert_eq!(1, app.matchers.len());
 
             app.arguments.time = true;
             app.add_pattern_matcher(
                 Pattern::builder()
                     .regex(".")
-                    .name("foo")
+                    .name("bar")
                     .time(false)
                     .build()
                     .unwrap(),
             );
-            assert_eq!(0, app.matchers.len());
+            assert_eq!(2, app.matchers.len());
 
-            // And yet, it works
+            // But only once
             app.add_pattern_matcher(
                 Pattern::builder()
                     .regex(".")
@@ -226,13 +665,13 @@ mod tests {
                     .build()
                     .unwrap(),
             );
-            assert_eq!(1, app.matchers.len());
+            assert_eq!(2, app.matchers.len());
         }
     }
 
     mod setup {
         use super::*;
-        use crate::test::test;
+        use crate::test::{assert_eq, test};
         use mockall::mock;
 
         use std::path::PathBuf;
@@ -250,7 +689,8 @@ mod tests {
                     &mut self,
                     _matchers: &[Box<dyn crate::matcher::Matcher>],
                     _paths: &[PathBuf],
-                ) -> Result<()>;
+                    _options: &crate::processing::Options,
+                ) -> Result<Outcome>;
             }
         }
 
@@ -260,14 +700,16 @@ mod tests {
             let mut ui = MockInterface::new();
 
             ui.expect_setup_logger().times(1).returning(|_| Ok(()));
-            ui.expect_process().times(1).returning(|_, _| Ok(()));
+            ui.expect_process()
+                .times(1)
+                .returning(|_, _, _| Ok(Outcome::Success));
 
             app.setup_with_ui(Box::new(ui)).unwrap();
 
             // by default, no matcher is added
             assert!(app.matchers.is_empty());
 
-            app.run().unwrap();
+            assert_eq!(Outcome::Success, app.run().unwrap());
         }
 
         #[test]
@@ -285,6 +727,115 @@ mod tests {
             assert!(app.matchers.iter().any(|m| m.name() == TODAY));
         }
 
+        #[test]
+        fn setup_offset_matcher() {
+            let mut app = Application::default();
+            let mut ui = MockInterface::new();
+
+            ui.expect_setup_logger().times(1).returning(|_| Ok(()));
+
+            use crate::matcher::predetermined_date::OFFSET;
+            app.arguments.date_offset = Some(-1);
+
+            app.setup_with_ui(Box::new(ui)).unwrap();
+
+            assert!(app.matchers.iter().any(|m| m.name() == OFFSET));
+        }
+
+        #[test]
+        fn setup_composite_matcher() {
+            let config: toml::Table = toml::from_str(
+                r#"
+                [patterns.time]
+                regex = '(?<hour>\d{2})(?<min>\d{2})'
+
+                [composite.modified_time]
+                date_source = "modified"
+                time_source = "time"
+                "#,
+            )
+            .unwrap();
+
+            for time in [true, false] {
+                let mut app = Application::default();
+                let mut ui = MockInterface::new();
+
+                ui.expect_setup_logger().times(1).returning(|_| Ok(()));
+
+                app.arguments.time = time;
+                app.arguments.patterns = config["patterns"].as_table().cloned();
+                app.arguments.composites =
+                    config["composite"].as_table().cloned();
+
+                app.setup_with_ui(Box::new(ui)).unwrap();
+
+                // Used whatever the global time, providing its own
+                assert!(app
+                    .matchers
+                    .iter()
+                    .any(|m| m.name() == "modified_time"));
+            }
+        }
+
+        #[test]
+        fn setup_matchers_priority() {
+            let config: toml::Table = toml::from_str(
+                r#"
+                [ymd]
+                regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+
+                [ydm]
+                regex = '(?<start>.+)\s+(?<year>\d{4})(?<day>\d{2})(?<month>\d{2})'
+                priority = -1
+
+                [ymd_too]
+                regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+                "#,
+            )
+            .unwrap();
+
+            let mut app = Application::default();
+            let mut ui = MockInterface::new();
+
+            ui.expect_setup_logger().times(1).returning(|_| Ok(()));
+
+            app.arguments.today = true;
+            app.arguments.patterns = Some(config);
+
+            app.setup_with_ui(Box::new(ui)).unwrap();
+
+            use crate::matcher::predetermined_date::TODAY;
+            assert_eq!(
+                vec!["ydm", TODAY, "ymd", "ymd_too"],
+                app.matchers.iter().map(|m| m.name()).collect::<Vec<_>>()
+            );
+
+            // The higher priority pattern produces the replacement
+            let path = PathBuf::from("foo 20241201");
+            let replacement = app
+                .matchers
+                .iter()
+                .find_map(|matcher| matcher.check(&path))
+                .unwrap();
+            assert_eq!("2024-01-12 foo", replacement.new_file_stem);
+        }
+
+        #[test]
+        fn setup_dir_name_matcher() {
+            use crate::matcher::dir_name::DIR_NAME;
+
+            let mut app = Application::default();
+            let mut ui = MockInterface::new();
+
+            ui.expect_setup_logger().times(1).returning(|_| Ok(()));
+
+            app.arguments.dir_date = true;
+
+            app.setup_with_ui(Box::new(ui)).unwrap();
+
+            assert!(app.matchers.iter().any(|m| m.name() == DIR_NAME));
+        }
+
         #[test]
         fn setup_created_matcher() {
             let mut app = Application::default();
@@ -0,0 +1,445 @@
+use crate::replacement::{Extensions, Replacement, Settings};
+
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+
+/// Expand directory arguments into the files they contain
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Walk {
+    /// Maximum depth to descend into, files directly inside a given directory
+    /// being at depth 1
+    pub max_depth: Option<usize>,
+    /// Descend into symlinked directories
+    pub follow_symlinks: bool,
+    /// Include files and directories whose name starts with a dot
+    pub hidden: bool,
+}
+
+impl Walk {
+    /// Replace every directory in paths by the files it contains
+    ///
+    /// Other paths are kept as is, and directories themselves are never part
+    /// of the result
+    pub fn expand(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let mut expanded = Vec::new();
+        let mut visited = HashSet::new();
+
+        for path in paths {
+            if path.is_dir() {
+                self.walk(path, 0, &mut visited, &mut expanded);
+            } else {
+                expanded.push(path.clone());
+            }
+        }
+
+        expanded
+    }
+
+    fn walk(
+        &self,
+        dir: &Path,
+        depth: usize,
+        visited: &mut HashSet<PathBuf>,
+        expanded: &mut Vec<PathBuf>,
+    ) {
+        if self.max_depth.is_some_and(|max| depth >= max) {
+            return;
+        }
+
+        // Avoid looping forever on symlinks pointing to a parent directory
+        if let Ok(canonical) = dir.canonicalize() {
+            if !visited.insert(canonical) {
+                return;
+            }
+        }
+
+        let mut entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| match entry {
+                    Ok(entry) => Some(entry.path()),
+                    Err(e) => {
+                        log::warn!("Unable to read entry in {:?}: {}", dir, e);
+                        None
+                    }
+                })
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                log::warn!("Unable to read directory {:?}: {}", dir, e);
+                return;
+            }
+        };
+        entries.sort();
+
+        for path in entries {
+            if !self.hidden && is_hidden(&path) {
+                continue;
+            }
+
+            let is_symlink = path.is_symlink();
+            if path.is_dir() {
+                if !is_symlink || self.follow_symlinks {
+                    self.walk(&path, depth + 1, visited, expanded);
+                }
+            } else {
+                expanded.push(path);
+            }
+        }
+    }
+}
+
+/// Filter the paths on their extension, as split by Extensions, ignoring case
+#[derive(Debug, Default, Clone)]
+pub struct ExtensionFilter {
+    /// Keep only the paths with one of these extensions, all if empty
+    pub include: Vec<String>,
+    /// Drop the paths with one of these extensions, after include
+    pub exclude: Vec<String>,
+}
+
+impl ExtensionFilter {
+    /// Check if a path with the given extension should be kept
+    pub fn allows(&self, ext: &str) -> bool {
+        let listed = |list: &[String]| {
+            list.iter().any(|listed| {
+                listed
+                    .strip_prefix('.')
+                    .unwrap_or(listed)
+                    .eq_ignore_ascii_case(ext)
+            })
+        };
+
+        (self.include.is_empty() || listed(&self.include))
+            && !listed(&self.exclude)
+    }
+
+    /// Keep only the paths allowed by the filter
+    pub fn apply(
+        &self,
+        paths: Vec<PathBuf>,
+        extensions: &Extensions,
+    ) -> Vec<PathBuf> {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return paths;
+        }
+
+        // Only the extension matters, so the parents are kept as given
+        let settings = Settings {
+            extensions: extensions.clone(),
+            canonicalize: false,
+            ..Settings::default()
+        };
+
+        paths
+            .into_iter()
+            .filter(|path| {
+                let ext = Replacement::try_from_with(path, &settings)
+                    .map(|replacement| replacement.extension)
+                    .unwrap_or_default();
+                self.allows(&ext)
+            })
+            .collect()
+    }
+}
+
+/// Read the paths separated by NUL characters if null is true, by newlines
+/// otherwise, ignoring empty entries
+pub fn read_paths<R: BufRead>(
+    reader: R,
+    null: bool,
+) -> io::Result<Vec<PathBuf>> {
+    let separator = if null { b'\0' } else { b'\n' };
+    let mut paths = Vec::new();
+
+    for entry in reader.split(separator) {
+        let mut entry = entry?;
+        if !null && entry.last() == Some(&b'\r') {
+            entry.pop();
+        }
+        if !entry.is_empty() {
+            paths.push(path_from_bytes(entry)?);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Path made of the given bytes, whatever they are
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> io::Result<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    Ok(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+/// Path made of the given bytes, which have to be valid UTF-8
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> io::Result<PathBuf> {
+    String::from_utf8(bytes)
+        .map(PathBuf::from)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Remove the paths given more than once, keeping the first occurrence
+///
+/// Paths are compared with their directory resolved, so `dir/foo` and
+/// `dir/./foo` are the same path, but two symlinks to the same file are not
+pub fn dedup(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+
+    paths
+        .into_iter()
+        .filter(|path| {
+            if !seen.insert(resolved(path)) {
+                log::debug!("Ignoring duplicate path {:?}", path);
+                return false;
+            }
+            true
+        })
+        .collect()
+}
+
+/// Path with its directory canonicalized, if possible
+fn resolved(path: &Path) -> PathBuf {
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name())
+    else {
+        return path.to_path_buf();
+    };
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+
+    parent
+        .canonicalize()
+        .map(|parent| parent.join(file_name))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    fn relative(temp: &TempDir, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths
+            .into_iter()
+            .map(|path| path.strip_prefix(temp.path()).unwrap().to_path_buf())
+            .collect()
+    }
+
+    fn fixture(temp: &TempDir) {
+        temp.existing_child("a").unwrap();
+        temp.existing_child(".hidden").unwrap();
+        temp.existing_child("sub/b").unwrap();
+        temp.existing_child("sub/deeper/c").unwrap();
+        temp.existing_child(".hidden_dir/d").unwrap();
+    }
+
+    #[test]
+    fn expand() {
+        with_temp_dir(|temp| {
+            fixture(temp);
+            let file = PathBuf::from("not/a/directory");
+            let paths = [temp.path().to_path_buf(), file.clone()];
+
+            let expanded = Walk::default().expand(&paths);
+            assert_eq!(Some(&file), expanded.last());
+            assert_eq!(
+                vec![
+                    PathBuf::from("a"),
+                    PathBuf::from("sub/b"),
+                    PathBuf::from("sub/deeper/c"),
+                ],
+                relative(temp, expanded[..3].to_vec())
+            );
+        });
+    }
+
+    #[test]
+    fn max_depth() {
+        with_temp_dir(|temp| {
+            fixture(temp);
+            let paths = [temp.path().to_path_buf()];
+
+            let walk = Walk {
+                max_depth: Some(1),
+                ..Walk::default()
+            };
+            assert_eq!(
+                vec![PathBuf::from("a")],
+                relative(temp, walk.expand(&paths))
+            );
+
+            let walk = Walk {
+                max_depth: Some(2),
+                ..Walk::default()
+            };
+            assert_eq!(
+                vec![PathBuf::from("a"), PathBuf::from("sub/b")],
+                relative(temp, walk.expand(&paths))
+            );
+        });
+    }
+
+    #[test]
+    fn hidden() {
+        with_temp_dir(|temp| {
+            fixture(temp);
+            let walk = Walk {
+                hidden: true,
+                ..Walk::default()
+            };
+
+            assert_eq!(
+                vec![
+                    PathBuf::from(".hidden"),
+                    PathBuf::from(".hidden_dir/d"),
+                    PathBuf::from("a"),
+                    PathBuf::from("sub/b"),
+                    PathBuf::from("sub/deeper/c"),
+                ],
+                relative(temp, walk.expand(&[temp.path().to_path_buf()]))
+            );
+        });
+    }
+
+    #[test]
+    fn extension_filter() {
+        let paths = ["a.jpg", "b.PNG", "c.txt", "d", "e.mp4.txt", "f.mp4"]
+            .map(PathBuf::from)
+            .to_vec();
+        let extensions = Extensions::default();
+        let filter = |include: &[&str], exclude: &[&str]| ExtensionFilter {
+            include: include.iter().map(|ext| ext.to_string()).collect(),
+            exclude: exclude.iter().map(|ext| ext.to_string()).collect(),
+        };
+
+        assert_eq!(
+            paths,
+            ExtensionFilter::default().apply(paths.clone(), &extensions)
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("a.jpg"),
+                PathBuf::from("b.PNG"),
+                PathBuf::from("f.mp4")
+            ],
+            filter(&["jpg", "png", "MP4"], &[])
+                .apply(paths.clone(), &extensions)
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("a.jpg"),
+                PathBuf::from("b.PNG"),
+                PathBuf::from("d"),
+                PathBuf::from("f.mp4")
+            ],
+            filter(&[], &["TXT"]).apply(paths.clone(), &extensions)
+        );
+        // Exclude applies after include
+        assert_eq!(
+            vec![PathBuf::from("a.jpg")],
+            filter(&[".jpg", "png"], &["png"]).apply(paths, &extensions)
+        );
+    }
+
+    #[test]
+    fn extension_filter_compound() {
+        let paths = ["a.tar.gz", "b.gz"].map(PathBuf::from).to_vec();
+        let extensions = Extensions {
+            compound: vec![String::from("tar.gz")],
+            ..Extensions::default()
+        };
+        let filter = ExtensionFilter {
+            include: vec![String::from("gz")],
+            ..ExtensionFilter::default()
+        };
+
+        assert_eq!(
+            vec![PathBuf::from("b.gz")],
+            filter.apply(paths, &extensions)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follow_symlinks() {
+        with_temp_dir(|temp| {
+            temp.existing_child("dir/a").unwrap();
+            temp.existing_child("other/b").unwrap();
+            let other = temp.child("other");
+            temp.child("dir/link").symlink_to_dir(other.path()).unwrap();
+            temp.child("dir/loop").symlink_to_dir(temp.path()).unwrap();
+            let paths = [temp.child("dir").to_path_buf()];
+
+            assert_eq!(
+                vec![PathBuf::from("dir/a")],
+                relative(temp, Walk::default().expand(&paths))
+            );
+
+            let walk = Walk {
+                follow_symlinks: true,
+                ..Walk::default()
+            };
+            // other is only visited once, through dir/link, and dir itself is
+            // not visited again through dir/loop
+            assert_eq!(
+                vec![PathBuf::from("dir/a"), PathBuf::from("dir/link/b")],
+                relative(temp, walk.expand(&paths))
+            );
+        });
+    }
+
+    #[test]
+    fn read_paths() {
+        let paths = |input: &str, null| {
+            super::read_paths(input.as_bytes(), null).unwrap()
+        };
+
+        assert_eq!(
+            vec![PathBuf::from("a b"), PathBuf::from("c\nd")],
+            paths("a b\0c\nd\0", true)
+        );
+        assert_eq!(
+            vec![PathBuf::from("a b"), PathBuf::from("c"), PathBuf::from("d")],
+            paths("a b\nc\r\n\nd", false)
+        );
+        assert!(paths("", true).is_empty());
+        assert!(paths("\n", false).is_empty());
+    }
+
+    #[test]
+    fn dedup() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo").unwrap();
+            let bar = temp.existing_child("sub/bar").unwrap();
+
+            let paths = vec![
+                foo.to_path_buf(),
+                bar.to_path_buf(),
+                foo.to_path_buf(),
+                temp.path().join("sub/../foo"),
+                temp.path().join("./sub/bar"),
+                PathBuf::from("missing"),
+                PathBuf::from("missing"),
+            ];
+            assert_eq!(
+                vec![
+                    foo.to_path_buf(),
+                    bar.to_path_buf(),
+                    PathBuf::from("missing")
+                ],
+                super::dedup(paths)
+            );
+        });
+    }
+}
@@ -0,0 +1,144 @@
+use std::fs::{File, FileTimes, Metadata};
+use std::io::Result;
+use std::path::Path;
+
+/// Filesystem operations needed to process paths
+///
+/// This allows injecting failures in tests, the default implementation being
+/// RealFs which simply forwards to std::fs
+#[cfg_attr(test, mockall::automock)]
+pub trait Fs {
+    /// Rename a file, see std::fs::rename
+    fn rename(&self, from: &Path, to: &Path) -> Result<()>;
+    /// Copy a file, see std::fs::copy
+    fn copy(&self, from: &Path, to: &Path) -> Result<u64>;
+    /// Copy the access and modification times of a file to another
+    fn copy_times(&self, from: &Path, to: &Path) -> Result<()>;
+    /// Set the given times of a file, see File::set_times
+    fn set_times(&self, path: &Path, times: FileTimes) -> Result<()>;
+    /// Remove a file, see std::fs::remove_file
+    fn remove_file(&self, path: &Path) -> Result<()>;
+    /// Create a directory and its missing parents, see std::fs::create_dir_all
+    fn create_dir_all(&self, path: &Path) -> Result<()>;
+    /// Query the metadata of a path, following symlinks
+    fn metadata(&self, path: &Path) -> Result<Metadata>;
+    /// Check if a path exists, see Path::try_exists
+    fn try_exists(&self, path: &Path) -> Result<bool>;
+    /// Check if a path is a special file, i.e. neither a regular file, a
+    /// directory nor a symlink, without following symlinks
+    fn is_special(&self, path: &Path) -> Result<bool>;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl Fs for RealFs {
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        std::fs::rename(from, to)
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> Result<u64> {
+        std::fs::copy(from, to)
+    }
+
+    fn copy_times(&self, from: &Path, to: &Path) -> Result<()> {
+        let metadata = from.metadata()?;
+        let times = FileTimes::new()
+            .set_accessed(metadata.accessed()?)
+            .set_modified(metadata.modified()?);
+
+        File::options().write(true).open(to)?.set_times(times)
+    }
+
+    fn set_times(&self, path: &Path, times: FileTimes) -> Result<()> {
+        File::options().write(true).open(path)?.set_times(times)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        path.metadata()
+    }
+
+    fn try_exists(&self, path: &Path) -> Result<bool> {
+        path.try_exists()
+    }
+
+    fn is_special(&self, path: &Path) -> Result<bool> {
+        let file_type = path.symlink_metadata()?.file_type();
+
+        Ok(!(file_type.is_file()
+            || file_type.is_dir()
+            || file_type.is_symlink()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+    use predicates::prelude::*;
+
+    #[test]
+    fn real_fs() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo").unwrap();
+            let bar = temp.child("bar");
+            let baz = temp.child("baz");
+
+            assert!(RealFs.try_exists(foo.path()).unwrap());
+            assert!(!RealFs.try_exists(bar.path()).unwrap());
+            assert!(RealFs.metadata(foo.path()).unwrap().is_file());
+            assert!(!RealFs.is_special(foo.path()).unwrap());
+            assert!(!RealFs.is_special(temp.path()).unwrap());
+
+            RealFs.rename(foo.path(), bar.path()).unwrap();
+            foo.assert(predicate::path::missing());
+            bar.assert(predicate::path::exists());
+
+            assert_eq!(0, RealFs.copy(bar.path(), baz.path()).unwrap());
+            bar.assert(predicate::path::exists());
+            baz.assert(predicate::path::exists());
+
+            assert!(RealFs.rename(foo.path(), bar.path()).is_err());
+
+            RealFs.remove_file(baz.path()).unwrap();
+            baz.assert(predicate::path::missing());
+            assert!(RealFs.remove_file(baz.path()).is_err());
+
+            let nested = temp.child("nested/dir");
+            RealFs.create_dir_all(nested.path()).unwrap();
+            nested.assert(predicate::path::is_dir());
+            RealFs.create_dir_all(nested.path()).unwrap();
+        });
+    }
+
+    #[test]
+    fn copy_times() {
+        use std::time::{Duration, SystemTime};
+
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo").unwrap();
+            let bar = temp.existing_child("bar").unwrap();
+            let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1705708800);
+            File::options()
+                .write(true)
+                .open(foo.path())
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+
+            RealFs.copy_times(foo.path(), bar.path()).unwrap();
+            assert_eq!(
+                time,
+                bar.path().metadata().unwrap().modified().unwrap()
+            );
+        });
+    }
+}
@@ -0,0 +1,31 @@
+//! Prefix files by the date they refer to
+//!
+//! The matchers determining the dates and the processing renaming the paths
+//! are exposed so the renamer can be embedded in other programs, using their
+//! own Communication interface and, if need be, their own Fs.
+
+pub mod application;
+pub mod fs;
+pub mod matcher;
+pub mod processing;
+pub mod replacement;
+mod ui;
+
+#[cfg(test)]
+mod test;
+
+use matcher::Matcher;
+use processing::{Communication, Processing};
+
+use std::path::PathBuf;
+
+/// Prefix the given paths with the dates found by the matchers, confirming
+/// the replacements and rescuing the paths without match through the
+/// interface
+pub fn prefix_paths<T: Communication>(
+    matchers: &[Box<dyn Matcher>],
+    paths: &[PathBuf],
+    interface: &T,
+) -> processing::Result<()> {
+    Processing::new(interface, matchers, paths).run()
+}
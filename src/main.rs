@@ -1,16 +1,21 @@
-mod application;
-mod matcher;
-mod processing;
-mod replacement;
-mod ui;
+use prefix_by_date::application::{Application, EXIT_CONFIG, EXIT_FAILURES};
+use prefix_by_date::processing::Outcome;
 
-#[cfg(test)]
-mod test;
+use std::process::ExitCode;
 
-use application::{Application, Result};
-
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let mut app = Application::new();
-    app.setup()?;
-    app.run()
+    if let Err(error) = app.setup() {
+        eprintln!("Error: {:?}", error);
+        return ExitCode::from(EXIT_CONFIG);
+    }
+
+    match app.run() {
+        Ok(Outcome::Success) => ExitCode::SUCCESS,
+        Ok(Outcome::Failures) => ExitCode::from(EXIT_FAILURES),
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            ExitCode::from(error.exit_code())
+        }
+    }
 }
@@ -0,0 +1,226 @@
+use crate::matcher::{Matcher, Metadata, Pattern};
+use crate::replacement::Replacement;
+
+use chrono::{DateTime, Local, TimeZone};
+
+/// Combine the date found by one matcher with the time of day, and the name,
+/// found by another, e.g. a reliable metadata date with a time in the name
+#[derive(Clone)]
+pub struct Composite {
+    name: String,
+    format: String,
+    date_source: Box<dyn Matcher>,
+    time_source: Box<dyn Matcher>,
+}
+
+impl Composite {
+    pub fn new(
+        name: &str,
+        format: &str,
+        date_source: Box<dyn Matcher>,
+        time_source: Box<dyn Matcher>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            format: format.to_string(),
+            date_source,
+            time_source,
+        }
+    }
+
+    /// Composite matcher configured by the given table, using `date_source`
+    /// (created or modified) for the date, and the pattern named by
+    /// `time_source` for the time
+    pub fn deserialize(
+        name: &str,
+        table: &toml::Table,
+        patterns: &[Pattern],
+        default_format: &str,
+    ) -> Option<Self> {
+        use crate::matcher::metadata::{CREATED, MODIFIED};
+        use toml::Value;
+
+        let date_source: Box<dyn Matcher> =
+            match table.get("date_source").and_then(Value::as_str) {
+                Some(CREATED) => Box::new(Metadata::new_created("")),
+                Some(MODIFIED) => Box::new(Metadata::new_modified("")),
+                source => {
+                    log::warn!(
+                        "Composite {:?}: invalid date source {:?}",
+                        name,
+                        source
+                    );
+                    return None;
+                }
+            };
+
+        let time_source = table.get("time_source").and_then(Value::as_str);
+        let Some(time_source) = patterns
+            .iter()
+            .find(|pattern| Some(pattern.name()) == time_source)
+        else {
+            log::warn!(
+                "Composite {:?}: unknown time source pattern {:?}",
+                name,
+                time_source
+            );
+            return None;
+        };
+
+        let format = table
+            .get("format")
+            .and_then(Value::as_str)
+            .unwrap_or(default_format);
+
+        Some(Self::new(
+            name,
+            format,
+            date_source,
+            Box::new(time_source.clone()),
+        ))
+    }
+}
+
+impl Matcher for Composite {
+    fn determine(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        let (_, date) = self.date_source.determine(replacement)?;
+        let (name, time) = self.time_source.determine_time(replacement)?;
+        let date_time = date.date_naive().and_time(time);
+
+        Some((name, Local.from_local_datetime(&date_time).earliest()?))
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn delimiter(&self) -> &str {
+        self.time_source.delimiter()
+    }
+
+    fn date_format(&self) -> &str {
+        self.format.as_str()
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::DEFAULT_DATE_TIME_FORMAT;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    use toml::Table;
+
+    fn time_pattern() -> Pattern {
+        Pattern::builder()
+            .regex(
+                r"
+                (?<start>.+)
+                _
+                (?<hour>\d{2})
+                (?<min>\d{2})
+                (?<sec>\d{2})
+                ",
+            )
+            .name("time_suffix")
+            .build()
+            .unwrap()
+    }
+
+    fn table(date_source: &str, time_source: &str) -> Table {
+        let mut table = Table::new();
+        table.insert("date_source".into(), date_source.into());
+        table.insert("time_source".into(), time_source.into());
+        table
+    }
+
+    #[test]
+    fn check() {
+        with_temp_dir(|temp| {
+            let child = temp.existing_child("IMG_134501.jpg").unwrap();
+            let modified: DateTime<Local> =
+                child.path().metadata().unwrap().modified().unwrap().into();
+
+            let composite = Composite::new(
+                "composite",
+                DEFAULT_DATE_TIME_FORMAT,
+                Box::new(Metadata::new_modified("")),
+                Box::new(time_pattern()),
+            );
+            let replacement = composite.check(child.path()).unwrap();
+
+            assert_eq!(
+                format!("{} 13h45m01 IMG", modified.format("%Y-%m-%d")),
+                replacement.new_file_stem
+            );
+        });
+    }
+
+    #[test]
+    fn check_without_time() {
+        with_temp_dir(|temp| {
+            let child = temp.existing_child("IMG.jpg").unwrap();
+
+            let composite = Composite::new(
+                "composite",
+                DEFAULT_DATE_TIME_FORMAT,
+                Box::new(Metadata::new_modified("")),
+                Box::new(time_pattern()),
+            );
+            assert!(composite.check(child.path()).is_none());
+        });
+    }
+
+    #[test]
+    fn deserialize() {
+        let patterns = [time_pattern()];
+
+        let composite = Composite::deserialize(
+            "foo",
+            &table("created", "time_suffix"),
+            &patterns,
+            "%Y",
+        )
+        .unwrap();
+        assert_eq!("foo", composite.name());
+        assert_eq!("%Y", composite.date_format());
+        assert_eq!("created", composite.date_source.name());
+        assert_eq!("time_suffix", composite.time_source.name());
+
+        let mut table = table("modified", "time_suffix");
+        table.insert("format".into(), "%H %Y".into());
+        let composite =
+            Composite::deserialize("foo", &table, &patterns, "%Y").unwrap();
+        assert_eq!("%H %Y", composite.date_format());
+        assert_eq!("modified", composite.date_source.name());
+    }
+
+    #[test]
+    fn deserialize_invalid() {
+        let patterns = [time_pattern()];
+
+        assert!(Composite::deserialize(
+            "foo",
+            &table("today", "time_suffix"),
+            &patterns,
+            ""
+        )
+        .is_none());
+        assert!(Composite::deserialize(
+            "foo",
+            &table("created", "unknown"),
+            &patterns,
+            ""
+        )
+        .is_none());
+        assert!(Composite::deserialize("foo", &Table::new(), &patterns, "")
+            .is_none());
+    }
+}
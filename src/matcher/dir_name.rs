@@ -0,0 +1,137 @@
+use crate::matcher::pattern::DateBounds;
+use crate::matcher::{Matcher, Pattern};
+use crate::replacement::Replacement;
+
+use chrono::{DateTime, Local};
+
+pub const DIR_NAME: &str = "dir_name";
+
+/// Regex used unless configured otherwise, matching dates like 2024-01-20,
+/// 2024_01_20 or 20240120 anywhere in the directory name
+pub const DEFAULT_REGEX: &str =
+    r"(?<year>\d{4})[-_.]?(?<month>\d{2})[-_.]?(?<day>\d{2})";
+
+/// Prefix files by the date found in the name of the directory containing
+/// them, e.g. `2024-01-20/scan001.pdf`
+#[derive(Clone)]
+pub struct DirName {
+    pattern: Pattern,
+}
+
+impl DirName {
+    /// Create the matcher, capturing the date in the directory name with the
+    /// given regex, like a Pattern
+    ///
+    /// Return None if the regex is invalid
+    pub fn new(format: &str, regex: &str) -> Option<Self> {
+        Pattern::builder()
+            .name(DIR_NAME)
+            .regex(regex)
+            .format(format)
+            .build()
+            .map(|pattern| Self { pattern })
+    }
+
+    /// Reject the dates outside of the given bounds as implausible
+    pub fn with_date_bounds(mut self, date_bounds: DateBounds) -> Self {
+        self.pattern.date_bounds = date_bounds;
+        self
+    }
+}
+
+impl Matcher for DirName {
+    fn determine(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        let dir_name = replacement.parent.file_name()?.to_str()?;
+        let (_, date_time) = self.pattern.determine_text(dir_name)?;
+
+        Some((
+            replacement.file_stem.clone(),
+            date_time.with_timezone(&Local),
+        ))
+    }
+
+    /// Name of the matcher
+    fn name(&self) -> &str {
+        DIR_NAME
+    }
+    /// Delimiter to place between the matched elements
+    fn delimiter(&self) -> &str {
+        self.pattern.delimiter()
+    }
+    /// Format to use for the date
+    fn date_format(&self) -> &str {
+        self.pattern.date_format()
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::DEFAULT_DATE_FORMAT;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    use std::path::PathBuf;
+
+    #[test]
+    fn check() {
+        let matcher = DirName::new(DEFAULT_DATE_FORMAT, DEFAULT_REGEX).unwrap();
+
+        with_temp_dir(|temp| {
+            let scan = temp.existing_child("2024-01-20/scan001.pdf").unwrap();
+
+            let replacement = matcher.check(scan.path()).unwrap();
+            assert_eq!("2024-01-20 scan001", replacement.new_file_stem);
+            assert_eq!("pdf", replacement.extension);
+        });
+
+        assert_eq!(
+            "2023-12-31 IMG_1",
+            matcher
+                .check(&PathBuf::from("/photos/NYE 20231231/IMG_1.jpg"))
+                .unwrap()
+                .new_file_stem
+        );
+    }
+
+    #[test]
+    fn check_undated_parent() {
+        let matcher = DirName::new(DEFAULT_DATE_FORMAT, DEFAULT_REGEX).unwrap();
+
+        assert!(matcher
+            .check(&PathBuf::from("/scans/scan001.pdf"))
+            .is_none());
+        assert!(matcher
+            .check(&PathBuf::from("/scans/2024-13-45/scan001.pdf"))
+            .is_none());
+        assert!(matcher.check(&PathBuf::from("scan001.pdf")).is_none());
+    }
+
+    #[test]
+    fn custom_regex() {
+        let matcher = DirName::new(
+            DEFAULT_DATE_FORMAT,
+            r"\A(?<day>\d{2})\.(?<month>\d{2})\.(?<year>\d{4})\z",
+        )
+        .unwrap();
+
+        assert_eq!(
+            "2024-01-20 scan",
+            matcher
+                .check(&PathBuf::from("/scans/20.01.2024/scan.pdf"))
+                .unwrap()
+                .new_file_stem
+        );
+        assert!(matcher
+            .check(&PathBuf::from("/scans/2024-01-20/scan.pdf"))
+            .is_none());
+
+        assert!(DirName::new(DEFAULT_DATE_FORMAT, "(").is_none());
+    }
+}
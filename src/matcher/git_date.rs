@@ -0,0 +1,131 @@
+#![cfg(feature = "git")]
+
+use crate::matcher::Matcher;
+use crate::replacement::Replacement;
+
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, Local};
+
+pub const GIT_DATE: &str = "git_date";
+
+/// Match files tracked in a git repository by the author date of the last
+/// commit touching them
+#[derive(Default, Clone)]
+pub struct GitDate {
+    format: String,
+}
+
+impl GitDate {
+    pub fn new(format: &str) -> Self {
+        Self {
+            format: format.to_string(),
+        }
+    }
+}
+
+/// Author date of the last commit touching the given path, if it is tracked
+/// in a git repository
+fn last_commit_date(path: &Path) -> Option<DateTime<Local>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path.parent()?)
+        .args(["log", "-1", "--format=%aI", "--"])
+        .arg(path.file_name()?)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let text = std::str::from_utf8(&output.stdout).ok()?.trim();
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|date_time| date_time.with_timezone(&Local))
+}
+
+impl Matcher for GitDate {
+    fn determine(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        let date_time = last_commit_date(&replacement.path())?;
+
+        Some((replacement.file_stem.clone(), date_time))
+    }
+
+    /// Name of the matcher
+    fn name(&self) -> &str {
+        GIT_DATE
+    }
+    /// Delimiter to place between the matched elements
+    fn delimiter(&self) -> &str {
+        " "
+    }
+    /// Format to use for the date
+    fn date_format(&self) -> &str {
+        self.format.as_str()
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::DEFAULT_DATE_FORMAT;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    use chrono::{FixedOffset, TimeZone};
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_AUTHOR_DATE", "2024-01-20T10:30:00+01:00")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("HOME", dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn check() {
+        with_temp_dir(|temp| {
+            let matcher = GitDate::new(DEFAULT_DATE_FORMAT);
+            let child = temp.existing_child("notes.md").unwrap();
+
+            // Not in a git repository
+            assert!(matcher.check(child.path()).is_none());
+
+            git(temp.path(), &["init", "--quiet"]);
+
+            // Not tracked
+            assert!(matcher.check(child.path()).is_none());
+
+            git(temp.path(), &["add", "notes.md"]);
+            git(temp.path(), &["commit", "--quiet", "-m", "Add notes"]);
+
+            let replacement = matcher.check(child.path()).unwrap();
+            assert_eq!(
+                Some(
+                    FixedOffset::east_opt(3600)
+                        .unwrap()
+                        .with_ymd_and_hms(2024, 1, 20, 10, 30, 0)
+                        .unwrap()
+                ),
+                replacement.date_time
+            );
+            assert_eq!("notes", replacement.file_stem);
+        });
+    }
+}
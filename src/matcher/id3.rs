@@ -0,0 +1,182 @@
+use crate::matcher::Matcher;
+use crate::replacement::Replacement;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use id3::{Tag, TagLike, Timestamp};
+
+pub const ID3: &str = "id3";
+
+/// Match audio files by the recording date of their ID3 tag
+#[derive(Default, Clone)]
+pub struct Id3 {
+    format: String,
+    /// Use the artist and title of the tag as name instead of the file stem
+    tag_name: bool,
+}
+
+impl Id3 {
+    pub fn new(format: &str, tag_name: bool) -> Self {
+        Self {
+            format: format.to_string(),
+            tag_name,
+        }
+    }
+
+    fn name_from(&self, tag: &Tag, replacement: &Replacement) -> String {
+        if self.tag_name {
+            match (tag.artist(), tag.title()) {
+                (Some(artist), Some(title)) => {
+                    return format!("{} - {}", artist, title)
+                }
+                (None, Some(title)) => return title.to_string(),
+                _ => {}
+            }
+        }
+
+        replacement.file_stem.clone()
+    }
+}
+
+/// Date of the tag, from TDRC (ID3v2.4) or TYER (ID3v2.3)
+fn date_time_from(tag: &Tag) -> Option<DateTime<Local>> {
+    let timestamp = tag.date_recorded().or_else(|| {
+        tag.year().map(|year| Timestamp {
+            year,
+            month: None,
+            day: None,
+            hour: None,
+            minute: None,
+            second: None,
+        })
+    })?;
+
+    let date = NaiveDate::from_ymd_opt(
+        timestamp.year,
+        timestamp.month.unwrap_or(1).into(),
+        timestamp.day.unwrap_or(1).into(),
+    )?;
+    let date_time = date.and_hms_opt(
+        timestamp.hour.unwrap_or(0).into(),
+        timestamp.minute.unwrap_or(0).into(),
+        timestamp.second.unwrap_or(0).into(),
+    )?;
+
+    Local.from_local_datetime(&date_time).earliest()
+}
+
+impl Matcher for Id3 {
+    fn determine(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        let tag = Tag::read_from_path(replacement.path()).ok()?;
+        let date_time = date_time_from(&tag)?;
+
+        Some((self.name_from(&tag, replacement), date_time))
+    }
+
+    /// Name of the matcher
+    fn name(&self) -> &str {
+        ID3
+    }
+    /// Delimiter to place between the matched elements
+    fn delimiter(&self) -> &str {
+        " "
+    }
+    /// Format to use for the date
+    fn date_format(&self) -> &str {
+        self.format.as_str()
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::DEFAULT_DATE_FORMAT;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    use id3::Version;
+
+    fn tagged(
+        temp: &TempDir,
+        name: &str,
+        build: impl FnOnce(&mut Tag),
+    ) -> ChildPath {
+        let child = temp.existing_child(name).unwrap();
+        let mut tag = Tag::new();
+        build(&mut tag);
+        tag.write_to_path(child.path(), Version::Id3v24).unwrap();
+        child
+    }
+
+    #[test]
+    fn check_date_recorded() {
+        with_temp_dir(|temp| {
+            let child = tagged(temp, "podcast.mp3", |tag| {
+                tag.set_date_recorded(Timestamp {
+                    year: 2024,
+                    month: Some(1),
+                    day: Some(20),
+                    hour: None,
+                    minute: None,
+                    second: None,
+                });
+                tag.set_artist("Foo");
+                tag.set_title("Bar");
+            });
+
+            let replacement = Id3::new(DEFAULT_DATE_FORMAT, false)
+                .check(child.path())
+                .unwrap();
+            assert_eq!("2024-01-20 podcast", replacement.new_file_stem);
+            assert_eq!("mp3", replacement.extension);
+
+            let replacement = Id3::new(DEFAULT_DATE_FORMAT, true)
+                .check(child.path())
+                .unwrap();
+            assert_eq!("2024-01-20 Foo - Bar", replacement.new_file_stem);
+        });
+    }
+
+    #[test]
+    fn check_year() {
+        with_temp_dir(|temp| {
+            let child = tagged(temp, "song.mp3", |tag| {
+                tag.set_year(1999);
+                tag.set_title("Bar");
+            });
+
+            let replacement = Id3::new(DEFAULT_DATE_FORMAT, true)
+                .check(child.path())
+                .unwrap();
+            assert_eq!("1999-01-01 Bar", replacement.new_file_stem);
+        });
+    }
+
+    #[test]
+    fn check_without_date() {
+        with_temp_dir(|temp| {
+            let child = tagged(temp, "song.mp3", |tag| {
+                tag.set_title("Bar");
+            });
+            assert!(Id3::default().check(child.path()).is_none());
+
+            let child = temp.existing_child("untagged.mp3").unwrap();
+            assert!(Id3::default().check(child.path()).is_none());
+        });
+    }
+
+    #[test]
+    fn name() {
+        assert_eq!("id3", Id3::default().name());
+    }
+
+    #[test]
+    fn auto_accept() {
+        assert!(!Id3::default().auto_accept());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::matcher::Matcher;
 use crate::replacement::Replacement;
 
+use std::path::Path;
+
 use chrono::{DateTime, Local};
 
 #[derive(Default, Clone, Copy)]
@@ -59,6 +61,17 @@ impl Matcher for Metadata {
         Some((replacement.file_stem.clone(), date_time.into()))
     }
 
+    fn unavailable(&self, path: &Path) -> Option<String> {
+        let result = path.metadata().and_then(|metadata| match self.kind {
+            Kind::Created => metadata.created(),
+            Kind::Modified => metadata.modified(),
+        });
+
+        result
+            .err()
+            .map(|error| format!("{} time unavailable: {}", self.name(), error))
+    }
+
     /// Name of the matcher
     fn name(&self) -> &str {
         self.kind.name()
@@ -83,7 +96,6 @@ mod tests {
     use crate::test::{assert_eq, assert_ne, test};
     use assert_fs::fixture::{FileTouch, NamedTempFile};
 
-    use std::path::Path;
     use std::thread::sleep;
     use std::time::Duration;
 
@@ -121,6 +133,33 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn unavailable() {
+        let temp_file = NamedTempFile::new("foo").unwrap();
+        temp_file.touch().unwrap();
+
+        let modified = Metadata::new_modified("foo");
+        assert_eq!(None, modified.unavailable(temp_file.path()));
+
+        let reason = modified.unavailable(Path::new("foo")).unwrap();
+        assert!(
+            reason.starts_with("modified time unavailable: "),
+            "Unexpected reason: {reason:?}"
+        );
+
+        // The creation time of an existing file depends on the platform and
+        // filesystem
+        let created = Metadata::new_created("foo");
+        let metadata = temp_file.path().metadata().unwrap();
+        match metadata.created() {
+            Ok(_) => assert_eq!(None, created.unavailable(temp_file.path())),
+            Err(error) => assert_eq!(
+                Some(format!("created time unavailable: {}", error)),
+                created.unavailable(temp_file.path())
+            ),
+        }
+    }
+
     #[test]
     fn name() {
         assert_eq!("created", Metadata::new_created("foo").name());
@@ -1,9 +1,10 @@
-use crate::replacement::Replacement;
+use crate::replacement::{self, Replacement};
 
 use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime};
 use dyn_clone::DynClone;
 
 pub mod predetermined_date;
@@ -15,24 +16,278 @@ pub use pattern::Pattern;
 pub mod metadata;
 pub use metadata::Metadata;
 
+pub mod id3;
+pub use self::id3::Id3;
+
+pub mod composite;
+pub use composite::Composite;
+
+pub mod dir_name;
+pub use dir_name::DirName;
+
+pub mod sidecar;
+pub use sidecar::Sidecar;
+
+pub mod pdf_meta;
+#[cfg(feature = "pdf")]
+pub use pdf_meta::PdfMeta;
+
+pub mod git_date;
+#[cfg(feature = "git")]
+pub use git_date::GitDate;
+
+/// Priority of the matchers not configured otherwise
+pub const DEFAULT_PRIORITY: i64 = 0;
+
+/// How the matchers compose the new file stems from the name and date they
+/// determine
+#[derive(Debug, Clone, PartialEq)]
+pub struct Naming {
+    /// Position of the date relative to the name
+    pub position: Position,
+    /// Separator between the date and the name, instead of the delimiter of
+    /// each matcher
+    pub prefix_separator: Option<String>,
+    /// Trim and collapse the whitespace of the names, or keep them as found
+    pub collapse_whitespace: bool,
+    /// Format the date-times with the offset they were determined with
+    /// instead of the local one
+    pub keep_timezone: bool,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Self {
+            position: Position::default(),
+            prefix_separator: None,
+            collapse_whitespace: true,
+            keep_timezone: false,
+        }
+    }
+}
+
+/// Position of the date relative to the name
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Position {
+    /// The date comes first, e.g. 2024-01-20 foo
+    #[default]
+    Prefix,
+    /// The date comes last, e.g. foo 2024-01-20
+    Suffix,
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "prefix" => Ok(Self::Prefix),
+            "suffix" => Ok(Self::Suffix),
+            _ => Err(format!("Unknown position {:?}", string)),
+        }
+    }
+}
+
+/// Join the formatted date and the name by the delimiter, in the order given
+/// by the position of the naming
+///
+/// A name already dated in the given format, whatever the date, isn't dated
+/// again but has its date replaced, and only the name is cased and collapsed,
+/// the date being kept as formatted
+fn compose_stem(
+    date: &str,
+    format: &str,
+    delimiter: &str,
+    name: &str,
+    case: Case,
+    naming: &Naming,
+) -> String {
+    let (position, collapse) = (naming.position, naming.collapse_whitespace);
+    let name = strip_date(name, format, delimiter, position);
+
+    match position {
+        Position::Prefix => {
+            let prefix = format!("{}{}", date, delimiter);
+            let name = case.apply(name);
+
+            if collapse {
+                let stem = format!("{}{}", prefix, collapse_whitespace(&name));
+                stem.trim_end().to_string()
+            } else {
+                format!("{}{}", prefix, name)
+            }
+        }
+        Position::Suffix => {
+            let suffix = format!("{}{}", delimiter, date);
+            let name = case.apply(name);
+
+            if collapse {
+                let stem = format!("{}{}", collapse_whitespace(&name), suffix);
+                stem.trim_start().to_string()
+            } else {
+                format!("{}{}", name, suffix)
+            }
+        }
+    }
+}
+
+/// Name without the date in the given format it already starts with, or ends
+/// with for a suffix, and the delimiter separating them
+fn strip_date<'a>(
+    name: &'a str,
+    format: &str,
+    delimiter: &str,
+    position: Position,
+) -> &'a str {
+    match position {
+        Position::Prefix => {
+            match NaiveDate::parse_and_remainder(name, format) {
+                Ok((_, rest)) => rest.strip_prefix(delimiter).unwrap_or(rest),
+                Err(_) => name,
+            }
+        }
+        Position::Suffix => name
+            .char_indices()
+            .map(|(index, _)| &name[..index])
+            .find(|rest| {
+                NaiveDate::parse_and_remainder(&name[rest.len()..], format)
+                    .is_ok_and(|(_, remainder)| remainder.is_empty())
+            })
+            .map(|rest| rest.strip_suffix(delimiter).unwrap_or(rest))
+            .unwrap_or(name),
+    }
+}
+
+/// Trim the name and collapse its runs of whitespace into a single space
+fn collapse_whitespace(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Case transformation of the name kept along the date
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Case {
+    /// Keep the name as found
+    #[default]
+    None,
+    Lower,
+    Upper,
+    /// Uppercase the first letter of each word separated by whitespace and
+    /// lowercase the others
+    Title,
+}
+
+impl Case {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            Self::None => name.to_string(),
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Title => {
+                let mut word_start = true;
+                let mut title = String::with_capacity(name.len());
+                for c in name.chars() {
+                    if word_start {
+                        title.extend(c.to_uppercase());
+                    } else {
+                        title.extend(c.to_lowercase());
+                    }
+                    word_start = c.is_whitespace();
+                }
+                title
+            }
+        }
+    }
+}
+
+impl FromStr for Case {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "none" => Ok(Self::None),
+            "lower" => Ok(Self::Lower),
+            "upper" => Ok(Self::Upper),
+            "title" => Ok(Self::Title),
+            _ => Err(format!("Unknown case {:?}", string)),
+        }
+    }
+}
+
 /// Match a file to be renamed
 pub trait Matcher: DynClone + Send {
     /// Check if the given path should be replaced by the matcher and
     /// if so, return the appropriate Replacement
+    ///
+    /// A name already prefixed by a date in the same format has it replaced
+    /// rather than being prefixed again, so processing the same file twice
+    /// gives the same name
     fn check(&self, path: &Path) -> Option<Replacement> {
-        let mut replacement = Replacement::try_from(path).ok()?;
-        let (name, date_time) = self.determine(&replacement)?;
+        self.check_with(
+            path,
+            &Naming::default(),
+            &replacement::Settings::default(),
+        )
+    }
 
-        replacement.new_file_stem = format!(
-            "{}{}{}",
-            date_time.format(self.date_format()),
-            self.delimiter(),
-            name
+    /// Same as check, but composing the new file stem according to the
+    /// naming and creating the replacement with the given settings
+    fn check_with(
+        &self,
+        path: &Path,
+        naming: &Naming,
+        settings: &replacement::Settings,
+    ) -> Option<Replacement> {
+        let mut replacement =
+            Replacement::try_from_with(path, settings).ok()?;
+        let (name, date, date_time) = if naming.keep_timezone {
+            let (name, date_time) = self.determine_with_offset(&replacement)?;
+            let date = date_time.format(self.date_format()).to_string();
+            (name, date, date_time)
+        } else {
+            let (name, date_time) = self.determine(&replacement)?;
+            let date = date_time.format(self.date_format()).to_string();
+            (name, date, date_time.fixed_offset())
+        };
+
+        replacement.new_file_stem = compose_stem(
+            &date,
+            self.date_format(),
+            naming
+                .prefix_separator
+                .as_deref()
+                .unwrap_or(self.delimiter()),
+            &name,
+            self.case(),
+            naming,
         );
+        replacement.date_time = Some(date_time);
+        replacement.date_format = Some(self.date_format().to_string());
 
         Some(replacement)
     }
 
+    /// Same as check, but explaining why the path doesn't match, if so
+    fn check_explained(
+        &self,
+        path: &Path,
+        naming: &Naming,
+        settings: &replacement::Settings,
+    ) -> std::result::Result<Replacement, String> {
+        let replacement = Replacement::try_from_with(path, settings)
+            .map_err(|error| error.to_string())?;
+        if let Some(reason) = self.unavailable(path) {
+            return Err(reason);
+        }
+
+        self.check_with(path, naming, settings)
+            .ok_or_else(|| self.explain_mismatch(&replacement))
+    }
+
+    /// Short reason why no date could be determined for the replacement
+    fn explain_mismatch(&self, _replacement: &Replacement) -> String {
+        String::from("no date determined")
+    }
+
     /// Determine the name and date-time to use
     ///
     /// The whole &Replacement is passed so you can access the path() if needed,
@@ -42,6 +297,26 @@ pub trait Matcher: DynClone + Send {
         replacement: &Replacement,
     ) -> Option<(String, DateTime<Local>)>;
 
+    /// Determine the name and date-time to use, keeping the offset of the
+    /// source when the matcher knows it
+    fn determine_with_offset(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<FixedOffset>)> {
+        self.determine(replacement)
+            .map(|(name, date_time)| (name, date_time.fixed_offset()))
+    }
+
+    /// Determine the name and only the time of day to use, for a Composite
+    /// matcher taking the date from another matcher
+    fn determine_time(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, NaiveTime)> {
+        self.determine(replacement)
+            .map(|(name, date_time)| (name, date_time.time()))
+    }
+
     /// Name of the matcher
     fn name(&self) -> &str;
     /// Delimiter to place between the matched elements
@@ -52,6 +327,27 @@ pub trait Matcher: DynClone + Send {
     /// Indicates if a replacement produced by this matcher can be accepted
     /// without user confirmation or not.
     fn auto_accept(&self) -> bool;
+
+    /// Matchers with a lower priority are tried first
+    fn priority(&self) -> i64 {
+        DEFAULT_PRIORITY
+    }
+
+    /// Case transformation of the name determined
+    fn case(&self) -> Case {
+        Case::None
+    }
+
+    /// Source the matcher was built from, if any, e.g. a pattern's regex
+    fn source(&self) -> Option<&str> {
+        None
+    }
+
+    /// Explain why the data to determine the date from couldn't be read for
+    /// the path, as opposed to the path simply not matching
+    fn unavailable(&self, _path: &Path) -> Option<String> {
+        None
+    }
 }
 
 impl fmt::Debug for dyn Matcher {
@@ -61,3 +357,79 @@ impl fmt::Debug for dyn Matcher {
 }
 
 dyn_clone::clone_trait_object!(Matcher);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    #[test]
+    fn compose_stem() {
+        let compose = |name, position| {
+            super::compose_stem(
+                "2024-01-20",
+                "%Y-%m-%d",
+                " ",
+                name,
+                Case::None,
+                &Naming {
+                    position,
+                    ..Naming::default()
+                },
+            )
+        };
+
+        assert_eq!("2024-01-20 foo", compose("foo", Position::Prefix));
+        assert_eq!(
+            "2024-01-20 foo",
+            compose("2024-01-20 foo", Position::Prefix)
+        );
+        assert_eq!("foo 2024-01-20", compose("foo", Position::Suffix));
+        assert_eq!(
+            "foo 2024-01-20",
+            compose("foo 2024-01-20", Position::Suffix)
+        );
+        assert_eq!("2024-01-20", compose(" ", Position::Suffix));
+
+        // Names dated in the same format on another day are dated again
+        assert_eq!(
+            "2024-01-20 foo",
+            compose("2024-01-21 foo", Position::Prefix)
+        );
+        assert_eq!(
+            "foo 2024-01-20",
+            compose("foo 2023-12-31", Position::Suffix)
+        );
+        // But not those dated in another format
+        assert_eq!(
+            "2024-01-20 20240121 foo",
+            compose("20240121 foo", Position::Prefix)
+        );
+        assert_eq!(
+            "foo 2024 2024-01-20",
+            compose("foo 2024", Position::Suffix)
+        );
+
+        assert_eq!(
+            "Foo Bar_2024-01-20",
+            super::compose_stem(
+                "2024-01-20",
+                "%Y-%m-%d",
+                "_",
+                "foo  bar",
+                Case::Title,
+                &Naming {
+                    position: Position::Suffix,
+                    ..Naming::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn position_from_str() {
+        assert_eq!(Ok(Position::Prefix), "prefix".parse());
+        assert_eq!(Ok(Position::Suffix), "suffix".parse());
+        assert!("middle".parse::<Position>().is_err());
+    }
+}
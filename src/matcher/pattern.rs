@@ -1,10 +1,13 @@
 use crate::application::DEFAULT_DATE_FORMAT;
-use crate::matcher::Matcher;
+use crate::matcher::{Case, Matcher, DEFAULT_PRIORITY};
 use crate::replacement::Replacement;
 
 use std::str::FromStr;
 
-use chrono::{DateTime, Local, TimeZone};
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone,
+    Weekday,
+};
 use regex::{Captures, Regex, RegexBuilder};
 
 #[derive(Clone)]
@@ -13,7 +16,215 @@ pub struct Pattern {
     pub format: String,
     pub name: String,
     pub delimiter: String,
+    /// Delimiter to place between the captured name parts
+    pub name_delimiter: String,
     pub time: bool,
+    /// Two-digit years below the pivot are in the 2000s, the others in the
+    /// 1900s
+    pub century_pivot: i32,
+    /// Language of the month names
+    pub locale: Locale,
+    /// How to interpret the captured month and day
+    pub date_order: DateOrder,
+    /// Patterns with a lower priority are tried first
+    pub priority: i64,
+    /// Case transformation of the captured name
+    pub case: Case,
+    /// Unit of the timestamp captured by the epoch group
+    pub epoch_unit: EpochUnit,
+    /// Use the text around the match as name when the regex has no start,
+    /// end or rest group
+    pub auto_rest: bool,
+    /// Range of the dates accepted, the others being rejected as implausible
+    pub date_bounds: DateBounds,
+}
+
+/// Default pivot to expand two-digit years
+pub const DEFAULT_CENTURY_PIVOT: i32 = 70;
+
+/// Regex of the iso preset, matching ISO 8601 dates in their basic
+/// (`20240120`) or extended (`2024-01-20`) form, with an optional time and
+/// offset, e.g. `2024-01-20T10:30:00+01:00`
+pub const ISO_DATE_REGEX: &str = r"
+    (?<year>\d{4})-?(?<month>\d{2})-?(?<day>\d{2})
+    (?:
+      [T\ _]?(?<hour>\d{2}):?(?<min>\d{2})(?::?(?<sec>\d{2}))?
+      (?<tz>Z|[+-]\d{2}(?::?\d{2})?)?
+    )?
+";
+
+/// Characters trimmed from the text around the match of auto_rest patterns,
+/// usually separating the date from the name
+const AUTO_REST_TRIMMED: [char; 4] = [' ', '_', '-', '.'];
+
+/// Range of the dates plausible enough for a pattern to match, to reject the
+/// garbage a loose regex captures, e.g. `99999999`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DateBounds {
+    /// Earliest date accepted, 1970-01-01 if not set
+    pub min: Option<NaiveDate>,
+    /// Latest date accepted, tomorrow if not set
+    pub max: Option<NaiveDate>,
+}
+
+impl DateBounds {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let min = self.min.unwrap_or(DateTime::UNIX_EPOCH.date_naive());
+        let max = self.max.unwrap_or_else(|| {
+            Local::now().date_naive() + chrono::Days::new(1)
+        });
+
+        min <= date && date <= max
+    }
+}
+
+/// Language used to recognize month names
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+impl Locale {
+    /// Names of the months, full or abbreviated, in lowercase
+    fn month_names(&self) -> [&'static [&'static str]; 12] {
+        match self {
+            Self::English => [
+                &["january", "jan"],
+                &["february", "feb"],
+                &["march", "mar"],
+                &["april", "apr"],
+                &["may"],
+                &["june", "jun"],
+                &["july", "jul"],
+                &["august", "aug"],
+                &["september", "sep", "sept"],
+                &["october", "oct"],
+                &["november", "nov"],
+                &["december", "dec"],
+            ],
+            Self::French => [
+                &["janvier", "janv", "jan"],
+                &["février", "fevrier", "févr", "fevr", "fév", "fev"],
+                &["mars", "mar"],
+                &["avril", "avr"],
+                &["mai"],
+                &["juin"],
+                &["juillet", "juil"],
+                &["août", "aout"],
+                &["septembre", "sept", "sep"],
+                &["octobre", "oct"],
+                &["novembre", "nov"],
+                &["décembre", "decembre", "déc", "dec"],
+            ],
+        }
+    }
+
+    /// Number of the month (1 to 12) with the given name, ignoring case and a
+    /// trailing dot
+    pub fn month(&self, name: &str) -> Option<u32> {
+        let name = name.trim_end_matches('.').to_lowercase();
+
+        self.month_names()
+            .iter()
+            .position(|names| names.contains(&name.as_str()))
+            .map(|index| index as u32 + 1)
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "en" | "english" => Ok(Self::English),
+            "fr" | "french" => Ok(Self::French),
+            _ => Err(format!("Unknown locale {:?}", string)),
+        }
+    }
+}
+
+/// Order in which the captured month and day are interpreted
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DateOrder {
+    /// Month and day as captured, swapping them if that isn't a valid date
+    #[default]
+    Auto,
+    /// Month and day as captured, never swapping them
+    MonthDay,
+    /// Captured month and day swapped, e.g. for ambiguous European dates
+    /// matched by a pattern written for American ones
+    DayMonth,
+}
+
+impl FromStr for DateOrder {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "auto" => Ok(Self::Auto),
+            "month_day" => Ok(Self::MonthDay),
+            "day_month" => Ok(Self::DayMonth),
+            _ => Err(format!("Unknown date order {:?}", string)),
+        }
+    }
+}
+
+/// Unit of a timestamp counted from the Unix epoch
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum EpochUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+}
+
+impl FromStr for EpochUnit {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "s" | "seconds" => Ok(Self::Seconds),
+            "ms" | "milliseconds" => Ok(Self::Milliseconds),
+            _ => Err(format!("Unknown epoch unit {:?}", string)),
+        }
+    }
+}
+
+impl EpochUnit {
+    /// Local date-time of the timestamp, e.g. 1705708800 seconds for
+    /// 2024-01-20T00:00:00Z
+    fn date_time(&self, timestamp: &str) -> Option<DateTime<FixedOffset>> {
+        let timestamp = timestamp.parse::<i64>().ok()?;
+        let date_time = match self {
+            Self::Seconds => DateTime::from_timestamp(timestamp, 0),
+            Self::Milliseconds => DateTime::from_timestamp_millis(timestamp),
+        }?;
+
+        Some(date_time.with_timezone(&Local).fixed_offset())
+    }
+}
+
+/// Replace the delimiter between the date and time portions of the format,
+/// i.e. the text between the last specifier before the hour and the hour
+///
+/// Formats without hour are returned unchanged
+pub fn with_time_delimiter(format: &str, delimiter: &str) -> String {
+    let Some(hour) = format.find("%H").or_else(|| format.find("%I")) else {
+        return format.to_string();
+    };
+    let date = &format[..hour];
+    let date_end = match date.rfind('%') {
+        Some(index) => {
+            let flag = date[index + 1..]
+                .starts_with(['-', '_', '0', '^', '#', ':'])
+                as usize;
+            (index + 2 + flag).min(date.len())
+        }
+        None => 0,
+    };
+
+    format!("{}{}{}", &date[..date_end], delimiter, &format[hour..])
 }
 
 impl Default for Pattern {
@@ -23,7 +234,16 @@ impl Default for Pattern {
             format: String::from(DEFAULT_DATE_FORMAT),
             name: String::from(""),
             delimiter: String::from(""),
+            name_delimiter: String::from(""),
             time: false,
+            century_pivot: DEFAULT_CENTURY_PIVOT,
+            locale: Locale::default(),
+            date_order: DateOrder::default(),
+            priority: DEFAULT_PRIORITY,
+            case: Case::default(),
+            epoch_unit: EpochUnit::default(),
+            auto_rest: false,
+            date_bounds: DateBounds::default(),
         }
     }
 }
@@ -33,7 +253,17 @@ pub struct PatternBuilder {
     pub format: String,
     pub name: Option<String>,
     pub delimiter: Option<String>,
+    pub name_delimiter: Option<String>,
+    pub time_delimiter: Option<String>,
     pub time: Option<bool>,
+    pub century_pivot: Option<i32>,
+    pub locale: Option<Locale>,
+    pub date_order: Option<DateOrder>,
+    pub priority: Option<i64>,
+    pub case: Option<Case>,
+    pub epoch_unit: Option<EpochUnit>,
+    pub auto_rest: Option<bool>,
+    pub date_bounds: Option<DateBounds>,
 }
 
 impl Default for PatternBuilder {
@@ -43,7 +273,17 @@ impl Default for PatternBuilder {
             format: String::from(DEFAULT_DATE_FORMAT),
             name: None,
             delimiter: None,
+            name_delimiter: None,
+            time_delimiter: None,
             time: None,
+            century_pivot: None,
+            locale: None,
+            date_order: None,
+            priority: None,
+            case: None,
+            epoch_unit: None,
+            auto_rest: None,
+            date_bounds: None,
         }
     }
 }
@@ -55,41 +295,210 @@ struct MatchedDateTime {
     hour: u32,
     min: u32,
     sec: u32,
+    offset: Option<FixedOffset>,
+    /// Month and day were computed from a week or a day of the year rather
+    /// than captured, so the date order doesn't apply
+    computed: bool,
 }
 
 impl MatchedDateTime {
-    fn new(captures: &Captures) -> Option<Self> {
+    fn new(
+        captures: &Captures,
+        century_pivot: i32,
+        locale: Locale,
+    ) -> Option<Self> {
+        let mut year = parse_year(captures, century_pivot)?;
+        let computed = captures.name("month").is_none()
+            && captures.name("quarter").is_none()
+            && (captures.name("week").is_some()
+                || captures.name("ordinal").is_some());
+
+        let (month, day) = if computed {
+            let date = match parse::<u32>(captures, "week") {
+                Some(week) => iso_week_date(
+                    year,
+                    week,
+                    parse(captures, "weekday").unwrap_or(1),
+                )?,
+                None => {
+                    NaiveDate::from_yo_opt(year, parse(captures, "ordinal")?)?
+                }
+            };
+            // The ISO year may differ from the calendar one
+            year = date.year();
+            (date.month(), date.day())
+        } else {
+            Self::month_day(captures, locale)?
+        };
+
         Some(Self {
-            year: parse(captures, "year")?,
-            month: parse(captures, "month")?,
-            day: parse(captures, "day")?,
+            year,
+            month,
+            day,
             hour: parse(captures, "hour").unwrap_or(0),
             min: parse(captures, "min").unwrap_or(0),
             sec: parse(captures, "sec").unwrap_or(0),
+            offset: match captures.name("tz") {
+                Some(tz) => Some(parse_offset(tz.as_str())?),
+                None => None,
+            },
+            computed,
         })
     }
 
-    /// Try to return the earliest matching local DateTime corresponding to the
-    /// matched date. If it fails, try swapping month and day around to match
-    /// imperial date format
-    fn resolve(&self) -> Option<DateTime<Local>> {
-        match Local
-            .with_ymd_and_hms(
-                self.year, self.month, self.day, self.hour, self.min, self.sec,
-            )
-            .earliest()
-        {
-            Some(time) => Some(time),
+    fn month_day(captures: &Captures, locale: Locale) -> Option<(u32, u32)> {
+        let (month, day) = match parse::<u32>(captures, "quarter") {
+            Some(quarter) => (quarter_first_month(quarter)?, 1),
+            None => {
+                let month = parse_month(captures, locale)?;
+                let day = match parse::<u32>(captures, "week_of_month") {
+                    Some(week) => week_of_month_first_day(week)?,
+                    // Month names are often used without day, e.g. Jan 2024
+                    None if captures.name("day").is_none()
+                        && parse::<u32>(captures, "month").is_none() =>
+                    {
+                        1
+                    }
+                    None => parse(captures, "day")?,
+                };
+                (month, day)
+            }
+        };
+
+        Some((month, day))
+    }
+
+    /// Try to return the earliest matching DateTime corresponding to the
+    /// matched date, interpreting month and day in the given order.
+    ///
+    /// The DateTime is in the captured offset if any, in the local one
+    /// otherwise.
+    ///
+    /// With DateOrder::Auto, if it fails, try swapping month and day around to
+    /// match imperial date format
+    fn resolve(&self, order: DateOrder) -> Option<DateTime<FixedOffset>> {
+        if self.computed {
+            return self.with_month_day(self.month, self.day);
+        }
+
+        match order {
+            DateOrder::Auto => self
+                .with_month_day(self.month, self.day)
+                .or_else(|| self.with_month_day(self.day, self.month)),
+            DateOrder::MonthDay => self.with_month_day(self.month, self.day),
+            DateOrder::DayMonth => self.with_month_day(self.day, self.month),
+        }
+    }
+
+    fn with_month_day(
+        &self,
+        month: u32,
+        day: u32,
+    ) -> Option<DateTime<FixedOffset>> {
+        let (year, hour, min, sec) = (self.year, self.hour, self.min, self.sec);
+
+        match self.offset {
+            Some(offset) => offset
+                .with_ymd_and_hms(year, month, day, hour, min, sec)
+                .single(),
             None => Local
-                .with_ymd_and_hms(
-                    self.year, self.day, self.month, self.hour, self.min,
-                    self.sec,
-                )
-                .earliest(),
+                .with_ymd_and_hms(year, month, day, hour, min, sec)
+                .earliest()
+                .map(|date_time| date_time.fixed_offset()),
         }
     }
 }
 
+/// Parse a UTC offset such as +0900, -05:30, +02 or Z
+fn parse_offset(tz: &str) -> Option<FixedOffset> {
+    if tz.eq_ignore_ascii_case("z") {
+        return FixedOffset::east_opt(0);
+    }
+
+    let sign = match tz.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = tz[1..].replace(':', "");
+    // \d also captures non-ASCII digits, which would be sliced in the middle
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Date of the given ISO year, week (1 to 53) and weekday (1 for Monday to 7
+/// for Sunday)
+fn iso_week_date(year: i32, week: u32, weekday: u32) -> Option<NaiveDate> {
+    let weekday = match weekday {
+        1..=7 => Weekday::try_from(weekday as u8 - 1).ok()?,
+        _ => return None,
+    };
+
+    NaiveDate::from_isoywd_opt(year, week, weekday)
+}
+
+/// First month of the given quarter (1 to 4)
+fn quarter_first_month(quarter: u32) -> Option<u32> {
+    match quarter {
+        1..=4 => Some((quarter - 1) * 3 + 1),
+        _ => None,
+    }
+}
+
+/// First day of the given week of the month (1 to 5), weeks being counted as
+/// successive 7 days periods starting on the 1st
+fn week_of_month_first_day(week: u32) -> Option<u32> {
+    match week {
+        1..=5 => Some((week - 1) * 7 + 1),
+        _ => None,
+    }
+}
+
+/// Parse the month, either as a number or as a name in the given locale
+fn parse_month(captures: &Captures, locale: Locale) -> Option<u32> {
+    let month = captures.name("month")?.as_str();
+
+    match month.parse::<u32>() {
+        Ok(month) => Some(month),
+        Err(_) => locale.month(month),
+    }
+}
+
+/// Parse the year, expanding it with the pivot when it has less than 4 digits
+fn parse_year(captures: &Captures, century_pivot: i32) -> Option<i32> {
+    let year = captures.name("year")?.as_str();
+    let value = year.parse::<i32>().ok()?;
+
+    if year.len() < 4 {
+        Some(expand_year(value, century_pivot))
+    } else {
+        Some(value)
+    }
+}
+
+/// Expand a two-digit year, e.g. 23 to 2023 or 99 to 1999 with a pivot of 70
+fn expand_year(year: i32, century_pivot: i32) -> i32 {
+    if year < century_pivot {
+        2000 + year
+    } else {
+        1900 + year
+    }
+}
+
 fn parse<T>(captures: &Captures, name: &str) -> Option<T>
 where
     T: FromStr,
@@ -109,25 +518,105 @@ impl Pattern {
         name: &str,
         table: &toml::Table,
         default_format: &str,
-    ) -> Option<Self> {
+    ) -> Result<Self, String> {
         Self::builder().deserialize(name, table, default_format)
     }
 
     pub fn time(&self) -> bool {
         self.time
     }
-}
 
-impl Matcher for Pattern {
-    fn determine(
+    /// Named captures the regex lacks to determine a date, empty if none
+    ///
+    /// The day is optional, as a month name alone gives the first of the
+    /// month, and the month can be replaced by a quarter, week or ordinal. An
+    /// epoch timestamp replaces all of them
+    pub fn missing_date_captures(&self) -> Vec<&'static str> {
+        let names: Vec<&str> = self.regex.capture_names().flatten().collect();
+        let mut missing = Vec::<&'static str>::default();
+
+        if names.contains(&"epoch") {
+            return missing;
+        }
+
+        if !names.contains(&"year") {
+            missing.push("year");
+        }
+        if !["month", "quarter", "week", "ordinal"]
+            .iter()
+            .any(|name| names.contains(name))
+        {
+            missing.push("month");
+        }
+
+        missing
+    }
+
+    /// Determine the name and date-time from any text, e.g. a directory name,
+    /// instead of a file stem
+    ///
+    /// Dates outside of the DateBounds of the pattern are rejected as
+    /// implausible
+    pub fn determine_text(
         &self,
-        replacement: &Replacement,
-    ) -> Option<(String, DateTime<Local>)> {
-        let captures = self.regex.captures(&replacement.file_stem)?;
-        let date_time = MatchedDateTime::new(&captures)?.resolve()?;
+        text: &str,
+    ) -> Option<(String, DateTime<FixedOffset>)> {
+        let captures = self.regex.captures(text)?;
+        let date_time = self.date_time(&captures)?;
+
+        if !self.date_bounds.contains(date_time.date_naive()) {
+            log::debug!(
+                "Pattern {}: implausible date {} in {:?}",
+                self.name,
+                date_time.date_naive(),
+                text
+            );
+            return None;
+        }
+
+        Some((self.name_from(text, &captures), date_time))
+    }
+
+    /// Date-time of the captured epoch timestamp if any, of the captured date
+    /// and time otherwise
+    fn date_time(&self, captures: &Captures) -> Option<DateTime<FixedOffset>> {
+        if let Some(epoch) = captures.name("epoch") {
+            return self.epoch_unit.date_time(epoch.as_str());
+        }
+
+        MatchedDateTime::new(captures, self.century_pivot, self.locale)?
+            .resolve(self.date_order)
+    }
+
+    /// Check if the regex has a group capturing a part of the name
+    fn has_name_groups(&self) -> bool {
+        self.regex
+            .capture_names()
+            .flatten()
+            .any(|name| ["start", "end", "rest"].contains(&name))
+    }
 
+    /// Captured name parts, joined by the name delimiter
+    ///
+    /// With auto_rest and no group capturing a part of the name, the text
+    /// before and after the match is used instead
+    ///
+    /// The counter, e.g. the sequence number of burst photos, comes last and
+    /// is kept verbatim so its zero-padding preserves the ordering
+    fn name_from(&self, text: &str, captures: &Captures) -> String {
         let mut elements = Vec::<String>::default();
 
+        if self.auto_rest && !self.has_name_groups() {
+            let matched =
+                captures.get(0).map_or(0..0, |matched| matched.range());
+            elements.extend(
+                [&text[..matched.start], &text[matched.end..]]
+                    .iter()
+                    .map(|part| part.trim_matches(AUTO_REST_TRIMMED))
+                    .filter(|part| !part.is_empty())
+                    .map(String::from),
+            );
+        }
         if let Some(start) = captures.name("start") {
             elements.push(start.as_str().into());
 
@@ -138,8 +627,58 @@ impl Matcher for Pattern {
         if let Some(text) = captures.name("rest") {
             elements.push(text.as_str().into());
         }
+        if let Some(counter) = captures.name("counter") {
+            elements.push(counter.as_str().into());
+        }
+
+        elements.join(&self.name_delimiter)
+    }
+}
+
+impl Matcher for Pattern {
+    fn determine(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        self.determine_with_offset(replacement)
+            .map(|(name, date_time)| (name, date_time.with_timezone(&Local)))
+    }
+
+    /// The offset is the one captured by the tz group, if any
+    fn determine_with_offset(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<FixedOffset>)> {
+        self.determine_text(&replacement.file_stem)
+    }
+
+    /// Only the hour is required to determine the time, so patterns without
+    /// date can be used by composite matchers
+    fn determine_time(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, NaiveTime)> {
+        let captures = self.regex.captures(&replacement.file_stem)?;
+        let time = NaiveTime::from_hms_opt(
+            parse(&captures, "hour")?,
+            parse(&captures, "min").unwrap_or(0),
+            parse(&captures, "sec").unwrap_or(0),
+        )?;
+
+        Some((self.name_from(&replacement.file_stem, &captures), time))
+    }
+
+    fn explain_mismatch(&self, replacement: &Replacement) -> String {
+        let Some(captures) = self.regex.captures(&replacement.file_stem) else {
+            return String::from("no regex match");
+        };
 
-        Some((elements.join(self.delimiter()), date_time))
+        match self.date_time(&captures) {
+            None => String::from("invalid date"),
+            Some(date_time) => {
+                format!("implausible date {}", date_time.date_naive())
+            }
+        }
     }
 
     fn name(&self) -> &str {
@@ -157,6 +696,18 @@ impl Matcher for Pattern {
     fn auto_accept(&self) -> bool {
         false
     }
+
+    fn priority(&self) -> i64 {
+        self.priority
+    }
+
+    fn case(&self) -> Case {
+        self.case
+    }
+
+    fn source(&self) -> Option<&str> {
+        Some(self.regex.as_str())
+    }
 }
 
 impl PatternBuilder {
@@ -175,6 +726,16 @@ impl PatternBuilder {
         self
     }
 
+    pub fn name_delimiter(&mut self, delim: &str) -> &mut Self {
+        self.name_delimiter = Some(delim.into());
+        self
+    }
+
+    pub fn time_delimiter(&mut self, delim: &str) -> &mut Self {
+        self.time_delimiter = Some(delim.into());
+        self
+    }
+
     pub fn format(&mut self, format: &str) -> &mut Self {
         self.format = format.into();
         self
@@ -185,37 +746,166 @@ impl PatternBuilder {
         self
     }
 
+    pub fn century_pivot(&mut self, century_pivot: i32) -> &mut Self {
+        self.century_pivot = Some(century_pivot);
+        self
+    }
+
+    pub fn priority(&mut self, priority: i64) -> &mut Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn locale(&mut self, locale: Locale) -> &mut Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    pub fn date_order(&mut self, date_order: DateOrder) -> &mut Self {
+        self.date_order = Some(date_order);
+        self
+    }
+
+    pub fn case(&mut self, case: Case) -> &mut Self {
+        self.case = Some(case);
+        self
+    }
+
+    pub fn epoch_unit(&mut self, epoch_unit: EpochUnit) -> &mut Self {
+        self.epoch_unit = Some(epoch_unit);
+        self
+    }
+
+    pub fn auto_rest(&mut self, auto_rest: bool) -> &mut Self {
+        self.auto_rest = Some(auto_rest);
+        self
+    }
+
+    pub fn date_bounds(&mut self, date_bounds: DateBounds) -> &mut Self {
+        self.date_bounds = Some(date_bounds);
+        self
+    }
+
+    /// Match ISO 8601 dates, basic or extended, with optional time, naming
+    /// files by the text around them unless auto_rest is set otherwise
+    pub fn iso_date(&mut self) -> &mut Self {
+        self.regex(ISO_DATE_REGEX);
+        self.auto_rest.get_or_insert(true);
+        self
+    }
+
+    /// Build a pattern from its config table, explaining why it can't be
+    /// built otherwise
+    ///
+    /// A regex without a year or epoch capture can't determine a date, so it's
+    /// refused unless it captures an hour, to be the time source of a composite
     pub fn deserialize(
         &mut self,
         name: &str,
         table: &toml::Table,
         default_format: &str,
-    ) -> Option<Pattern> {
+    ) -> Result<Pattern, String> {
         use toml::Value;
 
         self.name(name);
 
-        if let Some(regex) = table.get("regex").and_then(Value::as_str) {
-            self.regex(regex);
-        } else {
-            return None;
+        match (
+            table.get("regex").and_then(Value::as_str),
+            table.get("preset").and_then(Value::as_str),
+        ) {
+            (Some(_), Some(_)) => {
+                return Err(String::from("both regex and preset"));
+            }
+            (Some(regex), None) => {
+                self.regex(regex);
+            }
+            (None, Some("iso")) => {
+                self.iso_date();
+            }
+            (None, Some(preset)) => {
+                return Err(format!("Unknown preset {:?}", preset));
+            }
+            (None, None) => return Err(String::from("missing regex")),
         }
 
         if let Some(delim) = table.get("delimiter").and_then(Value::as_str) {
             self.delimiter(delim);
         }
 
+        if let Some(delim) = table.get("name_delimiter").and_then(Value::as_str)
+        {
+            self.name_delimiter(delim);
+        }
+
+        if let Some(delim) = table.get("time_delimiter").and_then(Value::as_str)
+        {
+            self.time_delimiter(delim);
+        }
+
         if let Some(time) = table.get("time").and_then(Value::as_bool) {
             self.time(time);
         }
 
+        if let Some(pivot) = table
+            .get("century_pivot")
+            .and_then(Value::as_integer)
+            .and_then(|pivot| i32::try_from(pivot).ok())
+        {
+            self.century_pivot(pivot);
+        }
+
+        if let Some(priority) =
+            table.get("priority").and_then(Value::as_integer)
+        {
+            self.priority(priority);
+        }
+
+        if let Some(locale) = table.get("locale").and_then(Value::as_str) {
+            match locale.parse() {
+                Ok(locale) => self.locale(locale),
+                Err(error) => return Err(error.to_string()),
+            };
+        }
+
+        if let Some(order) = table.get("date_order").and_then(Value::as_str) {
+            match order.parse() {
+                Ok(order) => self.date_order(order),
+                Err(error) => return Err(error.to_string()),
+            };
+        }
+
+        if let Some(case) = table.get("case").and_then(Value::as_str) {
+            match case.parse() {
+                Ok(case) => self.case(case),
+                Err(error) => return Err(error.to_string()),
+            };
+        }
+
+        if let Some(unit) = table.get("epoch_unit").and_then(Value::as_str) {
+            match unit.parse() {
+                Ok(unit) => self.epoch_unit(unit),
+                Err(error) => return Err(error.to_string()),
+            };
+        }
+
+        if let Some(auto_rest) = table.get("auto_rest").and_then(Value::as_bool)
+        {
+            self.auto_rest(auto_rest);
+        }
+
         if let Some(format) = table.get("format").and_then(Value::as_str) {
             self.format(format);
         } else {
             self.format(default_format);
         }
 
-        self.build()
+        let pattern = self.build().ok_or("invalid regex")?;
+        let mut names = pattern.regex.capture_names().flatten();
+        if !names.any(|name| ["year", "hour", "epoch"].contains(&name)) {
+            return Err(String::from("no year capture"));
+        }
+
+        Ok(pattern)
     }
 
     pub fn build(&mut self) -> Option<Pattern> {
@@ -223,15 +913,39 @@ impl PatternBuilder {
             .ignore_whitespace(true)
             .build()
             .ok()
-            .map(|regex| Pattern {
-                regex,
-                name: self
-                    .name
-                    .take()
-                    .expect("Name is mandatory to build pattern"),
-                delimiter: self.delimiter.take().unwrap_or(" ".into()),
-                format: std::mem::take(&mut self.format),
-                time: self.time.unwrap_or(false),
+            .map(|regex| {
+                let delimiter = self.delimiter.take().unwrap_or(" ".into());
+
+                Pattern {
+                    regex,
+                    name: self
+                        .name
+                        .take()
+                        .expect("Name is mandatory to build pattern"),
+                    name_delimiter: self
+                        .name_delimiter
+                        .take()
+                        .unwrap_or_else(|| delimiter.clone()),
+                    delimiter,
+                    format: match self.time_delimiter.take() {
+                        Some(delim) => {
+                            with_time_delimiter(&self.format, &delim)
+                        }
+                        None => std::mem::take(&mut self.format),
+                    },
+                    time: self.time.unwrap_or(false),
+                    century_pivot: self
+                        .century_pivot
+                        .take()
+                        .unwrap_or(DEFAULT_CENTURY_PIVOT),
+                    locale: self.locale.take().unwrap_or_default(),
+                    date_order: self.date_order.take().unwrap_or_default(),
+                    priority: self.priority.take().unwrap_or(DEFAULT_PRIORITY),
+                    case: self.case.take().unwrap_or_default(),
+                    epoch_unit: self.epoch_unit.take().unwrap_or_default(),
+                    auto_rest: self.auto_rest.take().unwrap_or(false),
+                    date_bounds: self.date_bounds.take().unwrap_or_default(),
+                }
             })
     }
 }
@@ -239,9 +953,11 @@ impl PatternBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::matcher::Naming;
+    use crate::replacement::Settings;
     use crate::test::{assert_eq, test};
 
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn invalid_regex() {
@@ -260,16 +976,20 @@ mod tests {
         assert_eq!(String::from("foo"), pattern.name);
         assert_eq!(String::from(" "), pattern.delimiter);
         assert_eq!(String::from("foo"), pattern.format);
+        assert_eq!(DEFAULT_PRIORITY, pattern.priority());
 
         let pattern2 = Pattern::builder()
             .regex(".+")
             .name("bar")
             .delimiter("-")
             .format("%Y-%m")
+            .priority(-1)
             .build()
             .unwrap();
         assert_eq!(String::from("bar"), pattern2.name);
+        assert_eq!(-1, pattern2.priority());
         assert_eq!(String::from("-"), pattern2.delimiter);
+        assert_eq!(String::from("-"), pattern2.name_delimiter);
         assert_eq!(String::from("%Y-%m"), pattern2.format);
     }
 
@@ -356,7 +1076,7 @@ mod tests {
     }
 
     #[test]
-    fn pattern_match_start_ymd_end_no_match() {
+    fn pattern_match_start_ymd_end_name_delimiter() {
         let pattern = Pattern::builder()
             .regex(
                 r"
@@ -369,52 +1089,603 @@ mod tests {
                 (?<end>.+)
                 ",
             )
-            .name("foo")
+            .name("with name delim")
+            .name_delimiter("-")
             .build()
             .unwrap();
 
-        let name = PathBuf::from("IMG-20230229-smth.jpb");
-        assert!(pattern.check(&name).is_none());
+        let name = PathBuf::from("IMG-20240120-whatever.jpg");
+        let replacement = pattern.check(&name).unwrap();
+
+        assert_eq!(
+            String::from("2024-01-20 IMG-whatever"),
+            replacement.new_file_stem
+        );
     }
 
     #[test]
-    fn pattern_match_ymd_hms_rest() {
-        use crate::application::DEFAULT_DATE_TIME_FORMAT;
+    fn pattern_match_case() {
+        let build = |case: Case| {
+            Pattern::builder()
+                .regex(
+                    r"(?<start>.+)\s(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})",
+                )
+                .name("case")
+                .case(case)
+                .build()
+                .unwrap()
+        };
+        let check = |case: Case, name: &str| {
+            build(case)
+                .check(&PathBuf::from(name))
+                .map(|replacement| replacement.new_file_stem)
+        };
+
+        let name = "IMG whatever 20240120";
+        assert_eq!(
+            Some("2024-01-20 IMG whatever".into()),
+            check(Case::None, name)
+        );
+        assert_eq!(
+            Some("2024-01-20 img whatever".into()),
+            check(Case::Lower, name)
+        );
+        assert_eq!(
+            Some("2024-01-20 IMG WHATEVER".into()),
+            check(Case::Upper, name)
+        );
+        assert_eq!(
+            Some("2024-01-20 Img Whatever".into()),
+            check(Case::Title, name)
+        );
+        assert_eq!(
+            Some("2024-01-20 Élan Œuvre".into()),
+            check(Case::Title, "éLAN œUVRE 20240120")
+        );
+    }
 
+    #[test]
+    fn pattern_match_normalized() {
         let pattern = Pattern::builder()
-            .regex(
-                r"
-                (?<year>\d{4})
-                (?<month>\d{2})
-                (?<day>\d{2})
-                -
-                (?<hour>\d{2})
-                (?<min>\d{2})
-                (?<sec>\d{2})
-                -
-                (?<rest>.+)
-                ",
-            )
-            .name("test")
-            .format(DEFAULT_DATE_TIME_FORMAT)
+            .regex(r"(?<start>caf\u{e9})\s(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})")
+            .name("cafe")
             .build()
             .unwrap();
-
-        let name = PathBuf::from("20231028-235959-almost midnight.jpg");
-        let replacement = pattern.check(&name).unwrap();
-
-        assert_eq!(
-            String::from("2023-10-28 23h59m59 almost midnight"),
-            replacement.new_file_stem
-        );
-
-        // Invalid date time
-        let invalid_name = PathBuf::from("20230229-256929-whatever.jpg");
-        assert!(pattern.check(&invalid_name).is_none());
+        let decomposed = "cafe\u{301} 20240120";
+
+        // The stem is composed before matching by default
+        let replacement = pattern.check(&PathBuf::from(decomposed)).unwrap();
+        assert_eq!("2024-01-20 caf\u{e9}", replacement.new_file_stem);
+
+        // But only matches once composed
+        let replacement = Replacement {
+            file_stem: decomposed.into(),
+            new_file_stem: decomposed.into(),
+            ..Replacement::default()
+        };
+        assert!(pattern.determine(&replacement).is_none());
     }
 
     #[test]
-    fn pattern_match_ymd_hms() {
+    fn pattern_match_implausible() {
+        let pattern = Pattern::builder()
+            .regex(r"(?<start>.+)\s(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})")
+            .name("ymd")
+            .build()
+            .unwrap();
+        let check = |name: &str| pattern.check(&PathBuf::from(name));
+
+        assert!(check("foo 20240120").is_some());
+        assert!(check("foo 99991231").is_none());
+        assert!(check("foo 19691231").is_none());
+
+        let next_year = Local::now().year() + 1;
+        assert!(check(&format!("foo {}0101", next_year)).is_none());
+    }
+
+    #[test]
+    fn check_explained() {
+        let pattern = Pattern::builder()
+            .regex(r"(?<start>.+)\s(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})")
+            .name("ymd")
+            .build()
+            .unwrap();
+        let explain = |name: &str| {
+            pattern
+                .check_explained(
+                    &PathBuf::from(name),
+                    &Naming::default(),
+                    &Settings::default(),
+                )
+                .map(|replacement| replacement.new_file_stem)
+        };
+
+        assert_eq!(Ok("2024-01-20 foo".into()), explain("foo 20240120"));
+        assert_eq!(Err("no regex match".into()), explain("foo"));
+        assert_eq!(Err("invalid date".into()), explain("foo 20241332"));
+        assert_eq!(
+            Err("implausible date 9999-12-31".into()),
+            explain("foo 99991231")
+        );
+    }
+
+    #[test]
+    fn pattern_match_epoch() {
+        let build = |unit: EpochUnit| {
+            Pattern::builder()
+                .regex(r"(?<epoch>\d{10,13})_(?<rest>.+)")
+                .name("epoch")
+                .epoch_unit(unit)
+                .build()
+                .unwrap()
+        };
+        let expected = DateTime::from_timestamp(1705708800, 0)
+            .unwrap()
+            .with_timezone(&Local);
+
+        let replacement = build(EpochUnit::Seconds)
+            .check(&PathBuf::from("1705708800_note.txt"))
+            .unwrap();
+        assert_eq!(
+            format!("{} note", expected.format("%Y-%m-%d")),
+            replacement.new_file_stem
+        );
+        assert_eq!(Some(expected.fixed_offset()), replacement.date_time);
+        assert_eq!(Some(String::from("%Y-%m-%d")), replacement.date_format);
+
+        let replacement = build(EpochUnit::Milliseconds)
+            .check(&PathBuf::from("1705708800000_note.txt"))
+            .unwrap();
+        assert_eq!(Some(expected.fixed_offset()), replacement.date_time);
+
+        // Interpreted as seconds, this is far in the future
+        assert!(build(EpochUnit::Seconds)
+            .check(&PathBuf::from("1705708800000_note.txt"))
+            .is_none());
+        assert!(build(EpochUnit::Seconds).missing_date_captures().is_empty());
+    }
+
+    #[test]
+    fn epoch_unit_from_str() {
+        assert_eq!(Ok(EpochUnit::Seconds), "seconds".parse());
+        assert_eq!(Ok(EpochUnit::Milliseconds), "ms".parse());
+        assert!("minutes".parse::<EpochUnit>().is_err());
+    }
+
+    #[test]
+    fn date_bounds() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let today = Local::now().date_naive();
+
+        let bounds = DateBounds::default();
+        assert!(bounds.contains(date(1970, 1, 1)));
+        assert!(bounds.contains(today + chrono::Days::new(1)));
+        assert!(!bounds.contains(date(1969, 12, 31)));
+        assert!(!bounds.contains(today + chrono::Days::new(2)));
+
+        let bounds = DateBounds {
+            min: Some(date(2000, 1, 1)),
+            max: Some(date(2099, 12, 31)),
+        };
+        assert!(bounds.contains(date(2099, 12, 31)));
+        assert!(!bounds.contains(date(1999, 12, 31)));
+        assert!(!bounds.contains(date(2100, 1, 1)));
+    }
+
+    #[test]
+    fn pattern_match_whitespace() {
+        let pattern = Pattern::builder()
+            .regex(r"(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})(?<rest>.*)")
+            .name("rest")
+            .build()
+            .unwrap();
+        let check = |name: &str| {
+            pattern
+                .check(&PathBuf::from(name))
+                .map(|replacement| replacement.new_file_stem)
+        };
+
+        assert_eq!(Some("2024-01-20 foo".into()), check("20240120 foo "));
+        assert_eq!(
+            Some("2024-01-20 foo bar".into()),
+            check("20240120  foo   bar")
+        );
+        assert_eq!(
+            Some("2024-01-20 foo bar".into()),
+            check("20240120\tfoo \t bar")
+        );
+        assert_eq!(Some("2024-01-20".into()), check("20240120   "));
+
+        // The date is kept as formatted
+        let pattern = Pattern::builder()
+            .regex(r"(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})(?<rest>.*)")
+            .name("rest")
+            .format("%Y  %m-%d")
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some("2024  01-20 foo".into()),
+            pattern
+                .check(&PathBuf::from("20240120  foo"))
+                .map(|replacement| replacement.new_file_stem)
+        );
+    }
+
+    #[test]
+    fn pattern_match_date_order() {
+        let build = |order: Option<DateOrder>| {
+            let mut builder = Pattern::builder();
+            builder
+                .regex(r"(?<year>\d{4})-(?<month>\d{2})-(?<day>\d{2})")
+                .name("order");
+            if let Some(order) = order {
+                builder.date_order(order);
+            }
+            builder.build().unwrap()
+        };
+        let check = |pattern: &Pattern, name: &str| {
+            pattern
+                .check(&PathBuf::from(name))
+                .map(|replacement| replacement.new_file_stem)
+        };
+
+        let pattern = build(None);
+        assert_eq!(DateOrder::Auto, pattern.date_order);
+        assert_eq!(Some("2023-05-06".into()), check(&pattern, "2023-05-06"));
+        assert_eq!(Some("2023-10-28".into()), check(&pattern, "2023-28-10"));
+
+        let pattern = build(Some(DateOrder::MonthDay));
+        assert_eq!(Some("2023-05-06".into()), check(&pattern, "2023-05-06"));
+        assert_eq!(None, check(&pattern, "2023-28-10"));
+
+        let pattern = build(Some(DateOrder::DayMonth));
+        assert_eq!(Some("2023-06-05".into()), check(&pattern, "2023-05-06"));
+        assert_eq!(Some("2023-10-28".into()), check(&pattern, "2023-28-10"));
+        assert_eq!(None, check(&pattern, "2023-10-28"));
+    }
+
+    #[test]
+    fn determine_time() {
+        let pattern = Pattern::builder()
+            .regex(r"(?<start>.+)_(?<hour>\d{2})(?<min>\d{2})")
+            .name("time")
+            .build()
+            .unwrap();
+        let determine = |name: &str| {
+            pattern.determine_time(
+                &Replacement::try_from(PathBuf::from(name).as_path()).unwrap(),
+            )
+        };
+
+        assert_eq!(
+            Some((
+                String::from("IMG"),
+                NaiveTime::from_hms_opt(13, 45, 0).unwrap()
+            )),
+            determine("IMG_1345.jpg")
+        );
+        assert_eq!(None, determine("IMG_2561.jpg"));
+        assert_eq!(None, determine("IMG.jpg"));
+        assert!(pattern.check(&PathBuf::from("IMG_1345.jpg")).is_none());
+    }
+
+    #[test]
+    fn pattern_match_timezone() {
+        let pattern = Pattern::builder()
+            .regex(r"(?<start>.+)_(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})_(?<hour>\d{2})(?<min>\d{2})(?<tz>[+-]\d{2}:?\d{2}|Z)")
+            .name("tz")
+            .format("%Y-%m-%d %H%M%z")
+            .time(true)
+            .build()
+            .unwrap();
+        let path = PathBuf::from("IMG_20240120_1030+0900.jpg");
+        let replacement = Replacement::try_from(path.as_path()).unwrap();
+        let keep_timezone = |path: &Path| {
+            let naming = Naming {
+                keep_timezone: true,
+                ..Naming::default()
+            };
+            pattern
+                .check_with(path, &naming, &Settings::default())
+                .unwrap()
+                .new_file_stem
+        };
+
+        let (name, date_time) =
+            pattern.determine_with_offset(&replacement).unwrap();
+        assert_eq!("IMG", name);
+        assert_eq!(
+            FixedOffset::east_opt(9 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2024, 1, 20, 10, 30, 0)
+                .unwrap(),
+            date_time
+        );
+        assert_eq!("2024-01-20 1030+0900 IMG", keep_timezone(&path));
+
+        // Without keeping the timezone, the date is converted to local time
+        let (_, local) = pattern.determine(&replacement).unwrap();
+        assert_eq!(date_time, local);
+        assert_eq!(
+            local.format("%Y-%m-%d %H%M%z ").to_string() + "IMG",
+            pattern.check(&path).unwrap().new_file_stem
+        );
+
+        assert_eq!(
+            "2024-01-20 1030-0530 IMG",
+            keep_timezone(Path::new("IMG_20240120_1030-05:30.jpg"))
+        );
+        assert_eq!(
+            "2024-01-20 1030+0000 IMG",
+            keep_timezone(Path::new("IMG_20240120_1030Z"))
+        );
+        assert!(pattern
+            .check(&PathBuf::from("IMG_20240120_1030+2500"))
+            .is_none());
+    }
+
+    #[test]
+    fn parse_offset() {
+        use super::parse_offset;
+
+        let offset = |secs| FixedOffset::east_opt(secs);
+
+        assert_eq!(offset(9 * 3600), parse_offset("+0900"));
+        assert_eq!(offset(9 * 3600), parse_offset("+09:00"));
+        assert_eq!(offset(9 * 3600), parse_offset("+09"));
+        assert_eq!(offset(-(5 * 3600 + 30 * 60)), parse_offset("-0530"));
+        assert_eq!(offset(0), parse_offset("Z"));
+        assert_eq!(None, parse_offset("0900"));
+        assert_eq!(None, parse_offset("+09600"));
+        assert_eq!(None, parse_offset("+0960"));
+        assert_eq!(None, parse_offset("+2500"));
+        assert_eq!(None, parse_offset("+1\u{966}"));
+        assert_eq!(None, parse_offset("+\u{966}\u{966}00"));
+    }
+
+    #[test]
+    fn pattern_match_twice() {
+        let pattern = crate::test::matchers::ymd();
+
+        let first = pattern.check(&PathBuf::from("foo 20240120.jpg")).unwrap();
+        assert_eq!("2024-01-20 foo", first.new_file_stem);
+        // The new name has no date suffix anymore
+        assert!(pattern.check(&first.new_path()).is_none());
+
+        let replacement = pattern
+            .check(&PathBuf::from("2024-01-20 foo 20240120.jpg"))
+            .unwrap();
+        assert_eq!("2024-01-20 foo", replacement.new_file_stem);
+    }
+
+    #[test]
+    fn with_time_delimiter() {
+        use super::with_time_delimiter;
+
+        assert_eq!(
+            "%Y-%m-%d_%Hh%Mm%S",
+            with_time_delimiter("%Y-%m-%d %Hh%Mm%S", "_")
+        );
+        assert_eq!(
+            "%m-%d at %Hh%Mm%S %Y",
+            with_time_delimiter("%m-%d %Hh%Mm%S %Y", " at ")
+        );
+        assert_eq!("%Y%m%dT%H%M", with_time_delimiter("%Y%m%d%H%M", "T"));
+        assert_eq!("%-d/%-m-%I%p", with_time_delimiter("%-d/%-m %I%p", "-"));
+        assert_eq!("_%H", with_time_delimiter("%H", "_"));
+        assert_eq!("%Y-%m-%d", with_time_delimiter("%Y-%m-%d", "_"));
+    }
+
+    #[test]
+    fn pattern_match_time_delimiter() {
+        let pattern = Pattern::builder()
+            .regex(
+                r"(?<start>.+)_(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})_(?<hour>\d{2})(?<min>\d{2})(?<sec>\d{2})",
+            )
+            .name("time delim")
+            .format("%Y-%m-%d %Hh%Mm%S")
+            .time_delimiter("_")
+            .time(true)
+            .build()
+            .unwrap();
+        assert_eq!("%Y-%m-%d_%Hh%Mm%S", pattern.date_format());
+
+        let replacement = pattern
+            .check(&PathBuf::from("IMG_20240120_103000.jpg"))
+            .unwrap();
+        assert_eq!("2024-01-20_10h30m00 IMG", replacement.new_file_stem);
+    }
+
+    #[test]
+    fn pattern_match_auto_rest() {
+        let build = |auto_rest| {
+            Pattern::builder()
+                .regex(r"(?<year>\d{4})-(?<month>\d{2})-(?<day>\d{2})")
+                .name("auto")
+                .auto_rest(auto_rest)
+                .build()
+                .unwrap()
+        };
+
+        let check = |pattern: &Pattern, path: &str| {
+            pattern
+                .check(&PathBuf::from(path))
+                .map(|replacement| replacement.new_file_stem)
+        };
+
+        let pattern = build(true);
+        assert_eq!(
+            Some(String::from("2024-01-20 Invoice ACME")),
+            check(&pattern, "Invoice 2024-01-20 ACME.pdf")
+        );
+        assert_eq!(
+            Some(String::from("2024-01-20 Invoice")),
+            check(&pattern, "Invoice_2024-01-20.pdf")
+        );
+        assert_eq!(
+            Some(String::from("2024-01-20 scan")),
+            check(&pattern, "2024-01-20-scan.pdf")
+        );
+
+        // Without auto_rest, only the captured groups form the name
+        let pattern = build(false);
+        assert_eq!(
+            Some(String::from("2024-01-20")),
+            check(&pattern, "Invoice 2024-01-20 ACME.pdf")
+        );
+
+        // Groups capturing the name take precedence
+        let pattern = Pattern::builder()
+            .regex(r"(?<year>\d{4})-(?<month>\d{2})-(?<day>\d{2})\s(?<rest>.+)")
+            .name("auto")
+            .auto_rest(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some(String::from("2024-01-20 ACME")),
+            check(&pattern, "Invoice 2024-01-20 ACME.pdf")
+        );
+    }
+
+    #[test]
+    fn pattern_match_iso_preset() {
+        let pattern = Pattern::builder()
+            .iso_date()
+            .name("iso")
+            .format("%Y-%m-%d %H:%M:%S")
+            .build()
+            .unwrap();
+
+        let check = |path: &str| {
+            pattern
+                .check(&PathBuf::from(path))
+                .map(|replacement| replacement.new_file_stem)
+        };
+
+        for path in [
+            "report 20240120.pdf",
+            "report 2024-01-20.pdf",
+            "report_2024-01-20.pdf",
+        ] {
+            assert_eq!(
+                Some(String::from("2024-01-20 00:00:00 report")),
+                check(path),
+                "{}",
+                path
+            );
+        }
+        for path in [
+            "report 20240120T103005.pdf",
+            "report 2024-01-20T10:30:05.pdf",
+            "report 2024-01-20 10:30:05.pdf",
+        ] {
+            assert_eq!(
+                Some(String::from("2024-01-20 10:30:05 report")),
+                check(path),
+                "{}",
+                path
+            );
+        }
+
+        let replacement = pattern
+            .check(&PathBuf::from("2024-01-20T10:30:00+09:00 call.m4a"))
+            .unwrap();
+        assert_eq!(
+            FixedOffset::east_opt(9 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2024, 1, 20, 10, 30, 0)
+                .single(),
+            replacement.date_time
+        );
+
+        assert_eq!(None, check("report 2024.pdf"));
+    }
+
+    #[test]
+    fn pattern_match_start_ymd_end_no_match() {
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                (?<start>[A-Z]+)
+                -
+                (?<year>\d{4})
+                (?<month>\d{2})
+                (?<day>\d{2})
+                -
+                (?<end>.+)
+                ",
+            )
+            .name("foo")
+            .build()
+            .unwrap();
+
+        let name = PathBuf::from("IMG-20230229-smth.jpb");
+        assert!(pattern.check(&name).is_none());
+    }
+
+    #[test]
+    fn pattern_match_ymd_hms_rest() {
+        use crate::application::DEFAULT_DATE_TIME_FORMAT;
+
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                (?<year>\d{4})
+                (?<month>\d{2})
+                (?<day>\d{2})
+                -
+                (?<hour>\d{2})
+                (?<min>\d{2})
+                (?<sec>\d{2})
+                -
+                (?<rest>.+)
+                ",
+            )
+            .name("test")
+            .format(DEFAULT_DATE_TIME_FORMAT)
+            .build()
+            .unwrap();
+
+        let name = PathBuf::from("20231028-235959-almost midnight.jpg");
+        let replacement = pattern.check(&name).unwrap();
+
+        assert_eq!(
+            String::from("2023-10-28 23h59m59 almost midnight"),
+            replacement.new_file_stem
+        );
+
+        // Invalid date time
+        let invalid_name = PathBuf::from("20230229-256929-whatever.jpg");
+        assert!(pattern.check(&invalid_name).is_none());
+    }
+
+    #[test]
+    fn pattern_match_counter() {
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                (?<start>[A-Z]+)_
+                (?<year>\d{4})(?<month>\d{2})(?<day>\d{2})
+                (?:_(?<rest>[a-z]+))?
+                _(?<counter>\d+)
+                ",
+            )
+            .name("burst")
+            .name_delimiter("_")
+            .build()
+            .unwrap();
+
+        let name = PathBuf::from("IMG_20240120_0007.jpg");
+        let replacement = pattern.check(&name).unwrap();
+        assert_eq!("2024-01-20 IMG_0007", replacement.new_file_stem);
+
+        let name = PathBuf::from("IMG_20240120_burst_0099.jpg");
+        let replacement = pattern.check(&name).unwrap();
+        assert_eq!("2024-01-20 IMG_burst_0099", replacement.new_file_stem);
+    }
+
+    #[test]
+    fn pattern_match_ymd_hms() {
         let pattern = Pattern::builder()
             .regex(
                 r"
@@ -445,6 +1716,286 @@ mod tests {
         );
     }
 
+    #[test]
+    fn source() {
+        let pattern = Pattern::builder()
+            .regex(r"(?<year>\d{4}) (?<rest>.+)")
+            .name("year")
+            .build()
+            .unwrap();
+
+        assert_eq!(Some(r"(?<year>\d{4}) (?<rest>.+)"), pattern.source());
+    }
+
+    #[test]
+    fn missing_date_captures() {
+        let missing = |regex: &str| {
+            Pattern::builder()
+                .regex(regex)
+                .name("foo")
+                .build()
+                .unwrap()
+                .missing_date_captures()
+        };
+
+        assert!(
+            missing(r"(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})").is_empty()
+        );
+        assert!(missing(r"Q(?<quarter>\d)-(?<year>\d{4})").is_empty());
+        assert!(missing(r"(?<year>\d{4})-W(?<week>\d{2})").is_empty());
+        assert_eq!(vec!["year"], missing(r"(?<month>\d{2})(?<day>\d{2})"));
+        assert_eq!(vec!["year", "month"], missing(r"(?<hour>\d{2})"));
+    }
+
+    #[test]
+    fn pattern_match_quarter() {
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                Q(?<quarter>\d)
+                -
+                (?<year>\d{4})
+                \s
+                (?<rest>.+)
+                ",
+            )
+            .name("quarter")
+            .build()
+            .unwrap();
+
+        let replacement = pattern.check(&PathBuf::from("Q1-2024 report.pdf"));
+        assert_eq!(
+            String::from("2024-01-01 report"),
+            replacement.unwrap().new_file_stem
+        );
+
+        let replacement = pattern.check(&PathBuf::from("Q4-2024 report.pdf"));
+        assert_eq!(
+            String::from("2024-10-01 report"),
+            replacement.unwrap().new_file_stem
+        );
+
+        assert!(pattern.check(&PathBuf::from("Q5-2024 report")).is_none());
+    }
+
+    #[test]
+    fn pattern_match_week_of_month() {
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                (?<year>\d{4})
+                -
+                (?<month>\d{2})
+                -W
+                (?<week_of_month>\d)
+                -
+                (?<rest>.+)
+                ",
+            )
+            .name("week_of_month")
+            .build()
+            .unwrap();
+
+        let replacement = pattern.check(&PathBuf::from("2024-03-W2-report"));
+        assert_eq!(
+            String::from("2024-03-08 report"),
+            replacement.unwrap().new_file_stem
+        );
+
+        let replacement = pattern.check(&PathBuf::from("2024-03-W1-report"));
+        assert_eq!(
+            String::from("2024-03-01 report"),
+            replacement.unwrap().new_file_stem
+        );
+
+        assert!(pattern.check(&PathBuf::from("2024-03-W6-report")).is_none());
+        // The 5th week of February 2023 would start on the 29th
+        assert!(pattern.check(&PathBuf::from("2023-02-W5-report")).is_none());
+    }
+
+    #[test]
+    fn pattern_match_iso_week() {
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                (?<year>\d{4})
+                -W
+                (?<week>\d{2})
+                (-(?<weekday>\d))?
+                \s
+                (?<rest>.+)
+                ",
+            )
+            .name("iso_week")
+            .build()
+            .unwrap();
+
+        let replacement = pattern.check(&PathBuf::from("2024-W03-1 notes.md"));
+        assert_eq!(
+            String::from("2024-01-15 notes"),
+            replacement.unwrap().new_file_stem
+        );
+
+        let replacement = pattern.check(&PathBuf::from("2024-W03-7 notes.md"));
+        assert_eq!(
+            String::from("2024-01-21 notes"),
+            replacement.unwrap().new_file_stem
+        );
+
+        // Monday by default
+        let replacement = pattern.check(&PathBuf::from("2024-W03 notes.md"));
+        assert_eq!(
+            String::from("2024-01-15 notes"),
+            replacement.unwrap().new_file_stem
+        );
+
+        // The first ISO week of 2020 starts in 2019
+        let replacement = pattern.check(&PathBuf::from("2020-W01-1 notes.md"));
+        assert_eq!(
+            String::from("2019-12-30 notes"),
+            replacement.unwrap().new_file_stem
+        );
+
+        assert!(pattern.check(&PathBuf::from("2024-W54-1 notes")).is_none());
+        assert!(pattern.check(&PathBuf::from("2024-W03-8 notes")).is_none());
+
+        // The date order doesn't apply to computed dates
+        let pattern = Pattern::builder()
+            .regex(
+                r"(?<year>\d{4})-W(?<week>\d{2})-(?<weekday>\d)\s(?<rest>.+)",
+            )
+            .name("iso_week")
+            .date_order(DateOrder::DayMonth)
+            .build()
+            .unwrap();
+        let replacement = pattern.check(&PathBuf::from("2024-W03-1 notes.md"));
+        assert_eq!(
+            String::from("2024-01-15 notes"),
+            replacement.unwrap().new_file_stem
+        );
+    }
+
+    #[test]
+    fn pattern_match_day_of_year() {
+        let pattern = Pattern::builder()
+            .regex(r"(?<year>\d{4})-(?<ordinal>\d{3})\s(?<rest>.+)")
+            .name("day_of_year")
+            .build()
+            .unwrap();
+
+        let replacement = pattern.check(&PathBuf::from("2024-060 notes.md"));
+        assert_eq!(
+            String::from("2024-02-29 notes"),
+            replacement.unwrap().new_file_stem
+        );
+
+        assert!(pattern.check(&PathBuf::from("2023-366 notes")).is_none());
+    }
+
+    #[test]
+    fn pattern_match_two_digit_year() {
+        let regex = r"
+            (?<rest>.+)
+            \s
+            (?<year>\d{2}|\d{4})
+            -
+            (?<month>\d{2})
+            -
+            (?<day>\d{2})
+        ";
+        let check = |pattern: &Pattern, name: &str| {
+            pattern.check(&PathBuf::from(name)).unwrap().new_file_stem
+        };
+
+        let pattern = Pattern::builder()
+            .regex(regex)
+            .name("two_digit_year")
+            .build()
+            .unwrap();
+        assert_eq!(DEFAULT_CENTURY_PIVOT, pattern.century_pivot);
+
+        assert_eq!("2023-10-28 relevé", check(&pattern, "relevé 23-10-28.pdf"));
+        assert_eq!("1999-10-28 relevé", check(&pattern, "relevé 99-10-28.pdf"));
+        assert_eq!(
+            "2023-10-28 relevé",
+            check(&pattern, "relevé 2023-10-28.pdf")
+        );
+
+        let pattern = Pattern::builder()
+            .regex(regex)
+            .name("two_digit_year")
+            .century_pivot(20)
+            .build()
+            .unwrap();
+        assert_eq!("1975-10-28 relevé", check(&pattern, "relevé 75-10-28.pdf"));
+        assert_eq!("2019-10-28 relevé", check(&pattern, "relevé 19-10-28.pdf"));
+    }
+
+    #[test]
+    fn month_names() {
+        assert_eq!(Some(1), Locale::English.month("Jan"));
+        assert_eq!(Some(1), Locale::English.month("JANUARY"));
+        assert_eq!(Some(9), Locale::English.month("Sept."));
+        assert_eq!(Some(1), Locale::French.month("janvier"));
+        assert_eq!(Some(2), Locale::French.month("Févr."));
+        assert_eq!(Some(3), Locale::French.month("mars"));
+        assert_eq!(Some(8), Locale::French.month("aout"));
+        assert_eq!(None, Locale::English.month("janvier"));
+        assert_eq!(None, Locale::French.month("foo"));
+    }
+
+    #[test]
+    fn pattern_match_month_name() {
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                (?<rest>.+)
+                \s
+                (?<month>\p{L}+)
+                \s
+                (?<year>\d{4})
+                ",
+            )
+            .name("month_name")
+            .build()
+            .unwrap();
+        assert_eq!(Locale::English, pattern.locale);
+
+        let replacement = pattern.check(&PathBuf::from("Invoice Jan 2024.pdf"));
+        assert_eq!(
+            String::from("2024-01-01 Invoice"),
+            replacement.unwrap().new_file_stem
+        );
+        assert!(pattern.check(&PathBuf::from("Invoice Foo 2024")).is_none());
+
+        let pattern = Pattern::builder()
+            .regex(
+                r"
+                (?<rest>.+)
+                \s
+                (?<day>\d{1,2})
+                \s
+                (?<month>\p{L}+)
+                \s
+                (?<year>\d{4})
+                ",
+            )
+            .name("month_name")
+            .locale(Locale::French)
+            .build()
+            .unwrap();
+
+        let replacement =
+            pattern.check(&PathBuf::from("rapport 15 mars 2024.pdf"));
+        assert_eq!(
+            String::from("2024-03-15 rapport"),
+            replacement.unwrap().new_file_stem
+        );
+        assert!(pattern
+            .check(&PathBuf::from("rapport 15 march 2024"))
+            .is_none());
+    }
+
     mod deserialize {
         use super::*;
         use crate::test::{assert_eq, test};
@@ -453,7 +2004,7 @@ mod tests {
         #[test]
         fn empty_map() {
             let table = Table::new();
-            assert!(Pattern::deserialize("foo", &table, "").is_none());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
         }
 
         #[test]
@@ -461,7 +2012,7 @@ mod tests {
             let mut table = Table::new();
             table.insert("delimiter".into(), "foo".into());
 
-            assert!(Pattern::deserialize("foo", &table, "").is_none());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
         }
 
         #[test]
@@ -469,14 +2020,29 @@ mod tests {
             let mut table = Table::new();
             table.insert("regex".into(), "((".into());
 
-            assert!(Pattern::deserialize("foo", &table, "").is_none());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
         }
 
         #[test]
-        fn simple() {
+        fn without_year() {
             let mut table = Table::new();
             table.insert("regex".into(), ".+".into());
 
+            assert_eq!(
+                Err(String::from("no year capture")),
+                Pattern::deserialize("foo", &table, "").map(|p| p.name)
+            );
+
+            // Time sources of composite matchers only capture the time
+            table.insert("regex".into(), r"(?<hour>\d{2})".into());
+            assert!(Pattern::deserialize("foo", &table, "").is_ok());
+        }
+
+        #[test]
+        fn simple() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+
             let pattern = Pattern::deserialize("foo", &table, "").unwrap();
 
             assert_eq!("foo", pattern.name());
@@ -486,7 +2052,7 @@ mod tests {
         #[test]
         fn with_format() {
             let mut table = Table::new();
-            table.insert("regex".into(), ".+".into());
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
             table.insert("format".into(), "%Y-%m-%d %Hh%M".into());
 
             let pattern = Pattern::deserialize("bar", &table, "").unwrap();
@@ -498,7 +2064,7 @@ mod tests {
         #[test]
         fn with_delimiter() {
             let mut table = Table::new();
-            table.insert("regex".into(), ".+".into());
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
             table.insert("delimiter".into(), ".+".into());
 
             let pattern = Pattern::deserialize("foo", &table, "").unwrap();
@@ -506,5 +2072,167 @@ mod tests {
             assert_eq!("foo", pattern.name());
             assert_eq!(".+", pattern.delimiter());
         }
+
+        #[test]
+        fn with_name_delimiter() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+            table.insert("delimiter".into(), "_".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!("_", pattern.name_delimiter);
+
+            table.insert("name_delimiter".into(), "-".into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!("_", pattern.delimiter());
+            assert_eq!("-", pattern.name_delimiter);
+        }
+
+        #[test]
+        fn with_time_delimiter() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+            table.insert("time_delimiter".into(), "_".into());
+
+            let pattern =
+                Pattern::deserialize("foo", &table, "%Y-%m-%d %Hh%M").unwrap();
+            assert_eq!("%Y-%m-%d_%Hh%M", pattern.date_format());
+
+            table.insert("format".into(), "%d/%m/%Y %H:%M".into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!("%d/%m/%Y_%H:%M", pattern.date_format());
+        }
+
+        #[test]
+        fn with_priority() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+            table.insert("priority".into(), 10.into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(10, pattern.priority);
+        }
+
+        #[test]
+        fn with_century_pivot() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(DEFAULT_CENTURY_PIVOT, pattern.century_pivot);
+
+            table.insert("century_pivot".into(), 30.into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(30, pattern.century_pivot);
+        }
+
+        #[test]
+        fn with_locale() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+            table.insert("locale".into(), "fr".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(Locale::French, pattern.locale);
+
+            table.insert("locale".into(), "foo".into());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
+        }
+
+        #[test]
+        fn with_date_order() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(DateOrder::Auto, pattern.date_order);
+
+            table.insert("date_order".into(), "day_month".into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(DateOrder::DayMonth, pattern.date_order);
+
+            table.insert("date_order".into(), "month_day".into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(DateOrder::MonthDay, pattern.date_order);
+
+            table.insert("date_order".into(), "foo".into());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
+        }
+
+        #[test]
+        fn with_epoch_unit() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<epoch>\d+)".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(EpochUnit::Seconds, pattern.epoch_unit);
+
+            table.insert("epoch_unit".into(), "milliseconds".into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(EpochUnit::Milliseconds, pattern.epoch_unit);
+
+            table.insert("epoch_unit".into(), "foo".into());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
+        }
+
+        #[test]
+        fn with_auto_rest() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert!(!pattern.auto_rest);
+
+            table.insert("auto_rest".into(), true.into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert!(pattern.auto_rest);
+        }
+
+        #[test]
+        fn with_preset() {
+            let mut table = Table::new();
+            table.insert("preset".into(), "iso".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(ISO_DATE_REGEX, pattern.regex.as_str());
+            assert!(pattern.auto_rest);
+
+            table.insert("auto_rest".into(), false.into());
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert!(!pattern.auto_rest);
+
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
+
+            let mut table = Table::new();
+            table.insert("preset".into(), "foo".into());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
+        }
+
+        #[test]
+        fn with_case() {
+            let mut table = Table::new();
+            table.insert("regex".into(), r"(?<year>\d{4})".into());
+
+            let pattern = Pattern::deserialize("foo", &table, "").unwrap();
+            assert_eq!(Case::None, pattern.case);
+
+            // The builder case is the default of the table
+            let pattern = Pattern::builder()
+                .case(Case::Upper)
+                .deserialize("foo", &table, "")
+                .unwrap();
+            assert_eq!(Case::Upper, pattern.case);
+
+            table.insert("case".into(), "title".into());
+            let pattern = Pattern::builder()
+                .case(Case::Upper)
+                .deserialize("foo", &table, "")
+                .unwrap();
+            assert_eq!(Case::Title, pattern.case);
+
+            table.insert("case".into(), "foo".into());
+            assert!(Pattern::deserialize("foo", &table, "").is_err());
+        }
     }
 }
@@ -0,0 +1,203 @@
+#![cfg(feature = "pdf")]
+
+use crate::matcher::Matcher;
+use crate::replacement::Replacement;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone};
+use lopdf::{Document, Object};
+
+pub const PDF_META: &str = "pdf_meta";
+
+/// Match PDF files by the creation date of their info dictionary
+#[derive(Default, Clone)]
+pub struct PdfMeta {
+    format: String,
+}
+
+impl PdfMeta {
+    pub fn new(format: &str) -> Self {
+        Self {
+            format: format.to_string(),
+        }
+    }
+}
+
+/// CreationDate of the info dictionary of the document, if any
+fn creation_date(document: &Document) -> Option<DateTime<Local>> {
+    let info = match document.trailer.get(b"Info").ok()? {
+        Object::Reference(id) => document.get_dictionary(*id).ok()?,
+        Object::Dictionary(info) => info,
+        _ => return None,
+    };
+
+    match info.get(b"CreationDate").ok()? {
+        Object::String(bytes, _) => {
+            parse_date(std::str::from_utf8(bytes).ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// Parse a PDF date string, `D:YYYYMMDDHHmmSSOHH'mm'`, where all the parts
+/// after the year are optional
+///
+/// Without offset, the date is considered local
+fn parse_date(text: &str) -> Option<DateTime<Local>> {
+    let text = text.strip_prefix("D:").unwrap_or(text);
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (date, offset) = text.split_at(digits);
+
+    let field = |start: usize, len: usize, default: u32| -> Option<u32> {
+        match date.get(start..start + len) {
+            Some(value) => value.parse().ok(),
+            None if date.len() <= start => Some(default),
+            None => None,
+        }
+    };
+
+    let date_time = NaiveDate::from_ymd_opt(
+        date.get(0..4)?.parse().ok()?,
+        field(4, 2, 1)?,
+        field(6, 2, 1)?,
+    )?
+    .and_hms_opt(field(8, 2, 0)?, field(10, 2, 0)?, field(12, 2, 0)?)?;
+
+    match parse_offset(offset)? {
+        Some(offset) => offset
+            .from_local_datetime(&date_time)
+            .single()
+            .map(|date_time| date_time.with_timezone(&Local)),
+        None => Local.from_local_datetime(&date_time).earliest(),
+    }
+}
+
+/// Parse the offset of a PDF date, `Z`, `+HH'mm'` or `-HH'mm'`, Some(None)
+/// meaning the date has no offset
+fn parse_offset(text: &str) -> Option<Option<FixedOffset>> {
+    let sign = match text.chars().next() {
+        None => return Some(None),
+        Some('Z') => return Some(FixedOffset::east_opt(0)),
+        Some('+') => 1,
+        Some('-') => -1,
+        Some(_) => return None,
+    };
+
+    let mut parts = text[1..].split('\'');
+    let hours: i32 = parts.next()?.parse().ok()?;
+    let minutes: i32 = match parts.next() {
+        Some(minutes) if !minutes.is_empty() => minutes.parse().ok()?,
+        _ => 0,
+    };
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(Some)
+}
+
+impl Matcher for PdfMeta {
+    fn determine(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        if !replacement.extension.eq_ignore_ascii_case("pdf") {
+            return None;
+        }
+
+        let document = Document::load(replacement.path()).ok()?;
+        let date_time = creation_date(&document)?;
+
+        Some((replacement.file_stem.clone(), date_time))
+    }
+
+    /// Name of the matcher
+    fn name(&self) -> &str {
+        PDF_META
+    }
+    /// Delimiter to place between the matched elements
+    fn delimiter(&self) -> &str {
+        " "
+    }
+    /// Format to use for the date
+    fn date_format(&self) -> &str {
+        self.format.as_str()
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::DEFAULT_DATE_FORMAT;
+    use crate::test::paths::FIXTURES;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    use chrono::Utc;
+
+    #[test]
+    fn check() {
+        let matcher = PdfMeta::new(DEFAULT_DATE_FORMAT);
+        let replacement = matcher.check(&FIXTURES.join("invoice.pdf")).unwrap();
+
+        assert_eq!("invoice", replacement.file_stem);
+        assert_eq!(
+            Some(
+                FixedOffset::east_opt(3600)
+                    .unwrap()
+                    .with_ymd_and_hms(2024, 1, 20, 10, 30, 0)
+                    .unwrap()
+            ),
+            replacement.date_time
+        );
+    }
+
+    #[test]
+    fn check_without_metadata() {
+        with_temp_dir(|temp| {
+            let matcher = PdfMeta::new(DEFAULT_DATE_FORMAT);
+
+            // Not a PDF
+            let child = temp.existing_child("invoice.txt").unwrap();
+            assert!(matcher.check(child.path()).is_none());
+
+            // Not an actual PDF document
+            let child = temp.existing_child("invoice.pdf").unwrap();
+            assert!(matcher.check(child.path()).is_none());
+        });
+    }
+
+    #[test]
+    fn parse_date() {
+        let local = |y, m, d, h, min, s| {
+            Local.with_ymd_and_hms(y, m, d, h, min, s).single()
+        };
+
+        assert_eq!(
+            local(2024, 1, 20, 10, 30, 5),
+            super::parse_date("D:20240120103005")
+        );
+        assert_eq!(local(2024, 1, 1, 0, 0, 0), super::parse_date("D:2024"));
+        assert_eq!(local(2024, 1, 20, 0, 0, 0), super::parse_date("20240120"));
+        assert_eq!(
+            FixedOffset::east_opt(-5 * 3600 - 1800)
+                .unwrap()
+                .with_ymd_and_hms(2024, 1, 20, 10, 30, 0)
+                .single()
+                .map(|date_time| date_time.with_timezone(&Local)),
+            super::parse_date("D:20240120103000-05'30'")
+        );
+        assert_eq!(
+            Utc.with_ymd_and_hms(2024, 1, 20, 10, 30, 0)
+                .single()
+                .map(|date_time| date_time.with_timezone(&Local)),
+            super::parse_date("D:20240120103000Z")
+        );
+
+        assert_eq!(None, super::parse_date("D:202"));
+        assert_eq!(None, super::parse_date("D:20241320"));
+        assert_eq!(None, super::parse_date("D:202401201"));
+        assert_eq!(None, super::parse_date("D:20240120?"));
+    }
+}
@@ -2,23 +2,46 @@ use crate::application::DEFAULT_DATE_FORMAT;
 use crate::matcher::Matcher;
 use crate::replacement::Replacement;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Duration, Local};
 
 #[derive(Default, Clone, Copy)]
 enum When {
     #[default]
     Today,
+    /// Given number of days from today, e.g. -1 for yesterday
+    Offset(i64),
+    /// Explicitly given date
+    Fixed,
 }
 
 impl When {
+    fn from_offset(days: i64) -> Self {
+        match days {
+            0 => When::Today,
+            days => When::Offset(days),
+        }
+    }
+
     fn name(&self) -> &'static str {
         match self {
             When::Today => TODAY,
+            When::Offset(_) => OFFSET,
+            When::Fixed => FIXED,
+        }
+    }
+
+    fn date_time(&self) -> DateTime<Local> {
+        match self {
+            When::Today => Local::now(),
+            When::Offset(days) => Local::now() + Duration::days(*days),
+            When::Fixed => Local::now(),
         }
     }
 }
 
 pub const TODAY: &str = "predetermined_date today";
+pub const OFFSET: &str = "predetermined_date offset";
+pub const FIXED: &str = "predetermined_date fixed";
 
 #[derive(Clone)]
 pub struct PredeterminedDate {
@@ -44,6 +67,26 @@ impl PredeterminedDate {
             ..Self::default()
         }
     }
+
+    /// Prefix by the given date
+    pub fn with_date_time(date_time: DateTime<Local>, format: &str) -> Self {
+        Self {
+            when: When::Fixed,
+            date_time,
+            format: format.to_string(),
+        }
+    }
+
+    /// Prefix by the date the given number of days from today
+    pub fn with_offset(format: &str, days: i64) -> Self {
+        let when = When::from_offset(days);
+
+        Self {
+            when,
+            date_time: when.date_time(),
+            format: format.to_string(),
+        }
+    }
 }
 
 impl Matcher for PredeterminedDate {
@@ -113,4 +156,67 @@ mod tests {
             replacement.new_path()
         );
     }
+
+    #[test]
+    fn check_twice() {
+        use crate::application::DEFAULT_DATE_FORMAT;
+
+        let today = PredeterminedDate::new(DEFAULT_DATE_FORMAT);
+        let first = today.check(&PathBuf::from("foo.bar")).unwrap();
+        let second = today.check(&first.new_path()).unwrap();
+        assert_eq!(first.new_file_stem, second.new_file_stem);
+        assert_eq!(second.file_stem, second.new_file_stem);
+
+        // Any date in the same format is considered as an existing prefix
+        let matcher = PredeterminedDate::with_date_time(
+            date(2024, 1, 20),
+            DEFAULT_DATE_FORMAT,
+        );
+        let replacement =
+            matcher.check(&PathBuf::from("2024-01-21 foo")).unwrap();
+        assert_eq!("2024-01-20 foo", replacement.new_file_stem);
+        let replacement =
+            matcher.check(&PathBuf::from("20240121 foo")).unwrap();
+        assert_eq!("2024-01-20 20240121 foo", replacement.new_file_stem);
+    }
+
+    #[test]
+    fn with_date_time() {
+        use crate::application::DEFAULT_DATE_FORMAT;
+
+        let matcher = PredeterminedDate::with_date_time(
+            date(2024, 1, 20),
+            DEFAULT_DATE_FORMAT,
+        );
+        assert_eq!(FIXED, matcher.name());
+        assert!(matcher.auto_accept());
+
+        let replacement = matcher.check(&PathBuf::from("foo.bar")).unwrap();
+        assert_eq!(PathBuf::from("2024-01-20 foo.bar"), replacement.new_path());
+    }
+
+    #[test]
+    fn with_offset() {
+        use crate::application::DEFAULT_DATE_FORMAT;
+
+        let today = PredeterminedDate::with_offset(DEFAULT_DATE_FORMAT, 0);
+        assert_eq!(TODAY, today.name());
+
+        let yesterday = PredeterminedDate::with_offset(DEFAULT_DATE_FORMAT, -1);
+        assert_eq!(OFFSET, yesterday.name());
+
+        let expected = (Local::now() - Duration::days(1)).date_naive();
+        assert_eq!(expected, yesterday.date_time.date_naive());
+        assert_eq!(
+            format!("{} foo", expected.format(DEFAULT_DATE_FORMAT)),
+            yesterday
+                .check(&PathBuf::from("foo"))
+                .unwrap()
+                .new_file_stem
+        );
+
+        let later = PredeterminedDate::with_offset(DEFAULT_DATE_FORMAT, 10);
+        let expected = (Local::now() + Duration::days(10)).date_naive();
+        assert_eq!(expected, later.date_time.date_naive());
+    }
 }
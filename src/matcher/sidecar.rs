@@ -0,0 +1,377 @@
+use crate::matcher::Matcher;
+use crate::replacement::{Replacement, Settings};
+
+use std::path::{Path, PathBuf};
+
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone,
+};
+use regex::Regex;
+use serde_json::Value;
+
+pub const SIDECAR: &str = "sidecar";
+
+/// Field of the JSON sidecars read unless configured otherwise
+pub const DEFAULT_JSON_FIELD: &str = "date_taken";
+/// Field of the XMP sidecars read unless configured otherwise
+pub const DEFAULT_XMP_FIELD: &str = "exif:DateTimeOriginal";
+
+/// Formats of the dates without offset found in sidecars, tried in order
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y:%m:%d %H:%M:%S%.f",
+];
+
+/// Prefix files by the date found in a sidecar file next to them, e.g.
+/// `photo.jpg.xmp` or `photo.json` for `photo.jpg`
+///
+/// The sidecars are renamed along with the file they describe, see `follow`.
+#[derive(Clone)]
+pub struct Sidecar {
+    format: String,
+    /// Dot-separated path of the date in the JSON sidecars, e.g.
+    /// `photoTakenTime.timestamp`
+    json_field: String,
+    /// Attribute or element holding the date in the XMP sidecars, built
+    /// from the configured field
+    xmp_regex: Option<Regex>,
+}
+
+impl Sidecar {
+    pub fn new(format: &str, json_field: &str, xmp_field: &str) -> Self {
+        let field = regex::escape(xmp_field);
+        let xmp_regex = Regex::new(&format!(
+            r#"{field}\s*=\s*"([^"]*)"|<{field}>([^<]*)</{field}>"#
+        ))
+        .ok();
+
+        Self {
+            format: format.to_string(),
+            json_field: json_field.to_string(),
+            xmp_regex,
+        }
+    }
+
+    /// Date of the first sidecar of the path containing the configured field
+    fn date_time(&self, path: &Path) -> Option<DateTime<FixedOffset>> {
+        sidecars(path, "xmp")
+            .filter_map(|sidecar| std::fs::read_to_string(sidecar).ok())
+            .find_map(|content| self.xmp_date_time(&content))
+            .or_else(|| {
+                sidecars(path, "json")
+                    .filter_map(|sidecar| std::fs::read_to_string(sidecar).ok())
+                    .find_map(|content| self.json_date_time(&content))
+            })
+    }
+
+    fn json_date_time(&self, content: &str) -> Option<DateTime<FixedOffset>> {
+        let json: Value = serde_json::from_str(content).ok()?;
+        let value = self
+            .json_field
+            .split('.')
+            .try_fold(&json, |value, key| value.get(key))?;
+
+        match value {
+            Value::String(string) => parse_date_time(string),
+            Value::Number(number) => timestamp(number.as_i64()?),
+            _ => None,
+        }
+    }
+
+    fn xmp_date_time(&self, content: &str) -> Option<DateTime<FixedOffset>> {
+        let captures = self.xmp_regex.as_ref()?.captures(content)?;
+        let value = captures.get(1).or_else(|| captures.get(2))?;
+
+        parse_date_time(value.as_str().trim())
+    }
+}
+
+/// Candidate sidecars of the path with the given extension, appended to the
+/// file name first, then replacing its extension
+fn candidates(path: &Path, extension: &str) -> [PathBuf; 2] {
+    let mut appended = path.as_os_str().to_owned();
+    appended.push(".");
+    appended.push(extension);
+
+    [PathBuf::from(appended), path.with_extension(extension)]
+}
+
+/// Existing sidecars of the path with the given extension
+fn sidecars<'a>(
+    path: &'a Path,
+    extension: &str,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    candidates(path, extension)
+        .into_iter()
+        .filter(move |sidecar| sidecar != path && sidecar.is_file())
+}
+
+/// Replacements of the sidecars next to the replaced path, so they keep
+/// following the new name of the file, e.g. `photo.jpg.xmp` and `photo.json`
+/// become `2024-01-20 photo.jpg.xmp` and `2024-01-20 photo.json`
+pub fn follow(
+    replacement: &Replacement,
+    settings: &Settings,
+) -> Vec<Replacement> {
+    let path = replacement.path();
+    if path == replacement.new_path() {
+        return Vec::new();
+    }
+
+    let mut replacements = Vec::new();
+    for extension in ["xmp", "json"] {
+        let [appended, replaced] = candidates(&path, extension);
+        let new_file_names = [
+            format!("{}.{}", replacement.new_file_name(), extension),
+            format!("{}.{}", replacement.new_file_stem, extension),
+        ];
+
+        for (sidecar, new_file_name) in [appended, replaced]
+            .into_iter()
+            .zip(new_file_names)
+            .filter(|(sidecar, _)| *sidecar != path && sidecar.is_file())
+        {
+            if let Ok(sidecar) =
+                Replacement::try_from_with(sidecar.as_path(), settings)
+            {
+                let mut sidecar = sidecar
+                    .with_new_file_name(&new_file_name, &settings.extensions);
+                sidecar.new_parent = replacement.new_parent.clone();
+                replacements.push(sidecar);
+            }
+        }
+    }
+
+    replacements
+}
+
+/// Parse a date found in a sidecar, keeping its offset if it has one
+fn parse_date_time(string: &str) -> Option<DateTime<FixedOffset>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(string) {
+        return Some(date_time);
+    }
+    if let Ok(timestamp) = string.parse::<i64>() {
+        return self::timestamp(timestamp);
+    }
+
+    let date_time = DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(string, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(string, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+
+    Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .map(|date_time| date_time.fixed_offset())
+}
+
+/// Date of the given number of seconds since the epoch, in local time
+fn timestamp(seconds: i64) -> Option<DateTime<FixedOffset>> {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|date_time| date_time.with_timezone(&Local).fixed_offset())
+}
+
+impl Matcher for Sidecar {
+    fn determine(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        self.determine_with_offset(replacement)
+            .map(|(name, date_time)| (name, date_time.with_timezone(&Local)))
+    }
+
+    fn determine_with_offset(
+        &self,
+        replacement: &Replacement,
+    ) -> Option<(String, DateTime<FixedOffset>)> {
+        let date_time = self.date_time(&replacement.path())?;
+
+        Some((replacement.file_stem.clone(), date_time))
+    }
+
+    /// Name of the matcher
+    fn name(&self) -> &str {
+        SIDECAR
+    }
+    /// Delimiter to place between the matched elements
+    fn delimiter(&self) -> &str {
+        " "
+    }
+    /// Format to use for the date
+    fn date_format(&self) -> &str {
+        self.format.as_str()
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::DEFAULT_DATE_FORMAT;
+    use crate::matcher::Naming;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    fn matcher() -> Sidecar {
+        Sidecar::new(DEFAULT_DATE_FORMAT, DEFAULT_JSON_FIELD, DEFAULT_XMP_FIELD)
+    }
+
+    #[test]
+    fn check_json() {
+        with_temp_dir(|temp| {
+            let photo = temp.existing_child("photo.jpg").unwrap();
+            temp.child("photo.json")
+                .write_str(r#"{"date_taken": "2024-01-20 10:30:00"}"#)
+                .unwrap();
+
+            let replacement = matcher().check(photo.path()).unwrap();
+            assert_eq!("2024-01-20 photo", replacement.new_file_stem);
+            assert_eq!("jpg", replacement.extension);
+        });
+    }
+
+    #[test]
+    fn check_json_nested_field() {
+        let matcher = Sidecar::new(
+            DEFAULT_DATE_FORMAT,
+            "photoTakenTime.timestamp",
+            DEFAULT_XMP_FIELD,
+        );
+
+        with_temp_dir(|temp| {
+            let photo = temp.existing_child("photo.jpg").unwrap();
+            let timestamp = Local
+                .with_ymd_and_hms(2024, 1, 20, 12, 0, 0)
+                .unwrap()
+                .timestamp();
+            temp.child("photo.jpg.json")
+                .write_str(&format!(
+                    r#"{{"photoTakenTime": {{"timestamp": "{}"}}}}"#,
+                    timestamp
+                ))
+                .unwrap();
+
+            let replacement = matcher.check(photo.path()).unwrap();
+            assert_eq!("2024-01-20 photo", replacement.new_file_stem);
+        });
+    }
+
+    #[test]
+    fn check_xmp() {
+        with_temp_dir(|temp| {
+            let photo = temp.existing_child("photo.jpg").unwrap();
+            temp.child("photo.jpg.xmp")
+                .write_str(
+                    r#"<rdf:Description exif:DateTimeOriginal="2024-01-20T10:30:00+01:00"/>"#,
+                )
+                .unwrap();
+
+            let naming = Naming {
+                keep_timezone: true,
+                ..Naming::default()
+            };
+            let replacement = matcher()
+                .check_with(photo.path(), &naming, &Settings::default())
+                .unwrap();
+            assert_eq!("2024-01-20 photo", replacement.new_file_stem);
+
+            temp.child("photo.jpg.xmp")
+                .write_str(
+                    "<exif:DateTimeOriginal>2023-12-31T23:59:59\
+                    </exif:DateTimeOriginal>",
+                )
+                .unwrap();
+
+            let replacement = matcher().check(photo.path()).unwrap();
+            assert_eq!("2023-12-31 photo", replacement.new_file_stem);
+        });
+    }
+
+    #[test]
+    fn check_without_date() {
+        with_temp_dir(|temp| {
+            let photo = temp.existing_child("photo.jpg").unwrap();
+
+            // No sidecar
+            assert!(matcher().check(photo.path()).is_none());
+
+            // No field
+            temp.child("photo.json")
+                .write_str(r#"{"title": "20240120"}"#)
+                .unwrap();
+            assert!(matcher().check(photo.path()).is_none());
+
+            // Invalid date
+            temp.child("photo.json")
+                .write_str(r#"{"date_taken": "yesterday"}"#)
+                .unwrap();
+            assert!(matcher().check(photo.path()).is_none());
+        });
+    }
+
+    #[test]
+    fn follow() {
+        with_temp_dir(|temp| {
+            let photo = temp.existing_child("photo.jpg").unwrap();
+            temp.existing_child("photo.jpg.xmp").unwrap();
+            temp.existing_child("photo.json").unwrap();
+            temp.existing_child("photo.txt").unwrap();
+
+            let replacement = Replacement::try_from(photo.path())
+                .unwrap()
+                .new_file_stem(String::from("2024-01-20 photo"));
+            let new_paths: Vec<PathBuf> =
+                super::follow(&replacement, &Settings::default())
+                    .iter()
+                    .map(Replacement::new_path)
+                    .collect();
+            assert_eq!(
+                vec![
+                    temp.child("2024-01-20 photo.jpg.xmp").to_path_buf(),
+                    temp.child("2024-01-20 photo.json").to_path_buf(),
+                ],
+                new_paths
+            );
+
+            // Nothing to follow if the file is not renamed
+            let replacement = Replacement::try_from(photo.path()).unwrap();
+            assert!(
+                super::follow(&replacement, &Settings::default()).is_empty()
+            );
+        });
+    }
+
+    #[test]
+    fn parse_date_time() {
+        let expected = Local
+            .with_ymd_and_hms(2024, 1, 20, 10, 30, 0)
+            .unwrap()
+            .fixed_offset();
+
+        for string in [
+            "2024-01-20T10:30:00",
+            "2024-01-20 10:30:00",
+            "2024:01:20 10:30:00",
+            "2024-01-20T10:30:00.250",
+        ] {
+            assert_eq!(
+                expected.date_naive(),
+                super::parse_date_time(string).unwrap().date_naive()
+            );
+        }
+
+        assert_eq!(
+            FixedOffset::east_opt(3600),
+            super::parse_date_time("2024-01-20T10:30:00+01:00")
+                .map(|date_time| *date_time.offset())
+        );
+        assert!(super::parse_date_time("20 January").is_none());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::processing::{Error, Reporter, Stats};
+use crate::replacement::Replacement;
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::Local;
+
+/// Name of the log written in each directory where files were renamed
+pub const FILE_NAME: &str = ".prefix-by-date.log";
+
+/// Append the renames to a log in the directory where they happened, so the
+/// provenance of the names travels with the directory
+///
+/// Each line holds the date of the rename, the old and the new file names,
+/// separated by tabs
+#[derive(Default)]
+pub struct DirLogReporter {
+    dry_run: bool,
+}
+
+impl DirLogReporter {
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run }
+    }
+
+    /// Remove the last line recording the rename, once it was reverted
+    fn remove(&self, replacement: &Replacement) -> io::Result<()> {
+        let path = replacement.parent.join(FILE_NAME);
+        let content = std::fs::read_to_string(&path)?;
+        let (file_name, new_file_name) =
+            (replacement.file_name(), replacement.new_file_name());
+
+        let mut lines = content.lines().collect::<Vec<_>>();
+        let Some(index) = lines.iter().rposition(|line| {
+            let mut fields = line.split('\t').skip(1);
+            fields.next() == Some(file_name.as_str())
+                && fields.next() == Some(new_file_name.as_str())
+        }) else {
+            return Ok(());
+        };
+        lines.remove(index);
+
+        if lines.is_empty() {
+            return std::fs::remove_file(path);
+        }
+        std::fs::write(path, lines.join("\n") + "\n")
+    }
+
+    fn append(&self, replacement: &Replacement) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(replacement.parent.join(FILE_NAME))?;
+
+        writeln!(
+            file,
+            "{}\t{}\t{}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
+            replacement.file_name(),
+            replacement.new_file_name()
+        )
+    }
+}
+
+impl Reporter for DirLogReporter {
+    fn setup(&self, _count: usize) {}
+
+    fn processing(&self, _path: &Path) {}
+
+    fn processing_err(&self, _path: &Path, _error: &Error) {}
+
+    fn finish(&self, _stats: &Stats) {}
+
+    /// Remove the reverted rename from the log of its directory
+    fn processing_undone(&self, replacement: &Replacement) {
+        if self.dry_run {
+            return;
+        }
+
+        if let Err(error) = self.remove(replacement) {
+            log::warn!(
+                "Unable to remove {} from directory log: {}",
+                replacement,
+                error
+            );
+        }
+    }
+
+    /// Record the rename in the log of its directory, unless nothing was
+    /// actually renamed
+    fn processing_ok(&self, replacement: &Replacement) {
+        if self.dry_run || replacement.path() == replacement.new_path() {
+            return;
+        }
+
+        if let Err(error) = self.append(replacement) {
+            log::warn!(
+                "Unable to record {} in directory log: {}",
+                replacement,
+                error
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    fn replacement(temp: &TempDir, name: &str, new_stem: &str) -> Replacement {
+        let mut replacement =
+            Replacement::try_from(temp.child(name).path()).unwrap();
+        replacement.new_file_stem = new_stem.into();
+        replacement
+    }
+
+    fn entries(temp: &TempDir) -> Vec<(String, String)> {
+        std::fs::read_to_string(temp.child(FILE_NAME).path())
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let fields = line.split('\t').collect::<Vec<_>>();
+                assert_eq!(3, fields.len());
+                (fields[1].to_string(), fields[2].to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn processing_ok() {
+        with_temp_dir(|temp| {
+            let reporter = DirLogReporter::new(false);
+            reporter.processing_ok(&replacement(temp, "foo.jpg", "2024 foo"));
+            reporter.processing_ok(&replacement(temp, "bar", "bar"));
+            reporter.processing_ok(&replacement(temp, "baz", "2024 baz"));
+
+            assert_eq!(
+                vec![
+                    ("foo.jpg".into(), "2024 foo.jpg".into()),
+                    ("baz".into(), "2024 baz".into()),
+                ],
+                entries(temp)
+            );
+        });
+    }
+
+    #[test]
+    fn dry_run() {
+        with_temp_dir(|temp| {
+            let reporter = DirLogReporter::new(true);
+            reporter.processing_ok(&replacement(temp, "foo", "2024 foo"));
+
+            temp.child(FILE_NAME).assert(predicates::path::missing());
+        });
+    }
+
+    #[test]
+    fn processing_undone() {
+        with_temp_dir(|temp| {
+            let reporter = DirLogReporter::new(false);
+            let foo = replacement(temp, "foo", "2024 foo");
+            let bar = replacement(temp, "bar", "2024 bar");
+            reporter.processing_ok(&foo);
+            reporter.processing_ok(&bar);
+
+            reporter.processing_undone(&foo);
+            assert_eq!(vec![("bar".into(), "2024 bar".into())], entries(temp));
+
+            reporter.processing_undone(&bar);
+            temp.child(FILE_NAME).assert(predicates::path::missing());
+        });
+    }
+}
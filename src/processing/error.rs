@@ -4,10 +4,21 @@ use std::{error, fmt, io};
 #[derive(Debug)]
 pub enum Error {
     Io(io::Error),
+    PermissionDenied(PathBuf),
+    ReadOnly(PathBuf),
     NotFound(PathBuf),
     NoMatch(PathBuf),
+    MetadataUnavailable(PathBuf, String),
     PathUnwrap(PathBuf, &'static str),
+    PathSeparator(String),
+    IllegalCharacter(String, char),
+    TargetExists(PathBuf),
+    SourceVanished(PathBuf),
+    /// Replacement given for the first path, but renaming the second one
+    MismatchedReplacement(PathBuf, PathBuf),
     Skip(PathBuf),
+    /// Special file, like a FIFO or a socket, left untouched
+    NotRegularFile(PathBuf),
     Abort,
 }
 
@@ -23,12 +34,25 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self {
             Self::Io(error) => fmt::Display::fmt(&error, f),
+            Self::PermissionDenied(path) => {
+                write!(f, "Permission denied: {:?}", path)
+            }
+            Self::ReadOnly(path) => {
+                write!(f, "Read-only file system: {:?}", path)
+            }
             Self::NotFound(path) => {
                 write!(f, "Path not found: {:?}", path)
             }
             Self::NoMatch(path) => {
                 write!(f, "No match for path: {:?}", path)
             }
+            Self::MetadataUnavailable(path, reason) => {
+                write!(
+                    f,
+                    "Unable to determine a date for {:?}: {}",
+                    path, reason
+                )
+            }
             Self::PathUnwrap(path, op) => {
                 write!(
                     f,
@@ -36,9 +60,39 @@ impl fmt::Display for Error {
                     op, path
                 )
             }
+            Self::PathSeparator(file_stem) => {
+                write!(
+                    f,
+                    "Customized name contains a path separator: {:?}",
+                    file_stem
+                )
+            }
+            Self::IllegalCharacter(file_name, c) => {
+                write!(
+                    f,
+                    "New name contains an invalid character {:?}: {:?}",
+                    c, file_name
+                )
+            }
+            Self::TargetExists(path) => {
+                write!(f, "Target already exists: {:?}", path)
+            }
+            Self::SourceVanished(path) => {
+                write!(f, "Path vanished before renaming: {:?}", path)
+            }
+            Self::MismatchedReplacement(path, other) => {
+                write!(
+                    f,
+                    "Replacement for {:?} renames another path: {:?}",
+                    path, other
+                )
+            }
             Self::Skip(path) => {
                 write!(f, "Skipping {:?}", path)
             }
+            Self::NotRegularFile(path) => {
+                write!(f, "Not a regular file: {:?}", path)
+            }
             Self::Abort => {
                 write!(f, "Abort received, quitting...")
             }
@@ -47,6 +101,20 @@ impl fmt::Display for Error {
 }
 
 impl Error {
+    /// Classify an IO error encountered while handling the given path, so
+    /// permission and read-only file system failures get their own variant
+    pub fn io(error: io::Error, path: &Path) -> Error {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied => {
+                Self::PermissionDenied(path.to_path_buf())
+            }
+            io::ErrorKind::ReadOnlyFilesystem => {
+                Self::ReadOnly(path.to_path_buf())
+            }
+            _ => Self::Io(error),
+        }
+    }
+
     pub fn not_found(path: &Path) -> Error {
         Self::NotFound(path.to_path_buf())
     }
@@ -54,4 +122,78 @@ impl Error {
     pub fn no_match(path: &Path) -> Error {
         Self::NoMatch(path.to_path_buf())
     }
+
+    /// Check if the error is a failure, as opposed to a path left untouched
+    /// on purpose, i.e. skipped, without match or not a regular file
+    pub fn is_failure(&self) -> bool {
+        !matches!(
+            self,
+            Self::Skip(_) | Self::NoMatch(_) | Self::NotRegularFile(_)
+        )
+    }
+
+    /// Short name of the variant, e.g. to count errors by kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::ReadOnly(_) => "read_only",
+            Self::NotFound(_) => "not_found",
+            Self::NoMatch(_) => "no_match",
+            Self::MetadataUnavailable(..) => "metadata_unavailable",
+            Self::PathUnwrap(..) => "path_unwrap",
+            Self::PathSeparator(_) => "path_separator",
+            Self::IllegalCharacter(..) => "illegal_character",
+            Self::TargetExists(_) => "target_exists",
+            Self::SourceVanished(_) => "source_vanished",
+            Self::MismatchedReplacement(..) => "mismatched_replacement",
+            Self::Skip(_) => "skip",
+            Self::NotRegularFile(_) => "not_regular_file",
+            Self::Abort => "abort",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    #[test]
+    fn is_failure() {
+        let path = Path::new("/tmp/foo.txt");
+
+        assert!(!Error::Skip(path.to_path_buf()).is_failure());
+        assert!(!Error::no_match(path).is_failure());
+        assert!(!Error::NotRegularFile(path.to_path_buf()).is_failure());
+        assert!(Error::not_found(path).is_failure());
+        assert!(Error::Abort.is_failure());
+    }
+
+    #[test]
+    fn io() {
+        let path = Path::new("/tmp/foo.txt");
+
+        let error =
+            Error::io(io::Error::from(io::ErrorKind::PermissionDenied), path);
+        assert!(matches!(&error, Error::PermissionDenied(p) if p == path));
+        assert_eq!("permission_denied", error.kind());
+        assert_eq!("Permission denied: \"/tmp/foo.txt\"", error.to_string());
+
+        let error =
+            Error::io(io::Error::from(io::ErrorKind::ReadOnlyFilesystem), path);
+        assert!(matches!(&error, Error::ReadOnly(p) if p == path));
+        assert_eq!("read_only", error.kind());
+        assert_eq!(
+            "Read-only file system: \"/tmp/foo.txt\"",
+            error.to_string()
+        );
+
+        let error =
+            Error::io(io::Error::from(io::ErrorKind::StorageFull), path);
+        assert!(matches!(
+            error,
+            Error::Io(e) if e.kind() == io::ErrorKind::StorageFull
+        ));
+    }
 }
@@ -0,0 +1,296 @@
+use crate::fs::Fs;
+use crate::processing::{Error, Reporter};
+use crate::replacement::{Replacement, Settings};
+
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+const EXTENSION: &str = "jsonl";
+
+/// Journal of the renames executed during a run, so they can be undone
+///
+/// Each line is a JSON array holding the old and new paths of a rename
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Journal in the given directory, named after the current date and time
+    ///
+    /// Nothing is written until the first rename is recorded
+    pub fn create(dir: &Path) -> Self {
+        let name = Local::now().format("%Y%m%dT%H%M%S%.6f");
+
+        Self {
+            path: dir.join(format!("{}.{}", name, EXTENSION)),
+        }
+    }
+
+    /// Most recent journal in the given directory, if any
+    pub fn latest(dir: &Path) -> io::Result<Option<Self>> {
+        if !dir.try_exists()? {
+            return Ok(None);
+        }
+
+        let mut paths = Vec::<PathBuf>::default();
+        for entry in dir.read_dir()? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == EXTENSION) {
+                paths.push(path);
+            }
+        }
+
+        Ok(paths.into_iter().max().map(|path| Self { path }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a rename to the journal
+    pub fn record(&self, from: &Path, to: &Path) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(file, "{}", serde_json::to_string(&(from, to))?)
+    }
+
+    /// Remove the last record of the given rename, once it was reverted,
+    /// removing the journal if nothing is left in it
+    pub fn forget(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut entries = self.entries()?;
+        let Some(index) = entries
+            .iter()
+            .rposition(|(old, new)| old == from && new == to)
+        else {
+            return Ok(());
+        };
+        entries.remove(index);
+
+        self.write(&entries)
+    }
+
+    /// Renames recorded in the journal, in the order they were executed
+    pub fn entries(&self) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+        let file = std::fs::File::open(&self.path)?;
+
+        BufReader::new(file)
+            .lines()
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+
+    /// Revert the recorded renames in reverse order, then remove the journal
+    ///
+    /// Entries whose current state no longer matches the journal are skipped
+    /// and reported as errors. They are kept in the journal, to retry them
+    /// once fixed, the journal being removed only if every rename is reverted
+    pub fn undo(
+        self,
+        fs: &dyn Fs,
+        reporter: &dyn Reporter,
+        settings: &Settings,
+    ) -> io::Result<()> {
+        let entries = self.entries()?;
+        reporter.setup(entries.len());
+
+        let mut failed = Vec::<&(PathBuf, PathBuf)>::default();
+        for entry @ (from, to) in entries.iter().rev() {
+            reporter.processing(to);
+
+            match revert(fs, from, to, settings) {
+                Ok(replacement) => reporter.processing_ok(&replacement),
+                Err(error) => {
+                    reporter.processing_err(to, &error);
+                    failed.push(entry);
+                }
+            }
+        }
+
+        let failed = failed.into_iter().rev().cloned().collect::<Vec<_>>();
+        self.write(&failed)
+    }
+
+    /// Replace the content of the journal with the given entries, removing it
+    /// if there are none
+    fn write(&self, entries: &[(PathBuf, PathBuf)]) -> io::Result<()> {
+        if entries.is_empty() {
+            return std::fs::remove_file(&self.path);
+        }
+
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        std::fs::write(&self.path, content)
+    }
+}
+
+/// Rename back `to` into `from`, creating the replacement with the given
+/// settings
+pub(crate) fn revert(
+    fs: &dyn Fs,
+    from: &Path,
+    to: &Path,
+    settings: &Settings,
+) -> crate::processing::Result<Replacement> {
+    if !fs.try_exists(to)? {
+        return Err(Error::not_found(to));
+    }
+    if fs.try_exists(from)? {
+        return Err(Error::TargetExists(from.to_path_buf()));
+    }
+
+    let original = Replacement::try_from_with(from, settings)?;
+    let mut replacement = Replacement::try_from_with(to, settings)?;
+    replacement.new_file_stem = original.file_stem;
+    if replacement.extension != original.extension {
+        replacement.new_extension = Some(original.extension);
+    }
+
+    if replacement.new_path() == from {
+        replacement.execute_with(fs, &settings.rename_retries)?;
+    } else {
+        fs.rename(to, from)?;
+    }
+
+    Ok(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::RealFs;
+    use crate::processing::LogReporter;
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+    use predicates::prelude::*;
+
+    #[test]
+    fn latest() {
+        with_temp_dir(|temp| {
+            let dir = temp.child("journal");
+            assert!(Journal::latest(dir.path()).unwrap().is_none());
+
+            dir.child("20240120T101010.000000.jsonl").touch().unwrap();
+            dir.child("20240121T101010.000000.jsonl").touch().unwrap();
+            dir.child("20240122T101010.000000.txt").touch().unwrap();
+
+            assert_eq!(
+                dir.child("20240121T101010.000000.jsonl").path(),
+                Journal::latest(dir.path()).unwrap().unwrap().path()
+            );
+        });
+    }
+
+    #[test]
+    fn record_and_entries() {
+        with_temp_dir(|temp| {
+            let journal = Journal::create(&temp.child("journal"));
+
+            journal
+                .record(Path::new("/foo"), Path::new("/2024 foo"))
+                .unwrap();
+            journal
+                .record(Path::new("/bar"), Path::new("/2024 bar"))
+                .unwrap();
+
+            assert_eq!(
+                vec![
+                    (PathBuf::from("/foo"), PathBuf::from("/2024 foo")),
+                    (PathBuf::from("/bar"), PathBuf::from("/2024 bar")),
+                ],
+                journal.entries().unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn forget() {
+        with_temp_dir(|temp| {
+            let journal = Journal::create(&temp.child("journal"));
+            let (foo, new_foo) = (Path::new("/foo"), Path::new("/2024 foo"));
+            let (bar, new_bar) = (Path::new("/bar"), Path::new("/2024 bar"));
+
+            journal.record(foo, new_foo).unwrap();
+            journal.record(bar, new_bar).unwrap();
+
+            journal.forget(foo, new_foo).unwrap();
+            assert_eq!(
+                vec![(bar.to_path_buf(), new_bar.to_path_buf())],
+                journal.entries().unwrap()
+            );
+
+            // Unknown renames are left alone
+            journal.forget(foo, new_foo).unwrap();
+            assert_eq!(1, journal.entries().unwrap().len());
+
+            journal.forget(bar, new_bar).unwrap();
+            assert!(!journal.path().exists());
+        });
+    }
+
+    #[test]
+    fn undo() {
+        with_temp_dir(|temp| {
+            let dir = temp.child("journal");
+            let journal = Journal::create(&dir);
+
+            let foo = temp.child("foo.txt");
+            let new_foo = temp.existing_child("2024-01-20 foo.txt").unwrap();
+            journal.record(foo.path(), new_foo.path()).unwrap();
+
+            // Moved away since
+            let bar = temp.child("bar");
+            let new_bar = temp.child("2024-01-20 bar");
+            journal.record(bar.path(), new_bar.path()).unwrap();
+
+            // Original name taken again since
+            let baz = temp.existing_child("baz").unwrap();
+            let new_baz = temp.existing_child("2024-01-20 baz").unwrap();
+            journal.record(baz.path(), new_baz.path()).unwrap();
+
+            let journal = Journal::latest(&dir).unwrap().unwrap();
+            journal
+                .undo(&RealFs, &LogReporter::default(), &Settings::default())
+                .unwrap();
+
+            foo.assert(predicate::path::exists());
+            new_foo.assert(predicate::path::missing());
+            bar.assert(predicate::path::missing());
+            baz.assert(predicate::path::exists());
+            new_baz.assert(predicate::path::exists());
+
+            // The failed renames are kept to be retried
+            let journal = Journal::latest(&dir).unwrap().unwrap();
+            assert_eq!(
+                vec![
+                    (bar.to_path_buf(), new_bar.to_path_buf()),
+                    (baz.to_path_buf(), new_baz.to_path_buf()),
+                ],
+                journal.entries().unwrap()
+            );
+
+            std::fs::remove_file(baz.path()).unwrap();
+            new_bar.touch().unwrap();
+            let path = journal.path().to_path_buf();
+            journal
+                .undo(&RealFs, &LogReporter::default(), &Settings::default())
+                .unwrap();
+
+            bar.assert(predicate::path::exists());
+            baz.assert(predicate::path::exists());
+            assert!(!path.exists());
+        });
+    }
+}
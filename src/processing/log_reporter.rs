@@ -1,4 +1,4 @@
-use crate::processing::{Error, Reporter};
+use crate::processing::{Error, Reporter, Stats};
 use crate::replacement::Replacement;
 
 use std::cell::Cell;
@@ -8,6 +8,10 @@ use std::path::Path;
 pub struct LogReporter {
     count: Cell<usize>,
     current: Cell<usize>,
+    dry_run: bool,
+    progress_interval: Option<usize>,
+    /// Logger receiving the progress, the global one unless replaced
+    progress_logger: Option<Box<dyn log::Log>>,
 }
 
 impl Reporter for LogReporter {
@@ -27,16 +31,89 @@ impl Reporter for LogReporter {
     fn processing_err(&self, path: &Path, error: &Error) {
         self.report_path("Error processing path", path);
         log::warn!("{}", error);
+        self.report_progress();
     }
 
     /// Report that processing  the path finished successfully
     fn processing_ok(&self, replacement: &Replacement) {
-        self.report_path("Success processing path", &replacement.path());
+        if self.dry_run {
+            self.report_path("Would rename path", &replacement.path());
+        } else {
+            self.report_path("Success processing path", &replacement.path());
+        }
         log::info!("Into: {}", replacement);
+        self.report_progress();
+    }
+
+    /// Report that the rename of the path was reverted
+    fn processing_undone(&self, replacement: &Replacement) {
+        self.report_path("Reverted path", &replacement.new_path());
+    }
+
+    /// Report the summary of the run
+    fn finish(&self, stats: &Stats) {
+        log::info!("Done: {}", stats);
     }
 }
 
 impl LogReporter {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            ..Self::default()
+        }
+    }
+
+    /// Log the progress every given number of paths, with the percentage
+    /// done as a structured PROGRESS field for the systemd journal
+    pub fn with_progress_interval(mut self, interval: Option<usize>) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_progress_logger(mut self, logger: Box<dyn log::Log>) -> Self {
+        self.progress_logger = Some(logger);
+        self
+    }
+
+    /// Percentage of the paths processed, if it is due to be reported
+    fn progress(&self) -> Option<usize> {
+        let interval = self.progress_interval?;
+        let current = self.current.get();
+        let count = self.count.get();
+
+        (current.is_multiple_of(interval) || current == count)
+            .then(|| current * 100 / count.max(1))
+    }
+
+    /// Log the progress if it is due, straight to the logger so that it is
+    /// recorded whatever the max level set by the verbosity flags, the
+    /// interval being asked for explicitly
+    fn report_progress(&self) {
+        let Some(percent) = self.progress() else {
+            return;
+        };
+        let logger = self.progress_logger.as_deref().unwrap_or(log::logger());
+
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target(module_path!())
+                .module_path_static(Some(module_path!()))
+                .file_static(Some(file!()))
+                .line(Some(line!()))
+                .key_values(&[("PROGRESS", percent)])
+                .args(format_args!(
+                    "Progress: {}% ({}/{})",
+                    percent,
+                    self.current.get(),
+                    self.count.get()
+                ))
+                .build(),
+        );
+    }
+
     fn report_path(&self, message: &str, path: &Path) {
         log::info!(
             "{} {}/{}: {:?}",
@@ -47,3 +124,66 @@ impl LogReporter {
         );
     }
 }
+
+// The progress is only logged to the systemd journal
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    use std::sync::{Arc, Mutex};
+
+    /// Level and PROGRESS field of a record
+    type Captured = (log::Level, Option<u64>);
+
+    /// Logger capturing the records
+    #[derive(Default, Clone)]
+    struct Capture(Arc<Mutex<Vec<Captured>>>);
+
+    impl log::Log for Capture {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            let progress = record
+                .key_values()
+                .get(log::kv::Key::from_str("PROGRESS"))
+                .and_then(|value| value.to_u64());
+            self.0.lock().unwrap().push((record.level(), progress));
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Records of the progress of processing 5 paths
+    fn captured(interval: Option<usize>) -> Vec<Captured> {
+        let capture = Capture::default();
+        let reporter = LogReporter::new(false)
+            .with_progress_interval(interval)
+            .with_progress_logger(Box::new(capture.clone()));
+
+        let path = Path::new("foo");
+        reporter.setup(5);
+        for _ in 0..5 {
+            reporter.processing(path);
+            reporter.processing_err(path, &Error::no_match(path));
+        }
+
+        let records = capture.0.lock().unwrap().clone();
+        records
+    }
+
+    // The progress reaches the journal every 2 paths and at the end, whatever
+    // the max level, which stays at errors by default
+    #[test]
+    fn progress() {
+        use log::Level::Info;
+
+        assert_eq!(Vec::<Captured>::new(), captured(None));
+        assert_eq!(
+            vec![(Info, Some(40)), (Info, Some(80)), (Info, Some(100))],
+            captured(Some(2))
+        );
+    }
+}
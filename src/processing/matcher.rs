@@ -1,6 +1,10 @@
-use crate::matcher::Matcher;
-use crate::replacement::Replacement;
+use crate::application::DEFAULT_DATE_FORMAT;
+use crate::matcher::{Matcher, Naming};
+use crate::replacement::{self, Replacement};
 
+use chrono::NaiveDate;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::path::Path;
 
 pub struct ProcessingMatcher<'a> {
@@ -20,8 +24,39 @@ impl<'a> From<&'a Box<dyn Matcher>> for ProcessingMatcher<'a> {
 }
 
 impl<'a> ProcessingMatcher<'a> {
-    pub fn check(&self, path: &Path) -> Option<Replacement> {
-        self.matcher.check(path)
+    pub fn check(
+        &self,
+        path: &Path,
+        naming: &Naming,
+        settings: &replacement::Settings,
+    ) -> Option<Replacement> {
+        check(self.matcher, path, naming, settings)
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.matcher.name()
+    }
+
+    /// Explain why the matcher couldn't read its data for the path, if so
+    pub fn unavailable(&self, path: &Path) -> Option<String> {
+        self.matcher.unavailable(path)
+    }
+
+    /// Check if the file stem of the replacement already starts with a date
+    /// within the given number of days of the one determined by the matcher
+    pub fn dated_within(&self, replacement: &Replacement, days: u64) -> bool {
+        let Some(current) =
+            leading_date(&replacement.file_stem, self.matcher.date_format())
+        else {
+            return false;
+        };
+
+        self.matcher
+            .determine(replacement)
+            .map(|(_, date_time)| date_time.date_naive())
+            .is_some_and(|date| {
+                (date - current).num_days().unsigned_abs() <= days
+            })
     }
 
     /// Check if the matcher needs confirmation
@@ -48,10 +83,51 @@ impl<'a> ProcessingMatcher<'a> {
     }
 }
 
+/// Date the file stem starts with, in the given format or the default one
+fn leading_date(file_stem: &str, format: &str) -> Option<NaiveDate> {
+    [format, DEFAULT_DATE_FORMAT].iter().find_map(|format| {
+        NaiveDate::parse_and_remainder(file_stem, format)
+            .ok()
+            .map(|(date, _)| date)
+    })
+}
+
+/// Check the path with the matcher, composing the new file stem according to
+/// the naming and creating the replacement with the given settings
+///
+/// A panicking matcher is isolated so it doesn't stop the whole run, the panic
+/// being logged and treated as no match
+pub fn check(
+    matcher: &dyn Matcher,
+    path: &Path,
+    naming: &Naming,
+    settings: &replacement::Settings,
+) -> Option<Replacement> {
+    match catch_unwind(AssertUnwindSafe(|| {
+        matcher.check_with(path, naming, settings)
+    })) {
+        Ok(replacement) => replacement,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            log::error!(
+                "Matcher {} panicked while checking {:?}: {}",
+                matcher.name(),
+                path,
+                message
+            );
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test::test;
+    use crate::test::{matchers, test};
 
     use std::path::PathBuf;
 
@@ -91,6 +167,74 @@ mod tests {
         let processing_matcher = ProcessingMatcher::from(&matcher);
         let path = PathBuf::from("foo");
 
-        assert!(processing_matcher.check(&path).is_none());
+        assert!(processing_matcher
+            .check(&path, &Naming::default(), &Default::default())
+            .is_none());
+    }
+
+    #[test]
+    fn check_keep_timezone() {
+        let matcher: Box<dyn Matcher> = Box::new(
+            Pattern::builder()
+                .name("tz")
+                .regex(r"(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})(?<hour>\d{2})(?<tz>[+-]\d{4})")
+                .format("%Y-%m-%d %H%z")
+                .build()
+                .unwrap(),
+        );
+        let processing_matcher = ProcessingMatcher::from(&matcher);
+        let path = PathBuf::from("foo 2024012010+0900");
+        let naming = Naming {
+            keep_timezone: true,
+            ..Naming::default()
+        };
+
+        assert_eq!(
+            "2024-01-20 10+0900 foo",
+            processing_matcher
+                .check(&path, &naming, &Default::default())
+                .unwrap()
+                .new_file_stem
+        );
+    }
+
+    #[test]
+    fn dated_within() {
+        let matcher = matchers::ymd_boxed();
+        let processing_matcher = ProcessingMatcher::from(&matcher);
+        let dated = |name: &str, days| {
+            let replacement = Replacement::try_from(Path::new(name)).unwrap();
+            processing_matcher.dated_within(&replacement, days)
+        };
+
+        assert!(dated("2024-01-19 foo 20240120", 2));
+        assert!(dated("2024-01-21 foo 20240120", 1));
+        assert!(!dated("2024-01-17 foo 20240120", 2));
+        assert!(!dated("foo 20240120", 2));
+    }
+
+    #[test]
+    fn leading_date() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 19);
+
+        assert_eq!(date, super::leading_date("2024-01-19 foo", "%Y-%m-%d"));
+        assert_eq!(date, super::leading_date("01-19 2024 foo", "%m-%d %Y"));
+        // Falls back on the default format
+        assert_eq!(
+            date,
+            super::leading_date("2024-01-19 foo", "%Y-%m-%d %Hh%Mm%S")
+        );
+        assert_eq!(None, super::leading_date("foo 2024-01-19", "%Y-%m-%d"));
+    }
+
+    #[test]
+    fn check_panicking() {
+        let matcher = matchers::panicking_boxed();
+        let processing_matcher = ProcessingMatcher::from(&matcher);
+        let path = PathBuf::from("foo 20240120");
+
+        assert!(processing_matcher
+            .check(&path, &Naming::default(), &Default::default())
+            .is_none());
     }
 }
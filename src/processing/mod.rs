@@ -1,4 +1,6 @@
-use crate::matcher::Matcher;
+use crate::application::OnConflict;
+use crate::fs::{Fs, RealFs};
+use crate::matcher::{sidecar, Matcher};
 use crate::replacement::Replacement;
 
 mod error;
@@ -8,11 +10,33 @@ pub type Result<T> = std::result::Result<T, Error>;
 mod matcher;
 pub use matcher::ProcessingMatcher;
 
+mod options;
+pub use options::Options;
+
+pub mod plan;
+
+mod stats;
+pub use stats::{Outcome, Stats};
+
+mod transform;
+pub use transform::Transform;
+
+mod journal;
+pub use journal::Journal;
+
 mod log_reporter;
+pub use log_reporter::LogReporter;
+
 mod notif_reporter;
 
+mod dir_log_reporter;
+
 use std::boxed::Box;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::Local;
 
 pub struct Processing<'a, T>
 where
@@ -22,6 +46,24 @@ where
     paths: &'a [PathBuf],
     interface: &'a T,
     reporters: Vec<Box<dyn Reporter>>,
+    /// Report to the log, notifications and directory logs on top of the
+    /// interface
+    reporting: bool,
+    options: Options,
+    fs: Box<dyn Fs>,
+    stats: Stats,
+    /// Name of the matcher which found the last replacement, None if rescued
+    matched_by: Option<&'a str>,
+    /// New paths assigned during the run, so the suffixes added to avoid
+    /// conflicts are unique even if the paths are not (yet) on disk
+    assigned: HashSet<PathBuf>,
+    /// Journal recording the renames of the run, unless it is a dry run
+    journal: Option<Journal>,
+    /// Last rename executed along with the name of the matcher which found
+    /// it, which the interface may ask to revert
+    last: Option<(Replacement, &'a str)>,
+    /// Accept all the following replacements without confirmation
+    accept_all: bool,
 }
 
 pub trait Reporter {
@@ -33,6 +75,12 @@ pub trait Reporter {
     fn processing_ok(&self, replacement: &Replacement);
     /// Processing encountered this error
     fn processing_err(&self, path: &Path, error: &Error);
+    /// The rename of this replacement was reverted, leaving its path
+    /// untouched
+    fn processing_undone(&self, replacement: &Replacement);
+    /// Processing is over, all the paths having been processed or the run
+    /// being aborted, with these statistics
+    fn finish(&self, stats: &Stats);
 }
 
 pub trait Communication: Reporter {
@@ -40,18 +88,35 @@ pub trait Communication: Reporter {
     fn confirm(&self, replacement: &Replacement) -> Confirmation;
     /// If no match is found, attempt to rescue the Error::NoMatch
     fn rescue(&self, error: Error) -> Result<Replacement>;
+    /// Whenever renaming the path fails, decide how to handle the error
+    fn on_error(&self, path: &Path, error: &Error) -> ErrorResolution;
 }
 
-#[allow(dead_code)]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum ErrorResolution {
+    /// Report the error and continue with the next path
+    Report,
+    /// Attempt the rename again with this replacement
+    Retry(Replacement),
+    Abort,
+}
+
+#[allow(dead_code, clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum Confirmation {
     Accept,
     Always,
+    /// Accept this replacement and all the following ones, whatever the
+    /// matcher proposing them
+    AlwaysAll,
     Skip,
     Refuse,
     Ignore,
     Abort,
     Replace(Replacement),
+    /// Revert the last rename, then confirm this replacement again
+    Undo,
 }
 
 impl PartialEq for Confirmation {
@@ -73,12 +138,51 @@ where
             matchers: matchers.iter().map(From::<_>::from).collect(),
             paths,
             interface,
-            reporters: vec![
-                Box::<log_reporter::LogReporter>::default(),
-                #[cfg(feature = "notif")]
-                Box::<notif_reporter::NotifReporter>::default(),
-            ],
+            reporters: Vec::default(),
+            reporting: true,
+            options: Options::default(),
+            fs: Box::new(RealFs),
+            stats: Stats::default(),
+            matched_by: None,
+            assigned: HashSet::default(),
+            journal: None,
+            last: None,
+            accept_all: false,
+        }
+    }
+
+    /// Use the given options, confirming the matchers to auto-accept
+    pub fn with_options(mut self, options: Options) -> Self {
+        for matcher in self.matchers.iter_mut() {
+            if options
+                .auto_accept
+                .iter()
+                .any(|name| name == matcher.name())
+            {
+                matcher.confirm();
+            }
         }
+
+        self.options = options;
+        self
+    }
+
+    /// Use the given filesystem instead of the real one
+    pub fn with_fs(mut self, fs: Box<dyn Fs>) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Only report to the interface, e.g. to collect the replacements of a
+    /// dry run without logging nor notifying them
+    pub fn without_reporters(mut self) -> Self {
+        self.reporting = false;
+        self
+    }
+
+    /// Statistics of the run so far
+    pub fn stats(&self) -> &Stats {
+        &self.stats
     }
 
     pub fn run(&mut self) -> Result<()> {
@@ -86,77 +190,349 @@ where
             return Ok(());
         }
 
+        let dry_run = self.options.dry_run;
+        self.journal = match dry_run {
+            true => None,
+            false => self.options.journal.as_deref().map(Journal::create),
+        };
+        if self.reporting {
+            self.setup_reporters();
+        }
+
+        let start = Instant::now();
+        self.stats.total = self.paths.len();
         self.report_setup(self.paths.len());
 
         for path in self.paths {
             self.report_processing(path);
 
             match self.prefix_if_possible(path).and_then(|replacement| {
-                replacement.execute().map(|_| replacement)
+                self.execute_or_retry(path, replacement)
             }) {
                 Ok(replacement) => {
+                    let matcher = self.matched_by.unwrap_or(stats::RESCUE);
+                    self.assigned.insert(replacement.new_path());
+                    self.stats.renamed(matcher);
                     self.report_processing_ok(&replacement);
+                    self.last = (!dry_run).then_some((replacement, matcher));
+
+                    if let Some(limit) = self.options.limit {
+                        if self.stats.renamed >= limit {
+                            log::info!(
+                                "Limit of {} renamed paths reached",
+                                limit
+                            );
+                            break;
+                        }
+                    }
                 }
                 Err(error) => {
+                    self.last = None;
+                    self.stats.failed(&error);
                     self.report_processing_err(path, &error);
 
                     if let Error::Abort = error {
+                        self.finish(start);
+                        return Err(error);
+                    }
+                    if self.options.fail_fast && error.is_failure() {
+                        log::info!("Stopping at the first failure");
+                        self.finish(start);
                         return Err(error);
                     }
                 }
             }
         }
 
+        self.finish(start);
         Ok(())
     }
 
+    /// Report to the log and notifications, and to the directory logs if
+    /// asked to
+    fn setup_reporters(&mut self) {
+        let dry_run = self.options.dry_run;
+        self.reporters = vec![
+            Box::new(
+                log_reporter::LogReporter::new(dry_run).with_progress_interval(
+                    self.options.journal_progress_interval.filter(|_| {
+                        systemd_journal_logger::connected_to_journal()
+                    }),
+                ),
+            ),
+            #[cfg(feature = "notif")]
+            Box::new(
+                notif_reporter::NotifReporter::new(dry_run)
+                    .with_summary_only(self.options.notif_summary_only),
+            ),
+        ];
+        if self.options.dir_log {
+            self.reporters
+                .push(Box::new(dir_log_reporter::DirLogReporter::new(dry_run)));
+        }
+    }
+
+    /// Execute the replacement, letting the interface retry it with another
+    /// one as long as the rename fails
+    fn execute_or_retry(
+        &self,
+        path: &Path,
+        mut replacement: Replacement,
+    ) -> Result<Replacement> {
+        loop {
+            let error = match self.execute(replacement) {
+                Ok(replacement) => return Ok(replacement),
+                Err(error @ (Error::Skip(_) | Error::Abort)) => {
+                    return Err(error)
+                }
+                Err(error) => error,
+            };
+
+            match self.interface.on_error(path, &error) {
+                ErrorResolution::Report => return Err(error),
+                ErrorResolution::Retry(retry) => {
+                    replacement = check_customized(path, retry, &self.options)?;
+                }
+                ErrorResolution::Abort => return Err(Error::Abort),
+            }
+        }
+    }
+
+    fn execute(&self, mut replacement: Replacement) -> Result<Replacement> {
+        let fs = self.fs.as_ref();
+        let journal = self.journal.as_ref();
+        let assigned = &self.assigned;
+        let on_conflict = self.options.on_conflict;
+        let retries = &self.options.replacement.rename_retries;
+
+        if let Some(output_dir) = &self.options.output_dir {
+            replacement.new_parent = Some(output_dir.clone());
+        }
+        if let (Some(organize), Some(date_time)) =
+            (self.options.organize, replacement.date_time)
+        {
+            let subdir = match self.options.naming.keep_timezone {
+                true => organize.subdir(&date_time),
+                false => organize.subdir(&date_time.with_timezone(&Local)),
+            };
+            replacement.new_parent =
+                Some(replacement.new_parent().join(subdir));
+        }
+        if self.options.dry_run {
+            if let OnConflict::Suffix = on_conflict {
+                replacement.resolve_suffix(fs, assigned)?;
+            }
+            return Ok(replacement);
+        }
+        match on_conflict {
+            OnConflict::Refuse => replacement.execute_with(fs, retries),
+            OnConflict::Suffix => {
+                replacement.execute_with_suffix(fs, assigned, retries)
+            }
+        }
+        .map_err(|error| match error {
+            Error::SourceVanished(path) if self.options.skip_vanished => {
+                Error::Skip(path)
+            }
+            error => error,
+        })?;
+        if let Some(journal) = journal {
+            record(journal, &replacement);
+        }
+        if let Err(error) = replacement.touch_with(fs, self.options.touch) {
+            log::warn!("Unable to touch {}: {}", replacement, error);
+        }
+        if self.matched_by == Some(sidecar::SIDECAR) {
+            self.follow_sidecars(&replacement, journal);
+        }
+        Ok(replacement)
+    }
+
+    /// Rename the sidecars the date was read from along with the file, so
+    /// they are still found next to it
+    fn follow_sidecars(
+        &self,
+        replacement: &Replacement,
+        journal: Option<&Journal>,
+    ) {
+        let settings = &self.options.replacement;
+
+        for sidecar in sidecar::follow(replacement, settings) {
+            match sidecar
+                .execute_with(self.fs.as_ref(), &settings.rename_retries)
+            {
+                Ok(()) => {
+                    log::info!("Renamed sidecar {}", sidecar);
+                    if let Some(journal) = journal {
+                        record(journal, &sidecar);
+                    }
+                }
+                Err(error) => {
+                    log::warn!(
+                        "Unable to rename sidecar {}: {}",
+                        sidecar,
+                        error
+                    );
+                }
+            }
+        }
+    }
+
+    /// Ask the interface to confirm the replacement, reverting the last
+    /// rename whenever asked to before confirming again
+    fn confirm(&mut self, replacement: &Replacement) -> Confirmation {
+        loop {
+            match self.interface.confirm(replacement) {
+                Confirmation::Undo => self.undo_last(),
+                confirmation => return confirmation,
+            }
+        }
+    }
+
+    /// Revert the last rename, which then counts as skipped
+    fn undo_last(&mut self) {
+        let Some((replacement, matcher)) = self.last.take() else {
+            return;
+        };
+        let (path, new_path) = (replacement.path(), replacement.new_path());
+
+        if let Err(error) = journal::revert(
+            self.fs.as_ref(),
+            &path,
+            &new_path,
+            &self.options.replacement,
+        ) {
+            log::error!("Unable to undo {}: {}", replacement, error);
+            return;
+        }
+        if let Some(journal) = &self.journal {
+            if let Err(error) = journal.forget(&path, &new_path) {
+                log::warn!(
+                    "Unable to remove {} from journal: {}",
+                    replacement,
+                    error
+                );
+            }
+        }
+        self.assigned.remove(&new_path);
+        self.stats.undone(matcher);
+        self.report_processing_undone(&replacement);
+    }
+
+    fn finish(&mut self, start: Instant) {
+        self.stats.duration = start.elapsed();
+        self.report_finish();
+
+        if let Some(path) = &self.options.stats_json {
+            if let Err(error) = self.stats.write(path) {
+                log::warn!("Unable to write stats to {:?}: {}", path, error);
+            }
+        }
+    }
+
     pub fn prefix_if_possible(&mut self, path: &Path) -> Result<Replacement> {
-        if !path.try_exists().unwrap() {
+        if !self.fs.try_exists(path).map_err(|e| Error::io(e, path))? {
             return Err(Error::not_found(path));
         }
+        if !self.options.allow_special
+            && self.fs.is_special(path).map_err(|e| Error::io(e, path))?
+        {
+            return Err(Error::NotRegularFile(path.to_path_buf()));
+        }
 
         // Get an immutable ref
         let interface: &T = self.interface;
-
+        self.matched_by = None;
+        let mut unavailable = None;
         let mut found = false;
 
-        for matcher in self
-            .matchers
-            .iter_mut()
-            .filter(|matcher| !matcher.ignored())
-        {
-            if let Some(replacement) = matcher.check(path) {
+        let lower_ext = self.options.lower_ext;
+        let no_metadata_fallback = self.options.no_metadata_fallback;
+        let skip_within = self.options.skip_if_dated_within_days;
+        let (since, until) = (self.options.since, self.options.until);
+
+        // Iterate by index, confirming may revert the last rename meanwhile
+        for index in 0..self.matchers.len() {
+            let matcher = &self.matchers[index];
+            if matcher.ignored() {
+                continue;
+            }
+            if let Some(mut replacement) = matcher.check(
+                path,
+                &self.options.naming,
+                &self.options.replacement,
+            ) {
                 found = true;
-                if matcher.confirmed() {
+                self.matched_by = Some(matcher.name());
+                if skip_within.is_some_and(|days| {
+                    matcher.dated_within(&replacement, days)
+                }) {
+                    log::info!("{:?} is already dated", path);
+                    return Err(Error::Skip(path.to_path_buf()));
+                }
+                if let Some(date) = replacement
+                    .date_time
+                    .map(|date_time| date_time.date_naive())
+                {
+                    if since.is_some_and(|since| date < since)
+                        || until.is_some_and(|until| date > until)
+                    {
+                        log::info!("{:?} is dated outside the range", path);
+                        return Err(Error::Skip(path.to_path_buf()));
+                    }
+                }
+                replacement.new_file_stem = Transform::apply_all(
+                    &self.options.transforms,
+                    &replacement.new_file_stem,
+                );
+                if lower_ext {
+                    replacement.lowercase_extension();
+                }
+
+                if matcher.confirmed() || self.accept_all {
                     return Ok(replacement);
                 }
-                match interface.confirm(&replacement) {
+                match self.confirm(&replacement) {
                     Confirmation::Accept => return Ok(replacement),
                     Confirmation::Always => {
-                        matcher.confirm();
+                        self.matchers[index].confirm();
+                        return Ok(replacement);
+                    }
+                    Confirmation::AlwaysAll => {
+                        self.accept_all = true;
                         return Ok(replacement);
                     }
                     Confirmation::Skip => {
                         return Err(Error::Skip(path.to_path_buf()));
                     }
-                    Confirmation::Refuse => {}
+                    Confirmation::Refuse | Confirmation::Undo => {}
                     Confirmation::Ignore => {
-                        matcher.ignore();
+                        self.matchers[index].ignore();
                     }
                     Confirmation::Abort => {
                         return Err(Error::Abort);
                     }
                     Confirmation::Replace(replacement) => {
-                        return Ok(replacement)
+                        return check_customized(
+                            path,
+                            replacement,
+                            &self.options,
+                        );
                     }
                 };
+            } else if no_metadata_fallback && unavailable.is_none() {
+                unavailable = matcher.unavailable(path);
             }
         }
 
         if found {
             Err(Error::no_match(path))
+        } else if let Some(reason) = unavailable {
+            Err(Error::MetadataUnavailable(path.to_path_buf(), reason))
         } else {
-            interface.rescue(Error::no_match(path))
+            interface
+                .rescue(Error::no_match(path))
+                .and_then(|rep| check_customized(path, rep, &self.options))
         }
     }
 
@@ -188,6 +564,69 @@ where
 
         self.interface.processing_err(path, error);
     }
+    fn report_processing_undone(&self, replacement: &Replacement) {
+        for reporter in &self.reporters {
+            reporter.processing_undone(replacement);
+        }
+
+        self.interface.processing_undone(replacement);
+    }
+    fn report_finish(&self) {
+        for reporter in &self.reporters {
+            reporter.finish(&self.stats);
+        }
+
+        self.interface.finish(&self.stats);
+    }
+}
+
+/// Record the executed replacement in the journal, if anything was renamed
+fn record(journal: &Journal, replacement: &Replacement) {
+    let (path, new_path) = (replacement.path(), replacement.new_path());
+
+    if path != new_path {
+        if let Err(error) = journal.record(&path, &new_path) {
+            log::warn!(
+                "Unable to record {} in journal: {}",
+                replacement,
+                error
+            );
+        }
+    }
+}
+
+/// Sanitize a customized replacement and ensure it does not introduce path
+/// separators, normalizing them if asked to by the options
+fn check_customized(
+    path: &Path,
+    mut replacement: Replacement,
+    options: &Options,
+) -> Result<Replacement> {
+    // Resolve the path like the replacement to compare them
+    if let Ok(source) = Replacement::try_from_with(path, &options.replacement) {
+        if source.path() != replacement.path() {
+            return Err(Error::MismatchedReplacement(
+                source.path(),
+                replacement.path(),
+            ));
+        }
+    }
+
+    replacement.sanitize(&options.replacement.sanitize);
+
+    if replacement.has_new_separators() {
+        if !options.normalize_separators {
+            return Err(Error::PathSeparator(replacement.new_file_stem));
+        }
+
+        log::warn!(
+            "Normalizing path separators in {:?}",
+            replacement.new_file_stem
+        );
+        replacement.normalize_separators();
+    }
+
+    Ok(replacement)
 }
 
 #[cfg(test)]
@@ -203,10 +642,13 @@ mod tests {
             fn processing(&self, path: &Path);
             fn processing_ok(&self, replacement: &Replacement);
             fn processing_err(&self, path: &Path, error: &Error);
+            fn processing_undone(&self, replacement: &Replacement);
+            fn finish(&self, stats: &Stats);
         }
         impl Communication for Interface {
             fn confirm(&self, replacement: &Replacement) -> Confirmation;
             fn rescue(&self, error: Error) -> Result<Replacement>;
+            fn on_error(&self, path: &Path, error: &Error) -> ErrorResolution;
         }
     }
 
@@ -241,6 +683,7 @@ mod tests {
             let paths = [path.clone()];
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -276,6 +719,7 @@ mod tests {
             let paths = [path.clone()];
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -320,6 +764,7 @@ mod tests {
             replacement.new_file_stem = String::from("bar");
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -368,6 +813,7 @@ mod tests {
             let replacement = Replacement::try_from(temp.child("bar").path())?;
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -385,16 +831,61 @@ mod tests {
                 .times(1)
                 .in_sequence(&mut seq)
                 .return_once(move |_| Ok(replacement));
+            interface.expect_on_error().never();
             interface
                 .expect_processing_err()
-                .withf(|_, e| matches!(e, Error::Io(_)))
+                .withf(|_, e| matches!(e, Error::MismatchedReplacement(..)))
                 .times(1)
                 .in_sequence(&mut seq)
                 .returning(|_, _| {});
             interface.expect_processing_ok().never();
 
             let mut processing = Processing::new(&interface, &matchers, &paths);
-            processing.run()
+            processing.run()?;
+
+            child.assert(predicate::path::exists());
+            Ok(())
+        })
+    }
+
+    // Ensure a replacement of another path given on confirmation is refused
+    // before trying to rename anything
+    #[test]
+    fn confirm_replace_mismatched() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let child = temp.existing_child("foo 20240120").unwrap();
+            let other = temp.existing_child("bar 20240120").unwrap();
+            let paths = [child.to_path_buf()];
+
+            let replacement = Replacement::try_from(other.path())?
+                .new_file_stem(String::from("2024-01-20 bar"));
+            let (expected, actual) = (child.to_path_buf(), other.to_path_buf());
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().times(1).returning(|_| {});
+            interface.expect_processing().times(1).returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .return_once(move |_| Confirmation::Replace(replacement));
+            interface
+                .expect_processing_err()
+                .withf(move |_, e| {
+                    matches!(e, Error::MismatchedReplacement(e, a)
+                        if *e == expected && *a == actual)
+                })
+                .times(1)
+                .returning(|_, _| {});
+            interface.expect_processing_ok().never();
+
+            let mut processing = Processing::new(&interface, &matchers, &paths);
+            processing.run()?;
+
+            child.assert(predicate::path::exists());
+            other.assert(predicate::path::exists());
+            Ok(())
         })
     }
 
@@ -414,6 +905,7 @@ mod tests {
             let paths = [path.clone(), path2.clone()];
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .with(predicate::eq(2))
@@ -490,6 +982,7 @@ mod tests {
             let paths = [path.clone(), path2.clone()];
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .with(predicate::eq(2))
@@ -544,6 +1037,48 @@ mod tests {
         })
     }
 
+    // Ensure no replacement is confirmed after AlwaysAll, whatever the matcher
+    #[test]
+    fn confirm_always_all() -> Result<()> {
+        with_temp_dir(|temp| {
+            use crate::matcher::Pattern;
+
+            let mut interface = MockInterface::new();
+            let dmy = Pattern::builder()
+                .name("dmy")
+                .regex(r"(?<start>.+)\s+(?<day>\d{2})(?<month>\d{2})(?<year>\d{4})")
+                .build()
+                .unwrap();
+            let matchers = [matchers::ymd_boxed(), Box::new(dmy) as _];
+
+            let foo = temp.existing_child("foo 20240120").unwrap();
+            let bar = temp.existing_child("bar 21012024").unwrap();
+            let paths = [foo.to_path_buf(), bar.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .returning(|_| Confirmation::AlwaysAll);
+            interface.expect_processing_ok().times(2).returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let mut processing = Processing::new(&interface, &matchers, &paths);
+            processing.run()?;
+
+            foo.assert(predicate::path::missing());
+            temp.child("2024-01-20 foo")
+                .assert(predicate::path::exists());
+            bar.assert(predicate::path::missing());
+            temp.child("2024-01-21 bar")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
     // Ensure second matcher is not considered if the path is skipped
     #[test]
     fn confirm_skip() -> Result<()> {
@@ -555,6 +1090,7 @@ mod tests {
             let paths = [path.clone()];
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -599,6 +1135,7 @@ mod tests {
             replacement.new_file_stem = String::from("bar");
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -640,6 +1177,7 @@ mod tests {
             let paths = [path.clone(), path.clone()];
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -697,6 +1235,7 @@ mod tests {
             let paths = [path.clone(), path.clone()];
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -742,6 +1281,7 @@ mod tests {
             replacement.new_file_stem = String::from("bar");
 
             let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
             interface
                 .expect_setup()
                 .times(1)
@@ -774,4 +1314,1390 @@ mod tests {
             Ok(())
         })
     }
+
+    // Ensure transforms are applied in order before confirmation
+    #[test]
+    fn transforms() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let child = temp.existing_child("Foo Bar 20240120").unwrap();
+            let paths = [child.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .withf(|rep| rep.new_file_stem == "2024-01-20-foo-bar")
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface.expect_processing_ok().times(1).returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let options = Options {
+                transforms: vec![Transform::Slugify, Transform::Lowercase],
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::missing());
+            temp.child("2024-01-20-foo-bar")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure the extension is lowercased along the transforms of the stem
+    #[test]
+    fn lower_ext() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let child = temp.existing_child("IMG_0001 20240120.JPG").unwrap();
+            let paths = [child.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .withf(|rep| {
+                    rep.new_file_name() == "2024-01-20 IMG_0001 Scan.jpg"
+                })
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface.expect_processing_ok().times(1).returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let options = Options {
+                transforms: vec![Transform::Append(String::from(" Scan"))],
+                lower_ext: true,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::missing());
+            temp.child("2024-01-20 IMG_0001 Scan.jpg")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure the renames are logged in their directory, except in dry-run
+    #[test]
+    fn dir_log() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let foo = temp.existing_child("foo 20240120").unwrap();
+            let bar = temp.existing_child("sub/bar 20240121").unwrap();
+            let paths = [foo.to_path_buf(), bar.to_path_buf()];
+
+            interface.expect_finish().times(2).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .returning(|_| Confirmation::Accept);
+            interface.expect_processing_ok().times(4).returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let log = |dir: &str| {
+                std::fs::read_to_string(
+                    temp.child(dir).child(".prefix-by-date.log").path(),
+                )
+            };
+
+            let options = Options {
+                dir_log: true,
+                dry_run: true,
+                ..Options::default()
+            };
+            Processing::new(&interface, &matchers, &paths)
+                .with_options(options)
+                .run()?;
+            assert!(log(".").is_err());
+            assert!(log("sub").is_err());
+
+            let options = Options {
+                dir_log: true,
+                ..Options::default()
+            };
+            Processing::new(&interface, &matchers, &paths)
+                .with_options(options)
+                .run()?;
+
+            let root = log(".").unwrap();
+            assert_eq!(1, root.lines().count());
+            assert!(root.ends_with("\tfoo 20240120\t2024-01-20 foo\n"));
+            let sub = log("sub").unwrap();
+            assert_eq!(1, sub.lines().count());
+            assert!(sub.ends_with("\tbar 20240121\t2024-01-21 bar\n"));
+
+            Ok(())
+        })
+    }
+
+    // Ensure the matchers named to be auto-accepted don't need confirmation
+    #[test]
+    fn auto_accept() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            use crate::matcher::Pattern;
+
+            let dmy = Pattern::builder()
+                .name("dmy")
+                .regex(r"(?<start>.+)\s+(?<day>\d{2})(?<month>\d{2})(?<year>\d{4})")
+                .build()
+                .unwrap();
+            let matchers = [matchers::ymd_boxed(), Box::new(dmy) as _];
+            let foo = temp.existing_child("foo 20240120").unwrap();
+            let bar = temp.existing_child("bar 21012024").unwrap();
+            let paths = [foo.to_path_buf(), bar.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .withf(|rep| rep.new_file_stem == "2024-01-21 bar")
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface.expect_processing_ok().times(2).returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let options = Options {
+                auto_accept: vec![String::from("ymd")],
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            foo.assert(predicate::path::missing());
+            temp.child("2024-01-20 foo")
+                .assert(predicate::path::exists());
+            temp.child("2024-01-21 bar")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure a panicking matcher doesn't stop the run
+    // Ensure the paths already dated close to the matched date are skipped
+    #[test]
+    fn skip_if_dated_within_days() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let close = temp.existing_child("2024-01-19 foo 20240120").unwrap();
+            let far = temp.existing_child("2024-01-10 bar 20240120").unwrap();
+            let paths = [close.to_path_buf(), far.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .withf(|rep| rep.file_stem == "2024-01-10 bar 20240120")
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface
+                .expect_processing_err()
+                .withf(|_, e| matches!(e, Error::Skip(_)))
+                .times(1)
+                .returning(|_, _| {});
+            interface.expect_processing_ok().times(1).returning(|_| {});
+
+            let options = Options {
+                skip_if_dated_within_days: Some(2),
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            close.assert(predicate::path::exists());
+            far.assert(predicate::path::missing());
+            // The previous date is replaced
+            temp.child("2024-01-20 bar")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn panicking_matcher() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::panicking_boxed(), matchers::ymd_boxed()];
+            let foo = temp.existing_child("foo 20240120").unwrap();
+            let bar = temp.existing_child("bar").unwrap();
+            let paths = [foo.to_path_buf(), bar.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .withf(|rep| rep.new_file_stem == "2024-01-20 foo")
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface.expect_rescue().times(1).returning(Err);
+            interface.expect_processing_ok().times(1).returning(|_| {});
+            interface
+                .expect_processing_err()
+                .times(1)
+                .returning(|_, _| {});
+
+            let mut processing = Processing::new(&interface, &matchers, &paths);
+            processing.run()?;
+
+            foo.assert(predicate::path::missing());
+            temp.child("2024-01-20 foo")
+                .assert(predicate::path::exists());
+            bar.assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure unreadable metadata is reported instead of rescued when asked
+    #[test]
+    fn no_metadata_fallback() -> Result<()> {
+        for no_metadata_fallback in [false, true] {
+            let mut interface = MockInterface::new();
+            let matchers =
+                [matchers::ymd_boxed(), matchers::unavailable_boxed()];
+            let paths = [PathBuf::from("/bar")];
+            let mut fs = crate::fs::MockFs::new();
+
+            fs.expect_try_exists().times(1).returning(|_| Ok(true));
+            fs.expect_is_special().returning(|_| Ok(false));
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_rescue()
+                .times(usize::from(!no_metadata_fallback))
+                .returning(Err);
+            interface
+                .expect_processing_err()
+                .withf(move |_, e| match e {
+                    Error::MetadataUnavailable(path, reason) => {
+                        no_metadata_fallback
+                            && path == &PathBuf::from("/bar")
+                            && reason == "creation time unsupported"
+                    }
+                    Error::NoMatch(_) => !no_metadata_fallback,
+                    _ => false,
+                })
+                .times(1)
+                .returning(|_, _| {});
+
+            let options = Options {
+                no_metadata_fallback,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options)
+                .with_fs(Box::new(fs));
+            processing.run()?;
+        }
+
+        Ok(())
+    }
+
+    // An existing file whose creation time isn't supported by the platform or
+    // filesystem is reported rather than rescued
+    #[test]
+    fn no_metadata_fallback_on_existing_file() -> Result<()> {
+        use crate::matcher::Metadata;
+
+        with_temp_dir(|temp| {
+            let child = temp.existing_child("foo").unwrap();
+            if child.path().metadata().unwrap().created().is_ok() {
+                log::info!("Skipping, the creation time is available");
+                return Ok(());
+            }
+
+            let mut interface = MockInterface::new();
+            let matchers =
+                [Box::new(Metadata::new_created("%Y")) as Box<dyn Matcher>];
+            let paths = [child.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface.expect_rescue().never();
+            interface
+                .expect_processing_err()
+                .withf(|_, e| {
+                    matches!(
+                        e,
+                        Error::MetadataUnavailable(_, reason)
+                            if reason.starts_with("created time unavailable")
+                    )
+                })
+                .times(1)
+                .returning(|_, _| {});
+
+            let options = Options {
+                no_metadata_fallback: true,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure a failing rename is reported and processing continues
+    #[test]
+    fn execute_failure() -> Result<()> {
+        use crate::fs::MockFs;
+        use std::io;
+
+        let mut interface = MockInterface::new();
+        let mut fs = MockFs::new();
+        fs.expect_is_special().returning(|_| Ok(false));
+        let matchers = [matchers::ymd_boxed()];
+        let paths = [PathBuf::from("/foo 20240120"), PathBuf::from("/bar")];
+
+        fs.expect_try_exists()
+            .with(predicate::eq(paths[0].clone()))
+            .times(2)
+            .returning(|_| Ok(true));
+        fs.expect_try_exists()
+            .with(predicate::eq(PathBuf::from("/2024-01-20 foo")))
+            .times(1)
+            .returning(|_| Ok(false));
+        fs.expect_try_exists()
+            .with(predicate::eq(paths[1].clone()))
+            .times(1)
+            .returning(|_| Ok(true));
+        fs.expect_rename()
+            .times(1)
+            .returning(|_, _| Err(io::Error::from(io::ErrorKind::Other)));
+
+        let mut seq = Sequence::new();
+        interface.expect_finish().times(1).returning(|_| {});
+        interface
+            .expect_setup()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_processing()
+            .with(predicate::eq(paths[0].clone()))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_confirm()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Confirmation::Accept);
+        interface
+            .expect_on_error()
+            .withf(|_, e| matches!(e, Error::Io(_)))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| ErrorResolution::Report);
+        interface
+            .expect_processing_err()
+            .withf(|_, e| matches!(e, Error::Io(_)))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| {});
+        interface
+            .expect_processing()
+            .with(predicate::eq(paths[1].clone()))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_rescue()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(Err);
+        interface
+            .expect_processing_err()
+            .withf(|_, e| matches!(e, Error::NoMatch(_)))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| {});
+        interface.expect_processing_ok().never();
+
+        let mut processing = Processing::new(&interface, &matchers, &paths)
+            .with_fs(Box::new(fs));
+        processing.run()
+    }
+
+    // Ensure a failing rename can be retried with another replacement
+    #[test]
+    fn execute_failure_retry() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let existing = temp.existing_child("2024-01-20 foo").unwrap();
+            let child = temp.existing_child("foo 20240120").unwrap();
+            let paths = [child.to_path_buf()];
+
+            let mut seq = Sequence::new();
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_| Confirmation::Accept);
+            interface
+                .expect_on_error()
+                .withf(|_, e| matches!(e, Error::TargetExists(_)))
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|path, _| {
+                    let mut replacement = Replacement::try_from(path).unwrap();
+                    replacement.new_file_stem = "2024-01-20 foo/bar".into();
+                    ErrorResolution::Retry(replacement)
+                });
+            interface
+                .expect_processing_ok()
+                .withf(|rep| rep.new_file_stem == "2024-01-20 foo-bar")
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let options = Options {
+                normalize_separators: true,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::missing());
+            existing.assert(predicate::path::exists());
+            temp.child("2024-01-20 foo-bar")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure the interface can abort the run when a rename fails
+    #[test]
+    fn execute_failure_abort() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            temp.existing_child("2024-01-20 foo").unwrap();
+            let child = temp.existing_child("foo 20240120").unwrap();
+            let paths = [child.to_path_buf(), temp.child("bar").to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().times(1).returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface
+                .expect_on_error()
+                .times(1)
+                .returning(|_, _| ErrorResolution::Abort);
+            interface
+                .expect_processing_err()
+                .withf(|_, e| matches!(e, Error::Abort))
+                .times(1)
+                .returning(|_, _| {});
+
+            let mut processing = Processing::new(&interface, &matchers, &paths);
+            assert!(matches!(processing.run(), Err(Error::Abort)));
+            child.assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure the modification time of the renamed files is set to the date
+    // they are prefixed by when asked to
+    #[test]
+    fn touch_modified() -> Result<()> {
+        use crate::application::Touch;
+        use chrono::{Local, TimeZone};
+        use std::time::SystemTime;
+
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let child = temp.existing_child("foo 20240120").unwrap();
+            let paths = [child.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface.expect_processing_ok().times(1).returning(|_| {});
+
+            let options = Options {
+                touch: Touch::Modified,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            let renamed = temp.child("2024-01-20 foo");
+            let date_time =
+                Local.with_ymd_and_hms(2024, 1, 20, 0, 0, 0).unwrap();
+            assert_eq!(
+                SystemTime::from(date_time),
+                renamed.path().metadata()?.modified()?
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn on_conflict_suffix() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let existing = temp.existing_child("2024-01-20 foo").unwrap();
+            let child = temp.existing_child("foo 20240120").unwrap();
+            let paths = [child.to_path_buf()];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .returning(|_| Confirmation::Accept);
+            interface
+                .expect_processing_ok()
+                .withf(|rep| rep.new_file_stem == "2024-01-20 foo (1)")
+                .times(1)
+                .returning(|_| {});
+
+            let options = Options {
+                on_conflict: OnConflict::Suffix,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::missing());
+            existing.assert(predicate::path::exists());
+            temp.child("2024-01-20 foo (1)")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure the names assigned during the run are unique even when they are
+    // not on disk, like in dry-run
+    #[test]
+    fn on_conflict_suffix_assigned() -> Result<()> {
+        use crate::matcher::Pattern;
+        use std::sync::{Arc, Mutex};
+
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let pattern = Pattern::builder()
+                .name("any_separator")
+                .regex(r"(?<start>[a-z]+)[\W_]*(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})")
+                .build()
+                .unwrap();
+            let matchers = [Box::new(pattern) as Box<dyn Matcher>];
+            let paths = ["foo-20240120", "foo_20240120", "foo 20240120"]
+                .map(|name| temp.existing_child(name).unwrap().to_path_buf());
+            let reported = Arc::new(Mutex::new(Vec::<String>::new()));
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .returning(|_| Confirmation::Accept);
+            let names = reported.clone();
+            interface
+                .expect_processing_ok()
+                .times(3)
+                .returning(move |rep| {
+                    names.lock().unwrap().push(rep.new_file_name());
+                });
+            interface.expect_processing_err().never();
+
+            let options = Options {
+                on_conflict: OnConflict::Suffix,
+                dry_run: true,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            assert_eq!(
+                vec![
+                    String::from("2024-01-20 foo"),
+                    String::from("2024-01-20 foo (1)"),
+                    String::from("2024-01-20 foo (2)"),
+                ],
+                *reported.lock().unwrap()
+            );
+            for path in &paths {
+                assert!(path.exists());
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn output_dir() -> Result<()> {
+        with_temp_dir(|temp| {
+            let interface = crate::ui::NonInteractive::new();
+            let matchers = [matchers::ymd_boxed()];
+            let out = temp.child("out");
+            let paths =
+                ["first/foo 20240120", "second/foo 20240120"].map(|name| {
+                    let child = temp.child(name);
+                    child.touch().unwrap();
+                    child.to_path_buf()
+                });
+
+            let options = Options {
+                output_dir: Some(out.to_path_buf()),
+                on_conflict: OnConflict::Suffix,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            for path in &paths {
+                assert!(!path.exists());
+            }
+            out.child("2024-01-20 foo")
+                .assert(predicate::path::exists());
+            out.child("2024-01-20 foo (1)")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn organize() -> Result<()> {
+        use crate::application::Organize;
+
+        with_temp_dir(|temp| {
+            let interface = crate::ui::NonInteractive::new();
+            let matchers = [matchers::ymd_boxed()];
+            let out = temp.child("out");
+            let child = temp.existing_child("foo 20240120").unwrap();
+            let paths = [child.to_path_buf()];
+
+            let options = Options {
+                output_dir: Some(out.to_path_buf()),
+                organize: Some(Organize::Ym),
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::missing());
+            out.child("2024/01").assert(predicate::path::is_dir());
+            out.child("2024/01/2024-01-20 foo")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn limit() -> Result<()> {
+        with_temp_dir(|temp| {
+            let interface = crate::ui::NonInteractive::new();
+            let matchers = [matchers::ymd_boxed()];
+            let unmatched = temp.existing_child("unmatched").unwrap();
+            let mut paths = vec![unmatched.to_path_buf()];
+            paths.extend((1..=5).map(|i| {
+                temp.existing_child(format!("foo{} 2024012{}", i, i))
+                    .unwrap()
+                    .to_path_buf()
+            }));
+
+            let options = Options {
+                limit: Some(2),
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            // The unmatched path doesn't count toward the limit
+            unmatched.assert(predicate::path::exists());
+            temp.child("2024-01-21 foo1")
+                .assert(predicate::path::exists());
+            temp.child("2024-01-22 foo2")
+                .assert(predicate::path::exists());
+            for path in &paths[3..] {
+                assert!(path.exists());
+            }
+            assert_eq!(2, processing.stats.renamed);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn fail_fast() -> Result<()> {
+        with_temp_dir(|temp| {
+            let interface = crate::ui::NonInteractive::new();
+            let matchers = [matchers::ymd_boxed()];
+            let unmatched = temp.existing_child("unmatched").unwrap();
+            let failing = temp.existing_child("foo 20240120").unwrap();
+            temp.existing_child("2024-01-20 foo").unwrap();
+            let good = temp.existing_child("bar 20240121").unwrap();
+            let paths = [
+                unmatched.to_path_buf(),
+                failing.to_path_buf(),
+                good.to_path_buf(),
+            ];
+
+            let options = Options {
+                fail_fast: true,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            assert!(matches!(
+                processing.run(),
+                Err(Error::TargetExists(path))
+                    if path == temp.child("2024-01-20 foo").path()
+            ));
+
+            // The unmatched path didn't stop the processing, the failing one
+            // did before the good one
+            assert_eq!(1, processing.stats.skipped);
+            assert_eq!(1, processing.stats.errored);
+            assert_eq!(0, processing.stats.renamed);
+            failing.assert(predicate::path::exists());
+            good.assert(predicate::path::exists());
+            temp.child("2024-01-21 bar")
+                .assert(predicate::path::missing());
+
+            Ok(())
+        })
+    }
+
+    // Only the paths whose matched date falls in the range are renamed
+    #[test]
+    fn since_until() -> Result<()> {
+        use chrono::NaiveDate;
+
+        with_temp_dir(|temp| {
+            let interface = crate::ui::NonInteractive::new();
+            let matchers = [matchers::ymd_boxed()];
+            let before = temp.existing_child("foo 20240110").unwrap();
+            let within = temp.existing_child("bar 20240120").unwrap();
+            let after = temp.existing_child("baz 20240130").unwrap();
+            let paths = [
+                before.to_path_buf(),
+                within.to_path_buf(),
+                after.to_path_buf(),
+            ];
+
+            let options = Options {
+                since: NaiveDate::from_ymd_opt(2024, 1, 15),
+                until: NaiveDate::from_ymd_opt(2024, 1, 25),
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            before.assert(predicate::path::exists());
+            within.assert(predicate::path::missing());
+            temp.child("2024-01-20 bar")
+                .assert(predicate::path::exists());
+            after.assert(predicate::path::exists());
+            assert_eq!(1, processing.stats.renamed);
+            assert_eq!(2, processing.stats.skipped);
+
+            Ok(())
+        })
+    }
+
+    // The naming is taken from the options rather than from a global
+    #[test]
+    fn naming_from_options() -> Result<()> {
+        use crate::matcher::{Naming, Position};
+
+        with_temp_dir(|temp| {
+            let interface = crate::ui::NonInteractive::new();
+            let matchers = [matchers::ymd_boxed()];
+            let foo = temp.existing_child("foo 20240120").unwrap();
+            let paths = [foo.to_path_buf()];
+
+            let options = Options {
+                naming: Naming {
+                    position: Position::Suffix,
+                    ..Naming::default()
+                },
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            foo.assert(predicate::path::missing());
+            temp.child("foo 2024-01-20")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn journal_and_undo() -> Result<()> {
+        with_temp_dir(|temp| {
+            let interface = crate::ui::NonInteractive::new();
+            let matchers = [matchers::ymd_boxed()];
+            let dir = temp.child("journal");
+            let foo = temp.existing_child("foo 20240120").unwrap();
+            let bar = temp.existing_child("bar 20240121").unwrap();
+            let paths = [foo.to_path_buf(), bar.to_path_buf()];
+
+            let options = Options {
+                journal: Some(dir.to_path_buf()),
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            foo.assert(predicate::path::missing());
+            bar.assert(predicate::path::missing());
+
+            let journal = Journal::latest(&dir)?.unwrap();
+            assert_eq!(2, journal.entries()?.len());
+            journal.undo(
+                &RealFs,
+                &LogReporter::default(),
+                &Default::default(),
+            )?;
+
+            foo.assert(predicate::path::exists());
+            bar.assert(predicate::path::exists());
+            temp.child("2024-01-20 foo")
+                .assert(predicate::path::missing());
+            temp.child("2024-01-21 bar")
+                .assert(predicate::path::missing());
+
+            Ok(())
+        })
+    }
+
+    // Undoing the previous rename while confirming the next one reverts it
+    // everywhere it was recorded, then confirms again
+    #[test]
+    fn undo_last() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let dir = temp.child("journal");
+            let foo = temp.existing_child("foo 20240120").unwrap();
+            let bar = temp.existing_child("bar 20240121").unwrap();
+            let paths = [foo.to_path_buf(), bar.to_path_buf()];
+
+            let mut seq = Sequence::new();
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface.expect_processing_ok().returning(|_| {});
+            interface.expect_processing_err().returning(|_, _| {});
+            interface.expect_finish().returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_| Confirmation::Accept);
+            interface
+                .expect_confirm()
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_| Confirmation::Undo);
+            interface
+                .expect_processing_undone()
+                .withf(|rep| rep.new_file_stem == "2024-01-20 foo")
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_| {});
+            interface
+                .expect_confirm()
+                .withf(|rep| rep.new_file_stem == "2024-01-21 bar")
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(|_| Confirmation::Skip);
+
+            let options = Options {
+                journal: Some(dir.to_path_buf()),
+                dir_log: true,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            foo.assert(predicate::path::exists());
+            bar.assert(predicate::path::exists());
+            temp.child("2024-01-20 foo")
+                .assert(predicate::path::missing());
+            assert!(Journal::latest(&dir)?.is_none());
+            temp.child(dir_log_reporter::FILE_NAME)
+                .assert(predicate::path::missing());
+
+            let stats = processing.stats();
+            assert_eq!(0, stats.renamed);
+            assert_eq!(2, stats.skipped);
+            assert!(stats.by_matcher.is_empty());
+            assert!(processing.assigned.is_empty());
+
+            Ok(())
+        })
+    }
+
+    // Ensure a path removed after its confirmation is reported as vanished,
+    // or skipped if asked to
+    #[test]
+    fn source_vanished() -> Result<()> {
+        for skip_vanished in [false, true] {
+            with_temp_dir(|temp| {
+                let mut interface = MockInterface::new();
+                let matchers = [matchers::ymd_boxed()];
+                let child = temp.existing_child("foo 20240120").unwrap();
+                let path = child.to_path_buf();
+                let paths = [path.clone()];
+
+                interface.expect_finish().times(1).returning(|_| {});
+                interface.expect_setup().returning(|_| {});
+                interface.expect_processing().returning(|_| {});
+                interface.expect_confirm().times(1).returning(move |_| {
+                    std::fs::remove_file(&path).unwrap();
+                    Confirmation::Accept
+                });
+                interface
+                    .expect_on_error()
+                    .times(if skip_vanished { 0 } else { 1 })
+                    .returning(|_, _| ErrorResolution::Report);
+                interface
+                    .expect_processing_err()
+                    .withf(move |_, e| match e {
+                        Error::SourceVanished(_) => !skip_vanished,
+                        Error::Skip(_) => skip_vanished,
+                        _ => false,
+                    })
+                    .times(1)
+                    .returning(|_, _| {});
+                interface.expect_processing_ok().never();
+
+                let options = Options {
+                    skip_vanished,
+                    ..Options::default()
+                };
+                let mut processing =
+                    Processing::new(&interface, &matchers, &paths)
+                        .with_options(options);
+                processing.run()?;
+
+                temp.child("2024-01-20 foo")
+                    .assert(predicate::path::missing());
+
+                Ok::<(), Error>(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    // Ensure special files are reported and skipped, unless allowed
+    #[cfg(unix)]
+    #[test]
+    fn not_regular_file() -> Result<()> {
+        for allow_special in [false, true] {
+            with_temp_dir(|temp| {
+                let mut interface = MockInterface::new();
+                let matchers = [matchers::ymd_boxed()];
+                let fifo = temp.child("foo 20240120");
+                let status = std::process::Command::new("mkfifo")
+                    .arg(fifo.path())
+                    .status()
+                    .unwrap();
+                assert!(status.success());
+                let paths = [fifo.to_path_buf()];
+
+                interface.expect_finish().times(1).returning(|_| {});
+                interface.expect_setup().returning(|_| {});
+                interface.expect_processing().returning(|_| {});
+                interface
+                    .expect_confirm()
+                    .times(usize::from(allow_special))
+                    .returning(|_| Confirmation::Accept);
+                interface
+                    .expect_processing_err()
+                    .withf(|path, e| match e {
+                        Error::NotRegularFile(other) => path == other,
+                        _ => false,
+                    })
+                    .times(usize::from(!allow_special))
+                    .returning(|_, _| {});
+                interface
+                    .expect_processing_ok()
+                    .times(usize::from(allow_special))
+                    .returning(|_| {});
+
+                let options = Options {
+                    allow_special,
+                    ..Options::default()
+                };
+                let mut processing =
+                    Processing::new(&interface, &matchers, &paths)
+                        .with_options(options);
+                processing.run()?;
+                assert_eq!(
+                    usize::from(!allow_special),
+                    processing.stats.skipped
+                );
+
+                temp.child("2024-01-20 foo").assert(match allow_special {
+                    true => predicate::path::exists().boxed(),
+                    false => predicate::path::missing().boxed(),
+                });
+
+                Ok::<(), Error>(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn stats_json() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let stats = temp.child("stats.json");
+
+            let paths = [
+                temp.existing_child("foo 20240120").unwrap().to_path_buf(),
+                temp.existing_child("bar 20240120").unwrap().to_path_buf(),
+                temp.existing_child("baz").unwrap().to_path_buf(),
+                temp.child("qux").to_path_buf(),
+            ];
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface.expect_processing_ok().returning(|_| {});
+            interface.expect_processing_err().returning(|_, _| {});
+            interface.expect_confirm().times(2).returning(|rep| {
+                match rep.file_stem.as_str() {
+                    "foo 20240120" => Confirmation::Accept,
+                    _ => Confirmation::Skip,
+                }
+            });
+            interface.expect_rescue().times(1).returning(Err);
+
+            let options = Options {
+                stats_json: Some(stats.to_path_buf()),
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            let json: serde_json::Value = serde_json::from_str(
+                &std::fs::read_to_string(stats.path()).unwrap(),
+            )
+            .unwrap();
+
+            assert_eq!(json["total"], 4);
+            assert_eq!(json["renamed"], 1);
+            assert_eq!(json["skipped"], 2);
+            assert_eq!(json["errored"], 1);
+            assert_eq!(json["by_matcher"], serde_json::json!({ "ymd": 1 }));
+            assert!(json["duration_ms"].is_u64());
+
+            Ok(())
+        })
+    }
+
+    // Ensure a path which can't be checked is reported and processing continues
+    #[test]
+    fn try_exists_failure() -> Result<()> {
+        use crate::fs::MockFs;
+        use std::io;
+
+        let mut interface = MockInterface::new();
+        let mut fs = MockFs::new();
+        fs.expect_is_special().returning(|_| Ok(false));
+        let matchers = [matchers::ymd_boxed()];
+        let paths =
+            [PathBuf::from("/locked/foo 20240120"), PathBuf::from("/bar")];
+
+        fs.expect_try_exists()
+            .with(predicate::eq(paths[0].clone()))
+            .times(1)
+            .returning(|_| {
+                Err(io::Error::from(io::ErrorKind::PermissionDenied))
+            });
+        fs.expect_try_exists()
+            .with(predicate::eq(paths[1].clone()))
+            .times(1)
+            .returning(|_| Ok(true));
+        fs.expect_rename().never();
+
+        let mut seq = Sequence::new();
+        interface.expect_finish().times(1).returning(|_| {});
+        interface
+            .expect_setup()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_processing()
+            .with(predicate::eq(paths[0].clone()))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_processing_err()
+            .withf(
+                |path, e| matches!(e, Error::PermissionDenied(p) if p == path),
+            )
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| {});
+        interface
+            .expect_processing()
+            .with(predicate::eq(paths[1].clone()))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_rescue()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(Err);
+        interface
+            .expect_processing_err()
+            .withf(|_, e| matches!(e, Error::NoMatch(_)))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| {});
+
+        let mut processing = Processing::new(&interface, &matchers, &paths)
+            .with_fs(Box::new(fs));
+        processing.run()
+    }
+
+    #[test]
+    fn dry_run() -> Result<()> {
+        use crate::fs::MockFs;
+
+        let mut interface = MockInterface::new();
+        let mut fs = MockFs::new();
+        fs.expect_is_special().returning(|_| Ok(false));
+        let matchers = [matchers::ymd_boxed()];
+        let paths = [PathBuf::from("/foo 20240120")];
+
+        fs.expect_try_exists().times(1).returning(|_| Ok(true));
+        fs.expect_rename().never();
+
+        let mut seq = Sequence::new();
+        interface.expect_finish().times(1).returning(|_| {});
+        interface
+            .expect_setup()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_processing()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+        interface
+            .expect_confirm()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Confirmation::Accept);
+        interface
+            .expect_processing_ok()
+            .withf(|rep| rep.new_file_stem == "2024-01-20 foo")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {});
+
+        let options = Options {
+            dry_run: true,
+            ..Options::default()
+        };
+        let mut processing = Processing::new(&interface, &matchers, &paths)
+            .with_options(options)
+            .with_fs(Box::new(fs));
+        processing.run()
+    }
+
+    // Ensure customized names with path separators are refused by default
+    #[test]
+    fn confirm_replace_with_separator() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::ymd_boxed()];
+            let child = temp.existing_child("foo 20240120").unwrap();
+            let paths = [child.to_path_buf()];
+
+            let mut replacement = Replacement::try_from(child.path())?;
+            replacement.new_file_stem = String::from("sub\\name");
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_confirm()
+                .times(1)
+                .return_once(move |_| Confirmation::Replace(replacement));
+            interface
+                .expect_processing_err()
+                .withf(|_, e| matches!(e, Error::PathSeparator(_)))
+                .times(1)
+                .returning(|_, _| {});
+            interface.expect_processing_ok().never();
+
+            let mut processing = Processing::new(&interface, &matchers, &paths);
+            processing.run()?;
+
+            child.assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure customized names are sanitized with the rules of the options
+    #[test]
+    fn rescue_sanitized() -> Result<()> {
+        use crate::replacement::{Sanitize, Settings};
+
+        let options = Options {
+            replacement: Settings {
+                sanitize: Sanitize {
+                    replacements: vec![('|', String::from(" - "))],
+                },
+                ..Settings::default()
+            },
+            ..Options::default()
+        };
+
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::weird_boxed()];
+            let child = temp.existing_child("foo").unwrap();
+            let paths = [child.to_path_buf()];
+
+            let mut replacement = Replacement::try_from(child.path())?;
+            replacement.new_file_stem = String::from("sanitized|name");
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_rescue()
+                .times(1)
+                .return_once(move |_| Ok(replacement));
+            interface
+                .expect_processing_ok()
+                .withf(|rep| rep.new_file_stem == "sanitized - name")
+                .times(1)
+                .returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::missing());
+            temp.child("sanitized - name")
+                .assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
+
+    // Ensure customized names with path separators are normalized if asked
+    #[test]
+    fn rescue_with_normalized_separator() -> Result<()> {
+        with_temp_dir(|temp| {
+            let mut interface = MockInterface::new();
+            let matchers = [matchers::weird_boxed()];
+            let child = temp.existing_child("foo").unwrap();
+            let paths = [child.to_path_buf()];
+
+            let mut replacement = Replacement::try_from(child.path())?;
+            replacement.new_file_stem = String::from("sub\\name");
+
+            interface.expect_finish().times(1).returning(|_| {});
+            interface.expect_setup().returning(|_| {});
+            interface.expect_processing().returning(|_| {});
+            interface
+                .expect_rescue()
+                .times(1)
+                .return_once(move |_| Ok(replacement));
+            interface
+                .expect_processing_ok()
+                .withf(|rep| rep.new_file_stem == "sub-name")
+                .times(1)
+                .returning(|_| {});
+            interface.expect_processing_err().never();
+
+            let options = Options {
+                normalize_separators: true,
+                ..Options::default()
+            };
+            let mut processing = Processing::new(&interface, &matchers, &paths)
+                .with_options(options);
+            processing.run()?;
+
+            child.assert(predicate::path::missing());
+            temp.child("sub-name").assert(predicate::path::exists());
+
+            Ok(())
+        })
+    }
 }
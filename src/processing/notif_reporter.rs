@@ -1,22 +1,128 @@
 #![cfg(feature = "notif")]
 
-use crate::processing::{Error, Reporter};
+use crate::processing::{Error, Reporter, Stats};
 use crate::replacement::Replacement;
 
 use std::cell::{Cell, RefCell};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use notify_rust::{
     get_capabilities, Hint, Notification, NotificationHandle, Timeout,
 };
 
+/// Destination of the notification sent at the end of the processing
+#[cfg_attr(test, mockall::automock)]
+pub trait Sink {
+    /// Show the body of the summary, offering to open the folder if any
+    fn finished(&self, body: &str, folder: Option<PathBuf>);
+}
+
+/// Sink showing desktop notifications
+#[derive(Debug, Default)]
+pub struct DesktopSink {
+    /// Thread awaiting the action of the last notification
+    action: RefCell<Option<JoinHandle<()>>>,
+}
+
+impl Sink for DesktopSink {
+    /// Notify the end of the processing, offering to open the folder
+    /// containing the renamed files if the server supports actions
+    ///
+    /// The action is awaited on another thread so that nothing blocks until
+    /// the notification is dismissed, the sink waiting for it a bounded time
+    /// when dropped so the program doesn't exit before it is handled.
+    fn finished(&self, body: &str, folder: Option<PathBuf>) {
+        let mut notif = Notification::new();
+        notif
+            .summary("Prefix by date")
+            .body(body)
+            .hint(Hint::Category("transfer.complete".to_owned()))
+            .timeout(Timeout::Default);
+
+        let actions = match get_capabilities() {
+            Ok(caps) => {
+                log::debug!("Notification capabilities: {:?}", caps);
+                caps.iter().any(|cap| cap == "actions")
+            }
+            Err(_) => false,
+        };
+
+        let Some(folder) = folder.filter(|_| actions) else {
+            let _ = notif.show();
+            return;
+        };
+
+        notif.action(OPEN_FOLDER, "Open folder");
+
+        if let Ok(handle) = notif.show() {
+            let action = std::thread::spawn(move || {
+                handle.wait_for_action(|action| {
+                    if action == OPEN_FOLDER {
+                        open_folder(&folder);
+                    }
+                });
+            });
+            self.action.replace(Some(action));
+        }
+    }
+}
+
+/// Longest time to wait for the action of the notification before exiting
+const ACTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl Drop for DesktopSink {
+    /// Wait until the notification is acted upon or dismissed, or for
+    /// ACTION_TIMEOUT at most
+    fn drop(&mut self) {
+        let Some(action) = self.action.take() else {
+            return;
+        };
+
+        let deadline = Instant::now() + ACTION_TIMEOUT;
+        while !action.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
 pub struct NotifReporter {
     count: Cell<usize>,
     current: Cell<usize>,
     notification: RefCell<Option<NotificationHandle>>,
+    folders: RefCell<Vec<PathBuf>>,
+    dry_run: bool,
+    /// Only notify the summary at the end, without a resident notification
+    /// of the progress
+    summary_only: bool,
+    sink: Box<dyn Sink>,
 }
 
 impl NotifReporter {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            count: Default::default(),
+            current: Default::default(),
+            notification: Default::default(),
+            folders: Default::default(),
+            dry_run,
+            summary_only: false,
+            sink: Box::<DesktopSink>::default(),
+        }
+    }
+
+    pub fn with_summary_only(mut self, summary_only: bool) -> Self {
+        self.summary_only = summary_only;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_sink(mut self, sink: Box<dyn Sink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
     fn inc_progress(&self) {
         self.current.set(self.current.get() + 1);
     }
@@ -45,6 +151,66 @@ impl NotifReporter {
             self.notification.replace(Some(notif));
         }
     }
+
+    /// Notify the summary of the processing, with the folder containing the
+    /// renamed files unless nothing was actually renamed
+    fn finished(&self, stats: &Stats) {
+        let folder = match self.dry_run {
+            true => None,
+            false => common_ancestor(self.folders.borrow().iter()),
+        };
+
+        self.sink
+            .finished(&summary_body(stats, self.dry_run), folder);
+    }
+}
+
+/// Describe the result of the processing, e.g. "Renamed 10 files, 2 errors"
+fn summary_body(stats: &Stats, dry_run: bool) -> String {
+    let mut body = match dry_run {
+        true => format!("Would rename {} files", stats.renamed),
+        false => format!("Renamed {} files", stats.renamed),
+    };
+
+    if stats.skipped > 0 {
+        body.push_str(&format!(", {} skipped", stats.skipped));
+    }
+
+    let errored = stats.errored;
+    if errored > 0 {
+        let noun = if errored == 1 { "error" } else { "errors" };
+        body.push_str(&format!(", {} {}", errored, noun));
+    }
+
+    body
+}
+
+const OPEN_FOLDER: &str = "open-folder";
+
+fn open_folder(folder: &Path) {
+    if let Err(error) =
+        std::process::Command::new("xdg-open").arg(folder).spawn()
+    {
+        log::warn!("Unable to open {:?}: {}", folder, error);
+    }
+}
+
+/// Compute the deepest directory containing all the given paths
+fn common_ancestor<'a, I>(mut paths: I) -> Option<PathBuf>
+where
+    I: Iterator<Item = &'a PathBuf>,
+{
+    let mut ancestor = paths.next()?.clone();
+
+    for path in paths {
+        while !path.starts_with(&ancestor) {
+            if !ancestor.pop() || ancestor.as_os_str().is_empty() {
+                return None;
+            }
+        }
+    }
+
+    Some(ancestor)
 }
 
 impl Drop for NotifReporter {
@@ -57,15 +223,7 @@ impl Drop for NotifReporter {
 
 impl Default for NotifReporter {
     fn default() -> Self {
-        if let Ok(caps) = get_capabilities() {
-            log::debug!("Notification capabilities: {:?}", caps);
-        }
-
-        Self {
-            count: Default::default(),
-            current: Default::default(),
-            notification: Default::default(),
-        }
+        Self::new(false)
     }
 }
 
@@ -73,6 +231,10 @@ impl Reporter for NotifReporter {
     /// Report the total count of elements about to be processed
     fn setup(&self, count: usize) {
         self.count.set(count);
+        if self.summary_only {
+            return;
+        }
+
         let notif = Notification::new()
             .summary("Prefix by date")
             .body("Processing files")
@@ -91,7 +253,11 @@ impl Reporter for NotifReporter {
     }
 
     /// Processing went well and ended-up with this replacement
-    fn processing_ok(&self, _replacement: &Replacement) {
+    fn processing_ok(&self, replacement: &Replacement) {
+        if let Some(folder) = replacement.new_path().parent() {
+            self.folders.borrow_mut().push(folder.to_path_buf());
+        }
+
         self.inc_progress();
     }
 
@@ -99,4 +265,100 @@ impl Reporter for NotifReporter {
     fn processing_err(&self, _path: &Path, _error: &Error) {
         self.inc_progress();
     }
+
+    /// The rename was reverted, its folder being left as is
+    fn processing_undone(&self, _replacement: &Replacement) {}
+
+    /// Processing is over, replace the progress with the summary
+    fn finish(&self, stats: &Stats) {
+        if let Some(notif) = self.notification.take() {
+            notif.close();
+        }
+
+        self.finished(stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    fn ancestor(paths: &[&str]) -> Option<PathBuf> {
+        let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        common_ancestor(paths.iter())
+    }
+
+    #[test]
+    fn common_ancestor_of_paths() {
+        assert_eq!(None, ancestor(&[]));
+        assert_eq!(Some(PathBuf::from("/foo/bar")), ancestor(&["/foo/bar"]));
+        assert_eq!(
+            Some(PathBuf::from("/foo/bar")),
+            ancestor(&["/foo/bar", "/foo/bar"])
+        );
+        assert_eq!(
+            Some(PathBuf::from("/foo")),
+            ancestor(&["/foo/bar", "/foo/baz/qux", "/foo"])
+        );
+        assert_eq!(Some(PathBuf::from("/")), ancestor(&["/foo", "/bar"]));
+        assert_eq!(None, ancestor(&["foo", "bar"]));
+    }
+
+    #[test]
+    fn summary_notification() {
+        use mockall::predicate;
+
+        let mut sink = MockSink::new();
+        sink.expect_finished()
+            .with(
+                predicate::eq("Renamed 1 files, 1 skipped, 2 errors"),
+                predicate::eq(Some(PathBuf::from("/foo"))),
+            )
+            .times(1)
+            .returning(|_, _| {});
+
+        let reporter = NotifReporter::new(false)
+            .with_summary_only(true)
+            .with_sink(Box::new(sink));
+        let replacement =
+            Replacement::try_from(Path::new("/foo/bar.txt")).unwrap();
+        let path = Path::new("/foo/baz");
+
+        let mut stats = Stats::default();
+        reporter.setup(4);
+        reporter.processing_ok(&replacement);
+        stats.renamed("ymd");
+        for error in
+            [Error::no_match(path), Error::not_found(path), Error::Abort]
+        {
+            reporter.processing_err(path, &error);
+            stats.failed(&error);
+        }
+        reporter.finish(&stats);
+        assert!(reporter.notification.borrow().is_none());
+    }
+
+    #[test]
+    fn summary_notification_dry_run() {
+        use mockall::predicate;
+
+        let mut sink = MockSink::new();
+        sink.expect_finished()
+            .with(predicate::eq("Would rename 1 files"), predicate::eq(None))
+            .times(1)
+            .returning(|_, _| {});
+
+        let reporter = NotifReporter::new(true)
+            .with_summary_only(true)
+            .with_sink(Box::new(sink));
+        let replacement =
+            Replacement::try_from(Path::new("/foo/bar.txt")).unwrap();
+
+        let mut stats = Stats::default();
+        reporter.setup(1);
+        reporter.processing_ok(&replacement);
+        stats.renamed("ymd");
+        reporter.finish(&stats);
+    }
 }
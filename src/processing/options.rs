@@ -0,0 +1,86 @@
+use crate::application::{ExtensionFilter, OnConflict, Organize, Touch, Walk};
+use crate::matcher::{Matcher, Naming};
+use crate::processing::Transform;
+use crate::replacement;
+
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+/// Options altering the way paths are processed
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Transforms applied in order to every replacement found by a matcher
+    pub transforms: Vec<Transform>,
+    /// Convert the extension of the new file names to lowercase
+    pub lower_ext: bool,
+    /// Replace path separators in customized names instead of refusing them
+    pub normalize_separators: bool,
+    /// Let interactive interfaces customize the extension along with the
+    /// rest of the file name
+    pub customize_extension: bool,
+    /// Skip the paths whose name already starts with a date within this
+    /// number of days of the date found by the matcher
+    pub skip_if_dated_within_days: Option<u64>,
+    /// Report the replacements without actually renaming anything
+    pub dry_run: bool,
+    /// Stop once this number of paths are renamed
+    pub limit: Option<usize>,
+    /// Stop at the first path failing to be processed, instead of going on
+    /// with the next ones
+    pub fail_fast: bool,
+    /// Skip the paths whose matched date is before this one
+    pub since: Option<NaiveDate>,
+    /// Skip the paths whose matched date is after this one
+    pub until: Option<NaiveDate>,
+    /// Report the paths whose metadata couldn't be read by a matcher as
+    /// errors, instead of treating them as not matching
+    pub no_metadata_fallback: bool,
+    /// Matcher used by non-interactive interfaces to rescue the paths no
+    /// other matcher could handle
+    pub default_rescue: Option<Box<dyn Matcher>>,
+    /// Matchers suggesting alternatives to the paths to rescue in the
+    /// interactive interfaces
+    pub rescue_alternatives: Vec<Box<dyn Matcher>>,
+    /// Write the statistics of the run as JSON to this path
+    pub stats_json: Option<PathBuf>,
+    /// Directory to move the renamed files into, instead of their own
+    pub output_dir: Option<PathBuf>,
+    /// Subdirectories of their date to move the renamed files into
+    pub organize: Option<Organize>,
+    /// How to handle replacements whose target already exists
+    pub on_conflict: OnConflict,
+    /// Time of the renamed files to set to the date they are prefixed by
+    pub touch: Touch,
+    /// Skip paths removed between their confirmation and their renaming
+    /// instead of reporting an error
+    pub skip_vanished: bool,
+    /// Process special files, like FIFOs or sockets, instead of skipping them
+    pub allow_special: bool,
+    /// Directory in which to keep the journal of the renames, to undo them
+    pub journal: Option<PathBuf>,
+    /// Record the renames in a log inside each directory where they happen
+    pub dir_log: bool,
+    /// Names of the matchers whose replacements are accepted without
+    /// confirmation
+    pub auto_accept: Vec<String>,
+    /// Expansion of the directories given to the interfaces during the run,
+    /// e.g. dropped onto the GUI, when running recursively
+    pub walk: Option<Walk>,
+    /// Filter on the extensions of the paths given to the interfaces during
+    /// the run, e.g. dropped onto the GUI
+    pub extension_filter: ExtensionFilter,
+    /// Hide the progress of the run
+    pub quiet: bool,
+    /// Log the progress every this number of paths when logging to the
+    /// systemd journal
+    pub journal_progress_interval: Option<usize>,
+    /// Only notify the summary at the end of the run, instead of showing a
+    /// resident notification of the progress
+    #[cfg(feature = "notif")]
+    pub notif_summary_only: bool,
+    /// How the matchers compose the new file stems
+    pub naming: Naming,
+    /// Settings of the replacements created from the paths and executed
+    pub replacement: replacement::Settings,
+}
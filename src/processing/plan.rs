@@ -0,0 +1,293 @@
+use crate::matcher::{Matcher, Naming};
+use crate::processing::matcher::check;
+use crate::processing::{
+    Communication, Confirmation, Error, ErrorResolution, Options, Processing,
+    Reporter, Stats,
+};
+use crate::replacement::{Replacement, Settings};
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+/// Name reported for the paths no matcher claims
+pub const UNMATCHED: &str = "unmatched";
+
+/// Name of the matcher claiming each path, i.e. the first one able to handle
+/// it, or None if the path would need a rescue
+pub fn attribute<'a>(
+    matchers: &'a [Box<dyn Matcher>],
+    paths: &[PathBuf],
+    naming: &Naming,
+    settings: &Settings,
+) -> Vec<Option<&'a str>> {
+    paths
+        .iter()
+        .map(|path| {
+            matchers
+                .iter()
+                .find(|matcher| {
+                    check(matcher.as_ref(), path, naming, settings).is_some()
+                })
+                .map(|matcher| matcher.name())
+        })
+        .collect()
+}
+
+/// New path of each path given by a dry run of the processing with these
+/// options, along with the name of the matcher claiming it, or None if the
+/// path would need a rescue, be skipped or fail
+pub fn preview<'a>(
+    matchers: &'a [Box<dyn Matcher>],
+    paths: &[PathBuf],
+    options: &Options,
+) -> Vec<Option<(PathBuf, &'a str)>> {
+    let options = Options {
+        dry_run: true,
+        stats_json: None,
+        ..options.clone()
+    };
+    let names =
+        attribute(matchers, paths, &options.naming, &options.replacement);
+    let collector = Collector::default();
+
+    let mut processing = Processing::new(&collector, matchers, paths)
+        .with_options(options)
+        .without_reporters();
+    if let Err(error) = processing.run() {
+        log::debug!("Preview stopped early: {}", error);
+    }
+
+    let mut new_paths = collector.new_paths.into_inner();
+    new_paths.resize(paths.len(), None);
+
+    new_paths
+        .into_iter()
+        .zip(names)
+        .map(|(new_path, name)| new_path.zip(name))
+        .collect()
+}
+
+/// Interface accepting every replacement found by the matchers and
+/// collecting the new path of each processed path, without rescuing any
+#[derive(Default)]
+struct Collector {
+    new_paths: RefCell<Vec<Option<PathBuf>>>,
+}
+
+impl Reporter for Collector {
+    fn setup(&self, _count: usize) {}
+    fn processing(&self, _path: &Path) {
+        self.new_paths.borrow_mut().push(None);
+    }
+    fn processing_ok(&self, replacement: &Replacement) {
+        if let Some(new_path) = self.new_paths.borrow_mut().last_mut() {
+            *new_path = Some(replacement.new_path());
+        }
+    }
+    fn processing_err(&self, _path: &Path, _error: &Error) {}
+    fn processing_undone(&self, _replacement: &Replacement) {}
+    fn finish(&self, _stats: &Stats) {}
+}
+
+impl Communication for Collector {
+    fn confirm(&self, _replacement: &Replacement) -> Confirmation {
+        Confirmation::Accept
+    }
+    fn rescue(&self, error: Error) -> crate::processing::Result<Replacement> {
+        Err(error)
+    }
+    fn on_error(&self, _path: &Path, _error: &Error) -> ErrorResolution {
+        ErrorResolution::Report
+    }
+}
+
+/// Outcome of every matcher for the path, in the order they are tried: the
+/// replacement it gives or the reason it doesn't match
+pub fn explain<'a>(
+    matchers: &'a [Box<dyn Matcher>],
+    path: &Path,
+    naming: &Naming,
+    settings: &Settings,
+) -> Vec<(&'a str, Result<Replacement, String>)> {
+    matchers
+        .iter()
+        .map(|matcher| {
+            let explained = matcher.check_explained(path, naming, settings);
+            (matcher.name(), explained)
+        })
+        .collect()
+}
+
+/// Number of paths claimed by each matcher, in the order of the matchers,
+/// followed by the number of unmatched paths
+pub fn count<'a>(
+    matchers: &'a [Box<dyn Matcher>],
+    paths: &[PathBuf],
+    naming: &Naming,
+    settings: &Settings,
+) -> Vec<(&'a str, usize)> {
+    let attribution = attribute(matchers, paths, naming, settings);
+    let claimed_by = |name: Option<&str>| {
+        attribution.iter().filter(|claim| **claim == name).count()
+    };
+
+    matchers
+        .iter()
+        .map(|matcher| (matcher.name(), claimed_by(Some(matcher.name()))))
+        .chain(std::iter::once((UNMATCHED, claimed_by(None))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, assert_fs::*, matchers, test, with_temp_dir};
+
+    #[test]
+    fn attribute() {
+        use crate::matcher::predetermined_date::TODAY;
+
+        let paths = [PathBuf::from("foo 20240120"), PathBuf::from("bar")];
+
+        let matchers = [matchers::ymd_boxed(), matchers::today_boxed()];
+        assert_eq!(
+            vec![Some("ymd"), Some(TODAY)],
+            super::attribute(
+                &matchers,
+                &paths,
+                &Naming::default(),
+                &Settings::default()
+            )
+        );
+
+        let matchers = [matchers::panicking_boxed(), matchers::ymd_boxed()];
+        assert_eq!(
+            vec![Some("ymd"), None],
+            super::attribute(
+                &matchers,
+                &paths,
+                &Naming::default(),
+                &Settings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn preview() {
+        use predicates::prelude::*;
+
+        with_temp_dir(|temp| {
+            let paths = ["foo 20240120", "bar"]
+                .iter()
+                .map(|name| temp.existing_child(name).unwrap().to_path_buf())
+                .collect::<Vec<_>>();
+
+            let matchers = [matchers::panicking_boxed(), matchers::ymd_boxed()];
+            assert_eq!(
+                vec![
+                    Some((temp.child("2024-01-20 foo").to_path_buf(), "ymd")),
+                    None
+                ],
+                super::preview(&matchers, &paths, &Options::default())
+            );
+            temp.child("foo 20240120").assert(predicate::path::exists());
+        })
+    }
+
+    // The table shows what the processing would do with the options
+    #[test]
+    fn preview_with_options() {
+        use crate::application::OnConflict;
+        use crate::processing::Transform;
+
+        with_temp_dir(|temp| {
+            let paths = ["baz 20240110", "Foo 20240120", "Bar 20240121"]
+                .iter()
+                .map(|name| temp.existing_child(name).unwrap().to_path_buf())
+                .collect::<Vec<_>>();
+            temp.existing_child("2024-01-20 foo").unwrap();
+
+            let options = Options {
+                transforms: vec![Transform::Lowercase],
+                on_conflict: OnConflict::Suffix,
+                since: chrono::NaiveDate::from_ymd_opt(2024, 1, 15),
+                limit: Some(1),
+                ..Options::default()
+            };
+            let matchers = [matchers::ymd_boxed()];
+            assert_eq!(
+                vec![
+                    None,
+                    Some((
+                        temp.child("2024-01-20 foo (1)").to_path_buf(),
+                        "ymd"
+                    )),
+                    None
+                ],
+                super::preview(&matchers, &paths, &options)
+            );
+        })
+    }
+
+    #[test]
+    fn explain() {
+        use crate::matcher::predetermined_date::TODAY;
+
+        let matchers = [matchers::ymd_boxed(), matchers::today_boxed()];
+        let explanation = super::explain(
+            &matchers,
+            Path::new("bar"),
+            &Naming::default(),
+            &Settings::default(),
+        )
+        .into_iter()
+        .map(|(name, result)| (name, result.map(|r| r.new_file_stem)))
+        .collect::<Vec<_>>();
+
+        assert_eq!(2, explanation.len());
+        assert_eq!(("ymd", Err("no regex match".into())), explanation[0]);
+        assert_eq!(TODAY, explanation[1].0);
+        assert!(explanation[1]
+            .1
+            .as_ref()
+            .is_ok_and(|stem| stem.ends_with(" bar")));
+    }
+
+    #[test]
+    fn count() {
+        use crate::matcher::Metadata;
+
+        with_temp_dir(|temp| {
+            let paths = ["foo 20240120", "bar 20240121", "baz", "qux"]
+                .iter()
+                .map(|name| temp.existing_child(name).unwrap().to_path_buf())
+                .collect::<Vec<_>>();
+
+            let matchers = [
+                matchers::weird_boxed(),
+                matchers::ymd_boxed(),
+                Box::new(Metadata::new_created("%Y")) as Box<dyn Matcher>,
+            ];
+            assert_eq!(
+                vec![("weird", 0), ("ymd", 2), ("created", 2), (UNMATCHED, 0)],
+                super::count(
+                    &matchers,
+                    &paths,
+                    &Naming::default(),
+                    &Settings::default()
+                )
+            );
+
+            let matchers = [matchers::ymd_boxed()];
+            assert_eq!(
+                vec![("ymd", 2), (UNMATCHED, 2)],
+                super::count(
+                    &matchers,
+                    &paths,
+                    &Naming::default(),
+                    &Settings::default()
+                )
+            );
+        });
+    }
+}
@@ -0,0 +1,197 @@
+use crate::processing::Error;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Name under which paths rescued by the interface are counted
+pub const RESCUE: &str = "rescue";
+
+/// Outcome of a processing run which wasn't aborted
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Every path was renamed, skipped or left without match
+    #[default]
+    Success,
+    /// Some paths failed to be processed
+    Failures,
+}
+
+/// Aggregate statistics of a processing run
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub total: usize,
+    pub renamed: usize,
+    /// Paths left untouched on purpose, i.e. skipped or without match
+    pub skipped: usize,
+    pub errored: usize,
+    /// Count of renamed paths per matcher name
+    pub by_matcher: BTreeMap<String, usize>,
+    pub duration: Duration,
+}
+
+impl Stats {
+    /// Record a path renamed with a replacement found by the given matcher
+    pub fn renamed(&mut self, matcher: &str) {
+        self.renamed += 1;
+        *self.by_matcher.entry(matcher.to_string()).or_default() += 1;
+    }
+
+    /// Record a path whose rename found by the given matcher was reverted,
+    /// leaving it untouched
+    pub fn undone(&mut self, matcher: &str) {
+        self.renamed = self.renamed.saturating_sub(1);
+        self.skipped += 1;
+        if let Some(count) = self.by_matcher.get_mut(matcher) {
+            *count -= 1;
+            if *count == 0 {
+                self.by_matcher.remove(matcher);
+            }
+        }
+    }
+
+    /// Record a path whose processing ended with the given error
+    pub fn failed(&mut self, error: &Error) {
+        match error.is_failure() {
+            true => self.errored += 1,
+            false => self.skipped += 1,
+        }
+    }
+
+    /// Outcome of the run, failed if any path errored
+    pub fn outcome(&self) -> Outcome {
+        if self.errored > 0 {
+            Outcome::Failures
+        } else {
+            Outcome::Success
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "total": self.total,
+            "renamed": self.renamed,
+            "skipped": self.skipped,
+            "errored": self.errored,
+            "by_matcher": self.by_matcher,
+            "duration_ms": self.duration.as_millis() as u64,
+        })
+    }
+
+    /// Write the statistics as JSON to the given path
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json())?;
+        std::fs::write(path, json + "\n")
+    }
+}
+
+/// One-line summary of the run, like "12 renamed, 2 skipped, 1 error"
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} renamed, {} skipped, {} {}",
+            self.renamed,
+            self.skipped,
+            self.errored,
+            if self.errored == 1 { "error" } else { "errors" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    use std::path::PathBuf;
+
+    #[test]
+    fn outcome() {
+        let mut stats = Stats::default();
+        assert_eq!(Outcome::Success, stats.outcome());
+
+        stats.renamed("ymd");
+        stats.failed(&Error::Skip(PathBuf::from("foo")));
+        stats.failed(&Error::no_match(&PathBuf::from("bar")));
+        assert_eq!(Outcome::Success, stats.outcome());
+
+        stats.failed(&Error::TargetExists(PathBuf::from("baz")));
+        assert_eq!(Outcome::Failures, stats.outcome());
+    }
+
+    #[test]
+    fn record() {
+        let mut stats = Stats::default();
+
+        stats.renamed("ymd");
+        stats.renamed("ymd");
+        stats.renamed(RESCUE);
+        stats.failed(&Error::Skip(PathBuf::from("foo")));
+        stats.failed(&Error::no_match(&PathBuf::from("bar")));
+        stats.failed(&Error::not_found(&PathBuf::from("baz")));
+
+        assert_eq!(3, stats.renamed);
+        assert_eq!(2, stats.skipped);
+        assert_eq!(1, stats.errored);
+        assert_eq!(Outcome::Failures, stats.outcome());
+        assert_eq!(Some(&2), stats.by_matcher.get("ymd"));
+        assert_eq!(Some(&1), stats.by_matcher.get(RESCUE));
+    }
+
+    #[test]
+    fn undone() {
+        let mut stats = Stats::default();
+
+        stats.renamed("ymd");
+        stats.renamed("ymd");
+        stats.renamed(RESCUE);
+        stats.undone("ymd");
+        stats.undone(RESCUE);
+
+        assert_eq!(1, stats.renamed);
+        assert_eq!(2, stats.skipped);
+        assert_eq!(Some(&1), stats.by_matcher.get("ymd"));
+        assert_eq!(None, stats.by_matcher.get(RESCUE));
+        assert_eq!("1 renamed, 2 skipped, 0 errors", stats.to_string());
+    }
+
+    #[test]
+    fn display() {
+        let mut stats = Stats::default();
+        assert_eq!("0 renamed, 0 skipped, 0 errors", stats.to_string());
+
+        stats.failed(&Error::Abort);
+        assert_eq!("0 renamed, 0 skipped, 1 error", stats.to_string());
+
+        stats.renamed("ymd");
+        stats.failed(&Error::Skip(PathBuf::from("foo")));
+        stats.failed(&Error::not_found(&PathBuf::from("bar")));
+        assert_eq!("1 renamed, 1 skipped, 2 errors", stats.to_string());
+    }
+
+    #[test]
+    fn to_json() {
+        let mut stats = Stats {
+            total: 2,
+            duration: Duration::from_millis(42),
+            ..Stats::default()
+        };
+        stats.renamed("ymd");
+        stats.failed(&Error::Abort);
+
+        assert_eq!(
+            serde_json::json!({
+                "total": 2,
+                "renamed": 1,
+                "skipped": 0,
+                "errored": 1,
+                "by_matcher": { "ymd": 1 },
+                "duration_ms": 42,
+            }),
+            stats.to_json()
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Post-processing step applied to the new file stem once a matcher found a
+/// replacement
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transform {
+    /// Convert the whole stem to lowercase
+    Lowercase,
+    /// Convert the whole stem to uppercase
+    Uppercase,
+    /// Replace every run of non-alphanumeric characters with a single dash
+    Slugify,
+    /// Strip diacritics and drop any remaining non-ASCII character
+    Ascii,
+    /// Append the given text at the end of the stem
+    Append(String),
+}
+
+impl Transform {
+    pub fn apply(&self, stem: &str) -> String {
+        match self {
+            Self::Lowercase => stem.to_lowercase(),
+            Self::Uppercase => stem.to_uppercase(),
+            Self::Slugify => slugify(stem),
+            Self::Ascii => ascii(stem),
+            Self::Append(text) => format!("{}{}", stem, text),
+        }
+    }
+
+    /// Apply all the transforms in order
+    pub fn apply_all(transforms: &[Transform], stem: &str) -> String {
+        transforms
+            .iter()
+            .fold(stem.to_string(), |stem, transform| transform.apply(&stem))
+    }
+}
+
+fn slugify(stem: &str) -> String {
+    stem.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn ascii(stem: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+
+    stem.nfd().filter(char::is_ascii).collect()
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "lowercase" => Ok(Self::Lowercase),
+            "uppercase" => Ok(Self::Uppercase),
+            "slugify" => Ok(Self::Slugify),
+            "ascii" => Ok(Self::Ascii),
+            _ => match string.strip_prefix("append=") {
+                Some(text) => Ok(Self::Append(text.to_string())),
+                None => Err(format!(
+                    "Unknown transform {:?}, expected one of lowercase, \
+                    uppercase, slugify, ascii or append=<TEXT>",
+                    string
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Lowercase => write!(f, "lowercase"),
+            Self::Uppercase => write!(f, "uppercase"),
+            Self::Slugify => write!(f, "slugify"),
+            Self::Ascii => write!(f, "ascii"),
+            Self::Append(text) => write!(f, "append={}", text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    #[test]
+    fn apply() {
+        let stem = "2024-01-20 Café au Lait";
+
+        assert_eq!("2024-01-20 café au lait", Transform::Lowercase.apply(stem));
+        assert_eq!("2024-01-20 CAFÉ AU LAIT", Transform::Uppercase.apply(stem));
+        assert_eq!("2024-01-20-Café-au-Lait", Transform::Slugify.apply(stem));
+        assert_eq!("2024-01-20 Cafe au Lait", Transform::Ascii.apply(stem));
+        assert_eq!(
+            "2024-01-20 Café au Lait!",
+            Transform::Append(String::from("!")).apply(stem)
+        );
+    }
+
+    #[test]
+    fn apply_all_in_order() {
+        let stem = "2024-01-20 foo bar";
+        let append = Transform::Append(String::from(" Draft"));
+
+        assert_eq!(
+            "2024-01-20-foo-bar Draft",
+            Transform::apply_all(&[Transform::Slugify, append.clone()], stem)
+        );
+        assert_eq!(
+            "2024-01-20-foo-bar-Draft",
+            Transform::apply_all(&[append.clone(), Transform::Slugify], stem)
+        );
+
+        assert_eq!(
+            "2024-01-20 FOO BAR DRAFT",
+            Transform::apply_all(&[append.clone(), Transform::Uppercase], stem)
+        );
+        assert_eq!(
+            "2024-01-20 foo bar draft",
+            Transform::apply_all(
+                &[append, Transform::Uppercase, Transform::Lowercase],
+                stem
+            )
+        );
+    }
+
+    #[test]
+    fn apply_all_empty() {
+        assert_eq!("foo", Transform::apply_all(&[], "foo"));
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Ok(Transform::Lowercase), "lowercase".parse());
+        assert_eq!(Ok(Transform::Uppercase), "uppercase".parse());
+        assert_eq!(Ok(Transform::Slugify), "slugify".parse());
+        assert_eq!(Ok(Transform::Ascii), "ascii".parse());
+        assert_eq!(
+            Ok(Transform::Append(String::from("-foo"))),
+            "append=-foo".parse()
+        );
+        assert!("foo".parse::<Transform>().is_err());
+    }
+}
@@ -1,20 +1,301 @@
+use crate::application::Touch;
+use crate::fs::{Fs, RealFs};
 use crate::processing::{Error, Result};
 
+use std::collections::HashSet;
 use std::fmt;
+use std::fs::FileTimes;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, FixedOffset};
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Replacement {
     pub parent: PathBuf,
     pub file_stem: String,
+    /// File stem as found on disk, if it differs from the normalized one
+    pub disk_file_stem: Option<String>,
     pub new_file_stem: String,
     pub extension: String,
+    /// Extension of the new file name, if different from the current one
+    pub new_extension: Option<String>,
+    /// Date-time determined by the matcher, if any
+    pub date_time: Option<DateTime<FixedOffset>>,
+    /// Format used to render the date in the new file stem, if any
+    pub date_format: Option<String>,
+    /// Directory to move the file into, if not its current one
+    pub new_parent: Option<PathBuf>,
 }
 
 impl TryFrom<&Path> for Replacement {
     type Error = Error;
 
+    /// Split the path using the default Settings
     fn try_from(path: &Path) -> Result<Self> {
+        Self::try_from_with(path, &Settings::default())
+    }
+}
+
+/// Settings of the replacements created from paths and executed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Rules to split the file names between stem and extension
+    pub extensions: Extensions,
+    /// Normalization of the file stems, the name found on disk being kept
+    /// to rename the file
+    pub normalization: Normalization,
+    /// Resolve the parent directory, following symlinks, or keep it as given
+    pub canonicalize: bool,
+    /// Retries of the renames failing for a transient reason
+    pub rename_retries: RenameRetries,
+    /// Replacements for the characters not wanted in customized file names
+    pub sanitize: Sanitize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            extensions: Extensions::new(),
+            normalization: Normalization::default(),
+            canonicalize: true,
+            rename_retries: RenameRetries::new(),
+            sanitize: Sanitize::new(),
+        }
+    }
+}
+
+/// Rules to split file names between stem and extension
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Extensions {
+    /// Extensions never treated as such but kept in the stem, like `min` in
+    /// `app.min.js` or `app.min`
+    pub keep_in_stem: Vec<String>,
+    /// Extensions made of multiple parts, like `tar.gz`, kept together as the
+    /// extension
+    pub compound: Vec<String>,
+}
+
+impl Extensions {
+    pub const fn new() -> Self {
+        Self {
+            keep_in_stem: Vec::new(),
+            compound: Vec::new(),
+        }
+    }
+
+    /// Adjust the stem and extension as split by Path
+    fn split(&self, file_stem: String, ext: String) -> (String, String) {
+        if self.keep_in_stem.contains(&ext) {
+            return (format!("{}.{}", file_stem, ext), String::new());
+        }
+
+        let (mut file_stem, mut ext) = (file_stem, ext);
+
+        // Move the last part of the stem to the extension while it forms a
+        // compound extension
+        while let Some((stem, part)) = split_extension(&file_stem) {
+            let compound = format!("{}.{}", part, ext);
+            if self.keep_in_stem.iter().any(|keep| keep == part)
+                || !self.is_compound(&compound)
+            {
+                break;
+            }
+
+            file_stem = stem.to_string();
+            ext = compound;
+        }
+
+        (file_stem, ext)
+    }
+
+    fn is_compound(&self, ext: &str) -> bool {
+        self.compound
+            .iter()
+            .any(|compound| compound.eq_ignore_ascii_case(ext))
+    }
+}
+
+/// Resolve the parent directory if asked to, but rescue silently if it
+/// doesn't work
+fn resolve_parent(parent: &Path, canonicalize: bool) -> PathBuf {
+    match canonicalize {
+        true => parent.canonicalize().unwrap_or(parent.to_path_buf()),
+        false => parent.to_path_buf(),
+    }
+}
+
+/// Unicode normalization form of the file stems given to the matchers
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Normalization {
+    /// Composed characters, e.g. `é` as a single code point
+    #[default]
+    Nfc,
+    /// Decomposed characters, e.g. `é` as `e` and a combining acute accent,
+    /// as stored by macOS
+    Nfd,
+    /// Keep the file stems as found on disk
+    None,
+}
+
+impl FromStr for Normalization {
+    type Err = String;
+
+    fn from_str(string: &str) -> std::result::Result<Self, Self::Err> {
+        match string {
+            "nfc" => Ok(Self::Nfc),
+            "nfd" => Ok(Self::Nfd),
+            "none" => Ok(Self::None),
+            _ => Err(format!("Unknown normalization {:?}", string)),
+        }
+    }
+}
+
+impl Normalization {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Self::Nfc => text.nfc().collect(),
+            Self::Nfd => text.nfd().collect(),
+            Self::None => text.to_string(),
+        }
+    }
+}
+
+/// Retries of the renames failing for a transient reason, e.g. a busy file
+/// on a network filesystem
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenameRetries {
+    /// Number of retries after the first attempt
+    pub count: u32,
+    /// Delay before the first retry, doubled before each of the next ones
+    pub delay: Duration,
+}
+
+impl Default for RenameRetries {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenameRetries {
+    pub const fn new() -> Self {
+        Self {
+            count: 3,
+            delay: Duration::from_millis(50),
+        }
+    }
+
+    /// Rename the file, retrying while it fails for a transient reason
+    fn rename(&self, fs: &dyn Fs, from: &Path, to: &Path) -> io::Result<()> {
+        let mut delay = self.delay;
+
+        for _ in 0..self.count {
+            match fs.rename(from, to) {
+                Err(error) if is_transient(&error) => {
+                    log::debug!(
+                        "Retrying to rename {:?} in {:?}: {}",
+                        from,
+                        delay,
+                        error
+                    );
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+
+        fs.rename(from, to)
+    }
+}
+
+/// Check if the error may not happen again, e.g. EBUSY or EAGAIN
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ResourceBusy
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+    )
+}
+
+/// Replacements for characters not wanted in new file names
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sanitize {
+    /// Each character with the text replacing it
+    pub replacements: Vec<(char, String)>,
+}
+
+/// Characters invalid in file names on Windows, besides control characters
+const WINDOWS_ILLEGAL: [char; 9] =
+    ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Check if the character is invalid in a file name on the current platform
+pub fn is_illegal(c: char) -> bool {
+    if cfg!(windows) {
+        is_illegal_on_windows(c)
+    } else if cfg!(target_os = "macos") {
+        is_illegal_on_macos(c)
+    } else {
+        is_illegal_on_unix(c)
+    }
+}
+
+fn is_illegal_on_windows(c: char) -> bool {
+    c.is_ascii_control() || WINDOWS_ILLEGAL.contains(&c)
+}
+
+/// The colon is displayed as a slash by the Finder
+fn is_illegal_on_macos(c: char) -> bool {
+    c == ':' || is_illegal_on_unix(c)
+}
+
+fn is_illegal_on_unix(c: char) -> bool {
+    matches!(c, '/' | '\0')
+}
+
+impl Sanitize {
+    pub const fn new() -> Self {
+        Self {
+            replacements: Vec::new(),
+        }
+    }
+
+    /// Replace the characters having a replacement in the text
+    pub fn apply(&self, text: &str) -> String {
+        text.chars().fold(String::new(), |mut sanitized, c| {
+            match self.replacements.iter().find(|(from, _)| *from == c) {
+                Some((_, to)) => sanitized.push_str(to),
+                None => sanitized.push(c),
+            }
+            sanitized
+        })
+    }
+}
+
+/// Split the stem and extension of a file name, like Path does
+fn split_extension(file_name: &str) -> Option<(&str, &str)> {
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => Some((stem, ext)),
+        _ => None,
+    }
+}
+
+impl Replacement {
+    /// Create a replacement for the given path, splitting the file name
+    /// according to the extensions rules of the settings
+    ///
+    /// The leading dot of hidden files is part of the stem, never an
+    /// extension separator, e.g. `.bashrc` has no extension while
+    /// `.config.toml` is a `toml` file named `.config`
+    ///
+    /// The file stem is normalized according to the Normalization of the
+    /// settings
+    pub fn try_from_with(path: &Path, settings: &Settings) -> Result<Self> {
         let parent = path
             .parent()
             .ok_or(Error::PathUnwrap(path.into(), "parent"))?;
@@ -31,54 +312,393 @@ impl TryFrom<&Path> for Replacement {
                 .to_string(),
             None => "".to_string(),
         };
+        let (disk_file_stem, ext) = settings.extensions.split(file_stem, ext);
+        let file_stem = settings.normalization.apply(&disk_file_stem);
+        let disk_file_stem = Some(disk_file_stem).filter(|s| *s != file_stem);
 
-        // Try to resolve the path, but rescue silently if it doesn't work
-        let parent = parent.canonicalize().unwrap_or(parent.to_path_buf());
+        let parent = resolve_parent(parent, settings.canonicalize);
 
         Ok(Replacement {
             parent,
             file_stem: file_stem.clone(),
+            disk_file_stem,
             new_file_stem: file_stem,
             extension: ext,
+            new_extension: None,
+            date_time: None,
+            date_format: None,
+            new_parent: None,
         })
     }
 }
 
+/// Maximum numeric suffix tried before giving up on finding a free name
+pub const MAX_SUFFIX: usize = 1000;
+
+/// Characters considered as path separators in a new file stem, whatever the
+/// current platform, so the same input behaves the same everywhere
+pub const SEPARATORS: [char; 2] = ['/', '\\'];
+
 impl Replacement {
+    /// Check if the new file stem introduces path separators that were not
+    /// part of the original file stem
+    pub fn has_new_separators(&self) -> bool {
+        SEPARATORS.iter().any(|sep| {
+            self.new_file_stem.contains(*sep) && !self.file_stem.contains(*sep)
+        })
+    }
+
+    /// Replace all path separators in the new file stem with a dash
+    pub fn normalize_separators(&mut self) {
+        self.new_file_stem = self.new_file_stem.replace(SEPARATORS, "-");
+    }
+
+    /// Replace the characters of the new file stem according to the given
+    /// Sanitize rules
+    pub fn sanitize(&mut self, sanitize: &Sanitize) {
+        self.new_file_stem = sanitize.apply(&self.new_file_stem);
+    }
+
+    /// First character of the new file name that is invalid according to
+    /// the given predicate
+    fn illegal_character<F>(&self, is_illegal: F) -> Option<char>
+    where
+        F: Fn(char) -> bool,
+    {
+        self.new_file_name().chars().find(|c| is_illegal(*c))
+    }
+
+    /// Render the date again in the new file stem, using the given format
+    ///
+    /// Return false, leaving the replacement untouched, if the format is
+    /// invalid or if the new file stem doesn't start or end with the date as
+    /// currently rendered
+    pub fn change_date_format(&mut self, format: &str) -> bool {
+        use std::fmt::Write;
+
+        let (Some(date_time), Some(current)) =
+            (self.date_time, &self.date_format)
+        else {
+            return false;
+        };
+        let mut date = String::new();
+        if write!(date, "{}", date_time.format(format)).is_err() {
+            return false;
+        }
+        let current = date_time.format(current).to_string();
+
+        let stem = &self.new_file_stem;
+        self.new_file_stem = if let Some(rest) = stem.strip_prefix(&current) {
+            format!("{}{}", date, rest)
+        } else if let Some(rest) = stem.strip_suffix(&current) {
+            format!("{}{}", rest, date)
+        } else {
+            return false;
+        };
+        self.date_format = Some(format.to_string());
+
+        true
+    }
+
     pub fn execute(&self) -> Result<()> {
-        std::fs::rename(self.path(), self.new_path())?;
+        self.execute_with(&RealFs, &RenameRetries::default())
+    }
+
+    /// Execute the replacement using the given filesystem
+    ///
+    /// An existing target is never overwritten, unless it is actually the
+    /// same file as the source (e.g. on case-insensitive filesystems)
+    ///
+    /// The source existence is checked again right before renaming, returning
+    /// Error::SourceVanished if it is gone
+    ///
+    /// When the rename fails because it crosses filesystems, the file is
+    /// copied then removed instead, and when it fails for a transient reason
+    /// it is retried according to the given RenameRetries
+    pub fn execute_with(
+        &self,
+        fs: &dyn Fs,
+        retries: &RenameRetries,
+    ) -> Result<()> {
+        let path = self.path();
+        let new_path = self.new_path();
+
+        // The path may have been removed since it was matched and confirmed
+        if !fs.try_exists(&path).map_err(|e| Error::io(e, &path))? {
+            return Err(Error::SourceVanished(path));
+        }
+
+        if path == new_path {
+            return Ok(());
+        }
+
+        if let Some(new_parent) = &self.new_parent {
+            fs.create_dir_all(new_parent)
+                .map_err(|e| Error::io(e, new_parent))?;
+        }
+        self.check_target(fs)?;
+
+        match retries.rename(fs, &path, &new_path) {
+            Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+                copy_and_remove(fs, &path, &new_path)
+                    .map_err(|e| Error::io(e, &path))?;
+            }
+            result => result.map_err(|e| Error::io(e, &path))?,
+        }
+
+        Ok(())
+    }
+
+    /// Set the time of the new path selected by touch to the date-time of the
+    /// replacement, if known, so the name and the time of the file agree
+    pub fn touch_with(&self, fs: &dyn Fs, touch: Touch) -> Result<()> {
+        let Some(date_time) = self.date_time else {
+            return Ok(());
+        };
+        let time = SystemTime::from(date_time);
+
+        let times = match touch {
+            Touch::None => return Ok(()),
+            Touch::Created => created_time(time)?,
+            Touch::Modified => FileTimes::new().set_modified(time),
+        };
+
+        Ok(fs.set_times(&self.new_path(), times)?)
+    }
+
+    /// Check that the new file name is valid on the current platform and that
+    /// the target doesn't already exist, unless it is the source itself,
+    /// without renaming anything
+    pub fn check_target(&self, fs: &dyn Fs) -> Result<()> {
+        let path = self.path();
+        let new_path = self.new_path();
+
+        if let Some(c) = self.illegal_character(is_illegal) {
+            return Err(Error::IllegalCharacter(self.new_file_name(), c));
+        }
+
+        if path != new_path
+            && fs.try_exists(&new_path)?
+            && !same_file(fs, &path, &new_path)?
+        {
+            return Err(Error::TargetExists(new_path));
+        }
 
         Ok(())
     }
 
+    /// Execute the replacement, appending a numeric suffix like ` (1)` to
+    /// the new file stem while the target already exists, or is one of the
+    /// reserved paths, e.g. already assigned to another file during the run
+    ///
+    /// The new file stem is updated to reflect the name finally used
+    pub fn execute_with_suffix(
+        &mut self,
+        fs: &dyn Fs,
+        reserved: &HashSet<PathBuf>,
+        retries: &RenameRetries,
+    ) -> Result<()> {
+        self.with_suffix(reserved, |replacement| {
+            replacement.execute_with(fs, retries)
+        })
+    }
+
+    /// Append a numeric suffix like execute_with_suffix, but without renaming
+    /// anything
+    pub fn resolve_suffix(
+        &mut self,
+        fs: &dyn Fs,
+        reserved: &HashSet<PathBuf>,
+    ) -> Result<()> {
+        self.with_suffix(reserved, |replacement| replacement.check_target(fs))
+    }
+
+    fn with_suffix<F>(
+        &mut self,
+        reserved: &HashSet<PathBuf>,
+        mut attempt: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&Self) -> Result<()>,
+    {
+        let new_file_stem = self.new_file_stem.clone();
+        let mut suffix = 0;
+
+        loop {
+            let new_path = self.new_path();
+            let result = match reserved.contains(&new_path) {
+                true => Err(Error::TargetExists(new_path)),
+                false => attempt(self),
+            };
+
+            match result {
+                Err(Error::TargetExists(_)) if suffix < MAX_SUFFIX => {
+                    suffix += 1;
+                    self.new_file_stem =
+                        format!("{} ({})", new_file_stem, suffix);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// File name as found on disk
     pub fn file_name(&self) -> String {
+        let file_stem = self.disk_file_stem.as_ref().unwrap_or(&self.file_stem);
+
         if self.extension.is_empty() {
-            self.file_stem.clone()
+            file_stem.clone()
         } else {
-            format!("{}.{}", self.file_stem, self.extension)
+            format!("{}.{}", file_stem, self.extension)
         }
     }
 
     pub fn new_file_name(&self) -> String {
-        if self.extension.is_empty() {
+        let extension = self.new_extension();
+
+        if extension.is_empty() {
             self.new_file_stem.clone()
         } else {
-            format!("{}.{}", self.new_file_stem, self.extension)
+            format!("{}.{}", self.new_file_stem, extension)
         }
     }
 
+    /// Same replacement, with the given new file stem
+    ///
+    /// ```
+    /// use prefix_by_date::replacement::Replacement;
+    /// use std::path::Path;
+    ///
+    /// let replacement = Replacement::try_from(Path::new("/tmp/foo.txt"))
+    ///     .unwrap()
+    ///     .new_file_stem(String::from("2024-01-20 foo"));
+    /// assert_eq!("2024-01-20 foo.txt", replacement.new_file_name());
+    /// ```
+    pub fn new_file_stem(mut self, new_file_stem: String) -> Self {
+        self.new_file_stem = new_file_stem;
+        self
+    }
+
+    /// Same replacement, with the new stem and extension split from the given
+    /// file name according to the given extensions rules
+    pub fn with_new_file_name(
+        mut self,
+        new_file_name: &str,
+        extensions: &Extensions,
+    ) -> Self {
+        let (file_stem, ext) = match split_extension(new_file_name) {
+            Some((stem, ext)) => {
+                extensions.split(stem.to_string(), ext.to_string())
+            }
+            None => (new_file_name.to_string(), String::new()),
+        };
+
+        self.new_file_stem = file_stem;
+        self.new_extension = (ext != self.extension).then_some(ext);
+        self
+    }
+
+    /// Extension of the new file name
+    pub fn new_extension(&self) -> &str {
+        self.new_extension.as_deref().unwrap_or(&self.extension)
+    }
+
+    /// Convert the extension of the new file name to lowercase
+    pub fn lowercase_extension(&mut self) {
+        let extension = self.new_extension().to_lowercase();
+
+        self.new_extension = (extension != self.extension).then_some(extension);
+    }
+
+    /// Directory the file ends up in
+    pub fn new_parent(&self) -> &Path {
+        self.new_parent.as_deref().unwrap_or(&self.parent)
+    }
+
     pub fn path(&self) -> PathBuf {
         self.parent.join(self.file_name())
     }
 
     pub fn new_path(&self) -> PathBuf {
-        self.parent.join(self.new_file_name())
+        self.new_parent().join(self.new_file_name())
+    }
+}
+
+/// Move a file across filesystems, which rename is unable to do, by copying it
+/// along with its times then removing the source
+///
+/// If anything fails or the move is interrupted, e.g. by an abort unwinding
+/// the stack, the copy is removed so the source is left as the only file
+fn copy_and_remove(
+    fs: &dyn Fs,
+    path: &Path,
+    new_path: &Path,
+) -> io::Result<()> {
+    let guard = CopyGuard { fs, path: new_path };
+
+    fs.copy(path, new_path)?;
+    fs.copy_times(path, new_path)?;
+    fs.remove_file(path)?;
+
+    // The move is complete, the copy is now the file
+    std::mem::forget(guard);
+    Ok(())
+}
+
+/// Remove the partial copy of a move across filesystems when dropped, unless
+/// forgotten once the move is complete
+struct CopyGuard<'a> {
+    fs: &'a dyn Fs,
+    path: &'a Path,
+}
+
+impl Drop for CopyGuard<'_> {
+    fn drop(&mut self) {
+        match self.fs.remove_file(self.path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                log::warn!("Unable to remove copy {:?}: {}", self.path, error);
+            }
+            _ => {}
+        }
     }
 }
 
+/// Check if both paths point to the same file on disk
+#[cfg(unix)]
+fn same_file(fs: &dyn Fs, path: &Path, other: &Path) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs.metadata(path)?;
+    let other = fs.metadata(other)?;
+
+    Ok(metadata.dev() == other.dev() && metadata.ino() == other.ino())
+}
+
+/// Check if both paths point to the same file on disk, by comparing their
+/// canonical form, which has the case of the names on disk
+#[cfg(not(unix))]
+fn same_file(_fs: &dyn Fs, path: &Path, other: &Path) -> Result<bool> {
+    Ok(path.canonicalize()? == other.canonicalize()?)
+}
+
 impl fmt::Display for Replacement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.extension.is_empty() {
+        if self.new_parent() != self.parent {
+            write!(
+                f,
+                "{} => {}",
+                self.path().to_str().unwrap(),
+                self.new_path().to_str().unwrap()
+            )
+        } else if self.new_extension() != self.extension {
+            write!(
+                f,
+                "{}/{{{} => {}}}",
+                self.parent.to_str().unwrap(),
+                self.file_name(),
+                self.new_file_name()
+            )
+        } else if self.extension.is_empty() {
             write!(
                 f,
                 "{}/{{{} => {}}}",
@@ -99,10 +719,33 @@ impl fmt::Display for Replacement {
     }
 }
 
+#[cfg(target_os = "windows")]
+fn created_time(time: SystemTime) -> io::Result<FileTimes> {
+    use std::os::windows::fs::FileTimesExt;
+
+    Ok(FileTimes::new().set_created(time))
+}
+
+#[cfg(target_os = "macos")]
+fn created_time(time: SystemTime) -> io::Result<FileTimes> {
+    use std::os::macos::fs::FileTimesExt;
+
+    Ok(FileTimes::new().set_created(time))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn created_time(_time: SystemTime) -> io::Result<FileTimes> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the creation time can't be set on this platform",
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test::{assert_eq, test};
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+    use predicates::prelude::*;
 
     fn path() -> PathBuf {
         PathBuf::from("/this/is/a/test.pdf")
@@ -117,6 +760,185 @@ mod tests {
         assert_eq!(path(), replacement.new_path());
     }
 
+    #[test]
+    fn change_date_format() {
+        let date_time =
+            DateTime::parse_from_rfc3339("2024-01-20T10:30:00+01:00").unwrap();
+        let replacement = Replacement {
+            new_file_stem: String::from("2024-01-20 test"),
+            date_time: Some(date_time),
+            date_format: Some(String::from("%Y-%m-%d")),
+            ..Replacement::default()
+        };
+
+        let mut changed = replacement.clone();
+        assert!(changed.change_date_format("%Y%m%d_%H%M"));
+        assert_eq!("20240120_1030 test", changed.new_file_stem);
+        assert_eq!(Some(String::from("%Y%m%d_%H%M")), changed.date_format);
+
+        let mut suffixed = Replacement {
+            new_file_stem: String::from("test 2024-01-20"),
+            ..replacement.clone()
+        };
+        assert!(suffixed.change_date_format("%d.%m.%Y"));
+        assert_eq!("test 20.01.2024", suffixed.new_file_stem);
+
+        // Invalid format
+        let mut invalid = replacement.clone();
+        assert!(!invalid.change_date_format("%Q"));
+        assert_eq!(replacement, invalid);
+
+        // Date edited away
+        let mut edited = Replacement {
+            new_file_stem: String::from("test"),
+            ..replacement.clone()
+        };
+        assert!(!edited.change_date_format("%Y"));
+        assert_eq!("test", edited.new_file_stem);
+
+        // No date determined
+        let mut undated = Replacement::default();
+        assert!(!undated.change_date_format("%Y"));
+    }
+
+    #[test]
+    fn normalization() {
+        let decomposed = "cafe\u{301}";
+        let composed = "caf\u{e9}";
+
+        assert_eq!(composed, Normalization::Nfc.apply(decomposed));
+        assert_eq!(decomposed, Normalization::Nfd.apply(composed));
+        assert_eq!(decomposed, Normalization::None.apply(decomposed));
+        assert_eq!(Ok(Normalization::Nfd), "nfd".parse());
+        assert!("nfkc".parse::<Normalization>().is_err());
+    }
+
+    #[test]
+    fn try_from_decomposed() {
+        with_temp_dir(|temp| {
+            let file = temp.existing_child("cafe\u{301}.txt").unwrap();
+
+            let mut replacement = Replacement::try_from(file.path()).unwrap();
+            assert_eq!("caf\u{e9}", replacement.file_stem);
+            assert_eq!(Some("cafe\u{301}".into()), replacement.disk_file_stem);
+            assert_eq!("cafe\u{301}.txt", replacement.file_name());
+
+            // The file is renamed by its name on disk
+            replacement.new_file_stem =
+                format!("2024-01-20 {}", replacement.file_stem);
+            replacement.execute().unwrap();
+            file.assert(predicate::path::missing());
+            temp.child("2024-01-20 caf\u{e9}.txt")
+                .assert(predicate::path::exists());
+        });
+    }
+
+    #[test]
+    fn resolve_parent() {
+        with_temp_dir(|temp| {
+            let dir = temp.child("dir");
+            dir.create_dir_all().unwrap();
+            let link = temp.child("link");
+            link.symlink_to_dir(dir.path()).unwrap();
+
+            assert_eq!(
+                dir.path().canonicalize().unwrap(),
+                super::resolve_parent(link.path(), true)
+            );
+            assert_eq!(
+                link.to_path_buf(),
+                super::resolve_parent(link.path(), false)
+            );
+
+            // Paths that cannot be resolved are kept as given
+            let missing = temp.child("missing");
+            assert_eq!(
+                missing.to_path_buf(),
+                super::resolve_parent(missing.path(), true)
+            );
+        });
+    }
+
+    #[test]
+    fn try_from_with_keep_in_stem() {
+        let settings = Settings {
+            extensions: Extensions {
+                keep_in_stem: vec![String::from("min")],
+                ..Extensions::new()
+            },
+            ..Settings::default()
+        };
+
+        let path = PathBuf::from("/this/is/a/app.min.js");
+        let replacement =
+            Replacement::try_from_with(path.as_path(), &settings).unwrap();
+        assert_eq!(String::from("app.min"), replacement.file_stem);
+        assert_eq!(String::from("js"), replacement.extension);
+        assert_eq!(path, replacement.new_path());
+
+        let path = PathBuf::from("/this/is/a/app.min");
+        let replacement =
+            Replacement::try_from_with(path.as_path(), &settings).unwrap();
+        assert_eq!(String::from("app.min"), replacement.file_stem);
+        assert_eq!(String::new(), replacement.extension);
+        assert_eq!(path, replacement.new_path());
+
+        let replacement =
+            Replacement::try_from_with(path.as_path(), &Settings::default())
+                .unwrap();
+        assert_eq!(String::from("app"), replacement.file_stem);
+        assert_eq!(String::from("min"), replacement.extension);
+    }
+
+    #[test]
+    fn try_from_with_compound() {
+        let settings = Settings {
+            extensions: Extensions {
+                compound: vec![String::from("tar.gz"), String::from("tar.bz2")],
+                ..Extensions::new()
+            },
+            ..Settings::default()
+        };
+        let split = |file_name: &str| {
+            let path = PathBuf::from("/this/is/a").join(file_name);
+            let replacement =
+                Replacement::try_from_with(path.as_path(), &settings).unwrap();
+            assert_eq!(path, replacement.new_path());
+            (replacement.file_stem, replacement.extension)
+        };
+
+        assert_eq!(
+            (String::from("foo"), String::from("tar.gz")),
+            split("foo.tar.gz")
+        );
+        assert_eq!(
+            (String::from("foo.bar"), String::from("TAR.BZ2")),
+            split("foo.bar.TAR.BZ2")
+        );
+        assert_eq!(
+            (String::from("foo.zip"), String::from("gz")),
+            split("foo.zip.gz")
+        );
+        assert_eq!(
+            (String::from("foo"), String::from("pdf")),
+            split("foo.pdf")
+        );
+        assert_eq!((String::from("foo"), String::new()), split("foo"));
+        assert_eq!(
+            (String::from(".tar"), String::from("gz")),
+            split(".tar.gz")
+        );
+
+        let mut replacement = Replacement::try_from_with(
+            PathBuf::from("/this/is/a/foo.tar.gz").as_path(),
+            &settings,
+        )
+        .unwrap();
+        replacement.new_file_stem =
+            format!("2024-01-20 {}", replacement.file_stem);
+        assert_eq!("2024-01-20 foo.tar.gz", replacement.new_file_name());
+    }
+
     #[test]
     fn try_from_without_extension() {
         let path = PathBuf::from("/this/is/a/test");
@@ -127,6 +949,36 @@ mod tests {
         assert_eq!(path, replacement.new_path());
     }
 
+    #[test]
+    fn try_from_hidden() {
+        let split = |name: &str| {
+            let replacement =
+                Replacement::try_from(Path::new("/tmp").join(name).as_path())
+                    .unwrap();
+            (replacement.file_stem.clone(), replacement.extension.clone())
+        };
+
+        assert_eq!((".bashrc".into(), "".into()), split(".bashrc"));
+        assert_eq!((".config".into(), "toml".into()), split(".config.toml"));
+        assert_eq!(("file".into(), "txt".into()), split("file.txt"));
+
+        let replacement = Replacement::try_from(Path::new("/tmp/foo.txt"))
+            .unwrap()
+            .with_new_file_name(".bashrc", &Extensions::new());
+        assert_eq!(".bashrc", replacement.new_file_stem);
+        assert_eq!("", replacement.new_extension());
+    }
+
+    #[test]
+    fn new_file_stem_fn() {
+        let replacement = Replacement::try_from(path().as_path())
+            .unwrap()
+            .new_file_stem("success".to_string());
+
+        assert_eq!("success", replacement.new_file_stem);
+        assert_eq!("success.pdf", replacement.new_file_name());
+    }
+
     #[test]
     fn customized_file_stem() {
         let mut replacement = Replacement::try_from(path().as_path()).unwrap();
@@ -139,6 +991,554 @@ mod tests {
         );
     }
 
+    #[test]
+    fn with_new_file_name() {
+        let path = PathBuf::from("/this/is/a/foo.txt");
+        let replacement = Replacement::try_from(path.as_path()).unwrap();
+
+        let changed = replacement
+            .clone()
+            .with_new_file_name("foo.md", &Extensions::new());
+        assert_eq!("foo", changed.new_file_stem);
+        assert_eq!("md", changed.new_extension());
+        assert_eq!(PathBuf::from("/this/is/a/foo.md"), changed.new_path());
+
+        let same = replacement
+            .clone()
+            .with_new_file_name("bar.txt", &Extensions::new());
+        assert_eq!("bar", same.new_file_stem);
+        assert!(same.new_extension.is_none());
+
+        let removed = replacement.with_new_file_name("foo", &Extensions::new());
+        assert_eq!("foo", removed.new_file_stem);
+        assert_eq!("", removed.new_extension());
+        assert_eq!(PathBuf::from("/this/is/a/foo"), removed.new_path());
+    }
+
+    #[test]
+    fn has_new_separators() {
+        let mut replacement = Replacement::try_from(path().as_path()).unwrap();
+        assert!(!replacement.has_new_separators());
+
+        replacement.new_file_stem = String::from("sub\\name");
+        assert!(replacement.has_new_separators());
+
+        replacement.new_file_stem = String::from("sub/name");
+        assert!(replacement.has_new_separators());
+
+        // Separators that were already there are not reported
+        replacement.file_stem = String::from("foo\\bar");
+        replacement.new_file_stem = String::from("2024-01-20 foo\\bar");
+        assert!(!replacement.has_new_separators());
+    }
+
+    #[test]
+    fn normalize_separators() {
+        let mut replacement = Replacement::try_from(path().as_path()).unwrap();
+        replacement.new_file_stem = String::from("sub\\name/foo");
+        replacement.normalize_separators();
+
+        assert_eq!("sub-name-foo", replacement.new_file_stem);
+        assert_eq!(
+            PathBuf::from("/this/is/a/sub-name-foo.pdf"),
+            replacement.new_path()
+        );
+    }
+
+    #[test]
+    fn sanitize() {
+        let sanitize = Sanitize {
+            replacements: vec![(':', String::from("h")), ('?', String::new())],
+        };
+
+        assert_eq!("10h30 what", sanitize.apply("10:30 what?"));
+        assert_eq!("foo", sanitize.apply("foo"));
+        assert_eq!("", Sanitize::default().apply(""));
+    }
+
+    #[test]
+    fn illegal_character() {
+        let mut replacement = Replacement::try_from(path().as_path()).unwrap();
+        replacement.new_file_stem = String::from("10:30 what?");
+
+        assert_eq!(
+            Some(':'),
+            replacement.illegal_character(is_illegal_on_windows)
+        );
+        assert_eq!(
+            Some(':'),
+            replacement.illegal_character(is_illegal_on_macos)
+        );
+        assert_eq!(None, replacement.illegal_character(is_illegal_on_unix));
+
+        replacement.new_file_stem = String::from("back\\slash");
+        assert_eq!(
+            Some('\\'),
+            replacement.illegal_character(is_illegal_on_windows)
+        );
+        assert_eq!(None, replacement.illegal_character(is_illegal_on_macos));
+
+        replacement.new_file_stem = String::from("nul\0");
+        assert_eq!(
+            Some('\0'),
+            replacement.illegal_character(is_illegal_on_windows)
+        );
+        assert_eq!(
+            Some('\0'),
+            replacement.illegal_character(is_illegal_on_unix)
+        );
+
+        // The extension is part of the new file name
+        replacement.new_file_stem = String::from("foo");
+        replacement.new_extension = Some(String::from("p*f"));
+        assert_eq!(
+            Some('*'),
+            replacement.illegal_character(is_illegal_on_windows)
+        );
+    }
+
+    #[test]
+    fn execute_with_illegal_character() {
+        use crate::fs::MockFs;
+
+        let mut replacement = Replacement::try_from(path().as_path()).unwrap();
+        replacement.new_file_stem = String::from("foo\0bar");
+
+        let mut fs = MockFs::new();
+        fs.expect_try_exists().returning(|p| Ok(p == path()));
+        fs.expect_rename().never();
+
+        assert!(matches!(
+            replacement.execute_with(&fs, &RenameRetries::default()),
+            Err(Error::IllegalCharacter(_, '\0'))
+        ));
+        assert!(matches!(
+            replacement.check_target(&fs),
+            Err(Error::IllegalCharacter(_, '\0'))
+        ));
+    }
+
+    #[test]
+    fn execute_with_crossing_devices() {
+        use crate::fs::MockFs;
+        use mockall::predicate::eq;
+        use mockall::Sequence;
+
+        let new_path = || PathBuf::from("/this/is/a/foo.pdf");
+        let mut replacement = Replacement::try_from(path().as_path()).unwrap();
+        replacement.new_file_stem = String::from("foo");
+        let expected = replacement.clone();
+
+        let crossing_fs = || {
+            let mut fs = MockFs::new();
+            fs.expect_try_exists().returning(|p| Ok(p == path()));
+            fs.expect_rename()
+                .times(1)
+                .returning(|_, _| Err(io::ErrorKind::CrossesDevices.into()));
+            fs
+        };
+
+        // Copy, then remove the source
+        let mut fs = crossing_fs();
+        let mut seq = Sequence::new();
+        fs.expect_copy()
+            .with(eq(path()), eq(new_path()))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(42));
+        fs.expect_copy_times()
+            .with(eq(path()), eq(new_path()))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        fs.expect_remove_file()
+            .with(eq(path()))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+        assert!(replacement
+            .execute_with(&fs, &RenameRetries::default())
+            .is_ok());
+        assert_eq!(expected, replacement);
+
+        // A failed copy is cleaned up
+        let mut fs = crossing_fs();
+        fs.expect_copy()
+            .times(1)
+            .returning(|_, _| Err(io::ErrorKind::StorageFull.into()));
+        fs.expect_copy_times().never();
+        fs.expect_remove_file()
+            .with(eq(new_path()))
+            .times(1)
+            .returning(|_| Ok(()));
+        assert!(matches!(
+            replacement.execute_with(&fs, &RenameRetries::default()),
+            Err(Error::Io(error))
+                if error.kind() == io::ErrorKind::StorageFull
+        ));
+
+        // So is the copy if the source cannot be removed
+        let mut fs = crossing_fs();
+        fs.expect_copy().times(1).returning(|_, _| Ok(42));
+        fs.expect_copy_times().times(1).returning(|_, _| Ok(()));
+        fs.expect_remove_file()
+            .with(eq(path()))
+            .times(1)
+            .returning(|_| Err(io::ErrorKind::PermissionDenied.into()));
+        fs.expect_remove_file()
+            .with(eq(new_path()))
+            .times(1)
+            .returning(|_| Ok(()));
+        assert!(matches!(
+            replacement.execute_with(&fs, &RenameRetries::default()),
+            Err(Error::PermissionDenied(p)) if p == path()
+        ));
+    }
+
+    #[test]
+    fn execute_with_crossing_devices_interrupted() {
+        use std::fs::Metadata;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        /// Real filesystem unable to rename, aborted once the copy is made
+        struct AbortingFs;
+
+        impl Fs for AbortingFs {
+            fn rename(&self, _: &Path, _: &Path) -> io::Result<()> {
+                Err(io::ErrorKind::CrossesDevices.into())
+            }
+            fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+                RealFs.copy(from, to)
+            }
+            fn copy_times(&self, _: &Path, _: &Path) -> io::Result<()> {
+                panic!("Abort received")
+            }
+            fn set_times(
+                &self,
+                path: &Path,
+                times: FileTimes,
+            ) -> io::Result<()> {
+                RealFs.set_times(path, times)
+            }
+            fn remove_file(&self, path: &Path) -> io::Result<()> {
+                RealFs.remove_file(path)
+            }
+            fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+                RealFs.create_dir_all(path)
+            }
+            fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+                RealFs.metadata(path)
+            }
+            fn try_exists(&self, path: &Path) -> io::Result<bool> {
+                RealFs.try_exists(path)
+            }
+            fn is_special(&self, path: &Path) -> io::Result<bool> {
+                RealFs.is_special(path)
+            }
+        }
+
+        with_temp_dir(|temp| {
+            let foo = temp.child("foo.txt");
+            foo.write_str("content").unwrap();
+            let bar = temp.child("bar.txt");
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                replacement.execute_with(&AbortingFs, &RenameRetries::default())
+            }));
+            assert!(result.is_err());
+
+            foo.assert("content");
+            bar.assert(predicate::path::missing());
+        });
+    }
+
+    #[test]
+    fn touch_with() {
+        use chrono::TimeZone;
+
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo").unwrap();
+            let modified =
+                || foo.path().metadata().unwrap().modified().unwrap();
+            let before = modified();
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            let date_time = FixedOffset::east_opt(3600)
+                .unwrap()
+                .with_ymd_and_hms(2024, 1, 20, 10, 0, 0)
+                .unwrap();
+
+            // Nothing to set without a date-time
+            replacement.touch_with(&RealFs, Touch::Modified).unwrap();
+            assert_eq!(before, modified());
+
+            replacement.date_time = Some(date_time);
+            replacement.touch_with(&RealFs, Touch::None).unwrap();
+            assert_eq!(before, modified());
+
+            replacement.touch_with(&RealFs, Touch::Modified).unwrap();
+            assert_eq!(SystemTime::from(date_time), modified());
+        });
+    }
+
+    #[test]
+    fn execute_with() {
+        use crate::fs::MockFs;
+        use mockall::predicate::eq;
+
+        let mut replacement = Replacement::try_from(path().as_path()).unwrap();
+        replacement.new_file_stem = String::from("foo");
+
+        let mut fs = MockFs::new();
+        fs.expect_try_exists()
+            .with(eq(path()))
+            .times(1)
+            .returning(|_| Ok(true));
+        fs.expect_try_exists()
+            .with(eq(PathBuf::from("/this/is/a/foo.pdf")))
+            .times(1)
+            .returning(|_| Ok(false));
+        fs.expect_rename()
+            .with(eq(path()), eq(PathBuf::from("/this/is/a/foo.pdf")))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        assert!(replacement
+            .execute_with(&fs, &RenameRetries::default())
+            .is_ok());
+
+        let mut fs = MockFs::new();
+        fs.expect_try_exists()
+            .times(2)
+            .returning(|p| Ok(p == path()));
+        fs.expect_rename().times(1).returning(|_, _| {
+            Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+        });
+        assert!(matches!(
+            replacement.execute_with(&fs, &RenameRetries::default()),
+            Err(Error::PermissionDenied(p)) if p == path()
+        ));
+    }
+
+    #[test]
+    fn execute_with_transient_failure() {
+        use crate::fs::MockFs;
+        use mockall::Sequence;
+
+        let mut replacement = Replacement::try_from(path().as_path()).unwrap();
+        replacement.new_file_stem = String::from("foo");
+
+        // Busy once, then renamed
+        let mut fs = MockFs::new();
+        let mut seq = Sequence::new();
+        fs.expect_try_exists().returning(|p| Ok(p == path()));
+        fs.expect_rename()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Err(io::ErrorKind::ResourceBusy.into()));
+        fs.expect_rename()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        assert!(replacement
+            .execute_with(&fs, &RenameRetries::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn rename_retries() {
+        use crate::fs::MockFs;
+
+        let retries = RenameRetries {
+            count: 2,
+            delay: Duration::ZERO,
+        };
+        let (from, to) = (path(), PathBuf::from("/this/is/a/foo.pdf"));
+
+        // Transient errors are retried until giving up
+        let mut fs = MockFs::new();
+        fs.expect_rename()
+            .times(3)
+            .returning(|_, _| Err(io::ErrorKind::WouldBlock.into()));
+        assert!(matches!(
+            retries.rename(&fs, &from, &to),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock
+        ));
+
+        // Others fail immediately
+        let mut fs = MockFs::new();
+        fs.expect_rename()
+            .times(1)
+            .returning(|_, _| Err(io::ErrorKind::PermissionDenied.into()));
+        assert!(retries.rename(&fs, &from, &to).is_err());
+    }
+
+    #[test]
+    fn execute_into_new_parent() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo.txt").unwrap();
+            let out = temp.child("out/nested");
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+            replacement.new_parent = Some(out.to_path_buf());
+            replacement.execute().unwrap();
+
+            foo.assert(predicate::path::missing());
+            out.child("bar.txt").assert(predicate::path::exists());
+        });
+    }
+
+    #[test]
+    fn execute_target_exists() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo.txt").unwrap();
+            let bar = temp.existing_child("bar.txt").unwrap();
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+
+            assert!(matches!(
+                replacement.execute(),
+                Err(Error::TargetExists(path)) if path == bar.path()
+            ));
+            foo.assert(predicate::path::exists());
+            bar.assert(predicate::path::exists());
+        });
+    }
+
+    #[test]
+    fn execute_with_suffix() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo.txt").unwrap();
+            temp.existing_child("bar.txt").unwrap();
+            temp.existing_child("bar (1).txt").unwrap();
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+
+            assert!(replacement
+                .execute_with_suffix(
+                    &RealFs,
+                    &HashSet::new(),
+                    &RenameRetries::default()
+                )
+                .is_ok());
+            assert_eq!("bar (2)", replacement.new_file_stem);
+            foo.assert(predicate::path::missing());
+            temp.child("bar (2).txt").assert(predicate::path::exists());
+        });
+    }
+
+    #[test]
+    fn execute_with_suffix_reserved() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo.txt").unwrap();
+            temp.existing_child("bar.txt").unwrap();
+            let reserved = HashSet::from([
+                temp.child("bar (1).txt").to_path_buf(),
+                temp.child("bar (2).txt").to_path_buf(),
+            ]);
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+
+            assert!(replacement.resolve_suffix(&RealFs, &reserved).is_ok());
+            assert_eq!("bar (3)", replacement.new_file_stem);
+            foo.assert(predicate::path::exists());
+
+            replacement.new_file_stem = String::from("bar");
+            assert!(replacement
+                .execute_with_suffix(
+                    &RealFs,
+                    &reserved,
+                    &RenameRetries::default()
+                )
+                .is_ok());
+            assert_eq!("bar (3)", replacement.new_file_stem);
+            foo.assert(predicate::path::missing());
+            temp.child("bar (3).txt").assert(predicate::path::exists());
+        });
+    }
+
+    #[test]
+    fn execute_with_suffix_gives_up() {
+        use crate::fs::MockFs;
+
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo").unwrap();
+            let bar = temp.existing_child("bar").unwrap();
+            let foo_metadata = foo.metadata().unwrap();
+            let bar_metadata = bar.metadata().unwrap();
+            let source = foo.to_path_buf();
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+
+            let mut fs = MockFs::new();
+            fs.expect_try_exists()
+                .times(2 * (MAX_SUFFIX + 1))
+                .returning(|_| Ok(true));
+            fs.expect_metadata().returning(move |path| {
+                if path == source {
+                    Ok(foo_metadata.clone())
+                } else {
+                    Ok(bar_metadata.clone())
+                }
+            });
+            fs.expect_rename().never();
+
+            assert!(matches!(
+                replacement.execute_with_suffix(
+                    &fs,
+                    &HashSet::new(),
+                    &RenameRetries::default()
+                ),
+                Err(Error::TargetExists(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn execute_source_vanished() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo.txt").unwrap();
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+            std::fs::remove_file(foo.path()).unwrap();
+
+            assert!(matches!(
+                replacement.execute(),
+                Err(Error::SourceVanished(path)) if path == foo.path()
+            ));
+            temp.child("bar.txt").assert(predicate::path::missing());
+        });
+    }
+
+    #[test]
+    fn execute_same_file() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo.txt").unwrap();
+
+            // Identical source and target
+            let replacement = Replacement::try_from(foo.path()).unwrap();
+            assert!(replacement.execute().is_ok());
+            foo.assert(predicate::path::exists());
+
+            // Target is a hard link to the source
+            let bar = temp.child("bar.txt");
+            std::fs::hard_link(foo.path(), bar.path()).unwrap();
+
+            let mut replacement = Replacement::try_from(foo.path()).unwrap();
+            replacement.new_file_stem = String::from("bar");
+            assert!(replacement.execute().is_ok());
+            bar.assert(predicate::path::exists());
+        });
+    }
+
     #[test]
     fn display() {
         let path = PathBuf::from("/this/is/a/test.pdf");
@@ -148,6 +1548,21 @@ mod tests {
         assert_eq!("/this/is/a/{test => foo}.pdf", format!("{}", replacement));
     }
 
+    #[test]
+    fn display_new_parent() {
+        let path = PathBuf::from("/this/is/a/test.pdf");
+        let mut replacement = Replacement::try_from(path.as_path()).unwrap();
+        replacement.new_file_stem = String::from("foo");
+        replacement.new_parent = Some(PathBuf::from("/out"));
+
+        assert_eq!(Path::new("/out"), replacement.new_parent());
+        assert_eq!(PathBuf::from("/out/foo.pdf"), replacement.new_path());
+        assert_eq!(
+            "/this/is/a/test.pdf => /out/foo.pdf",
+            format!("{}", replacement)
+        );
+    }
+
     #[test]
     fn display_without_extension() {
         let path = PathBuf::from("/this/is/a/test");
@@ -156,4 +1571,26 @@ mod tests {
 
         assert_eq!("/this/is/a/{test => foo}", format!("{}", replacement));
     }
+
+    #[test]
+    fn lowercase_extension() {
+        let path = PathBuf::from("/this/is/a/IMG_0001.JPG");
+        let mut replacement = Replacement::try_from(path.as_path()).unwrap();
+        replacement.new_file_stem = String::from("2024-01-20 IMG_0001");
+        replacement.lowercase_extension();
+
+        assert_eq!("JPG", replacement.extension);
+        assert_eq!("jpg", replacement.new_extension());
+        assert_eq!(path, replacement.path());
+        assert_eq!("2024-01-20 IMG_0001.jpg", replacement.new_file_name());
+        assert_eq!(
+            "/this/is/a/{IMG_0001.JPG => 2024-01-20 IMG_0001.jpg}",
+            format!("{}", replacement)
+        );
+
+        let path = PathBuf::from("/this/is/a/test.pdf");
+        let mut replacement = Replacement::try_from(path.as_path()).unwrap();
+        replacement.lowercase_extension();
+        assert!(replacement.new_extension.is_none());
+    }
 }
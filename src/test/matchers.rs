@@ -1,6 +1,10 @@
 use std::boxed::Box;
+use std::path::Path;
 
 use crate::matcher::{Matcher, Pattern, PredeterminedDate};
+use crate::replacement::Replacement;
+
+use chrono::{DateTime, Local};
 
 pub fn today() -> PredeterminedDate {
     PredeterminedDate::default()
@@ -33,3 +37,74 @@ pub fn weird() -> Pattern {
 pub fn weird_boxed() -> Box<dyn Matcher> {
     Box::new(weird())
 }
+
+/// Matcher panicking on every path it checks
+#[derive(Clone)]
+pub struct Panicking;
+
+impl Matcher for Panicking {
+    fn determine(
+        &self,
+        _replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        panic!("Panicking matcher")
+    }
+
+    fn name(&self) -> &str {
+        "panicking"
+    }
+
+    fn delimiter(&self) -> &str {
+        " "
+    }
+
+    fn date_format(&self) -> &str {
+        "%Y"
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+}
+
+pub fn panicking_boxed() -> Box<dyn Matcher> {
+    Box::new(Panicking)
+}
+
+/// Matcher never able to read the data it needs, like the creation time on a
+/// filesystem without support for it
+#[derive(Clone)]
+pub struct Unavailable;
+
+impl Matcher for Unavailable {
+    fn determine(
+        &self,
+        _replacement: &Replacement,
+    ) -> Option<(String, DateTime<Local>)> {
+        None
+    }
+
+    fn name(&self) -> &str {
+        "unavailable"
+    }
+
+    fn delimiter(&self) -> &str {
+        " "
+    }
+
+    fn date_format(&self) -> &str {
+        "%Y"
+    }
+
+    fn auto_accept(&self) -> bool {
+        false
+    }
+
+    fn unavailable(&self, _path: &Path) -> Option<String> {
+        Some(String::from("creation time unsupported"))
+    }
+}
+
+pub fn unavailable_boxed() -> Box<dyn Matcher> {
+    Box::new(Unavailable)
+}
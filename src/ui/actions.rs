@@ -1,4 +1,4 @@
-use crate::processing::Confirmation;
+use crate::processing::{Confirmation, Transform};
 use crate::replacement::Replacement;
 
 use crate::ui::state::Current;
@@ -10,6 +10,9 @@ pub enum Action {
     /// Accept the current replacement and all successive replacements
     /// proposed by the same matcher
     Always,
+    /// Accept the current replacement and all successive replacements,
+    /// whatever the matcher proposing them
+    AlwaysAll,
     /// Skip processing this path and try the next one
     Skip,
     /// Refuse the current replacement, potentially allowing another one to be
@@ -32,6 +35,17 @@ pub enum Action {
     ViewAlternatives,
     /// Cancel the current customization
     Cancel,
+    /// Skip this path and all the following ones up to the next one expected
+    /// to need a rescue
+    SkipToRescue,
+    /// Apply the given transform to the current change only, as a
+    /// customization
+    Transform(Transform),
+    /// Revert the rename performed for the previous path
+    Undo,
+    /// Render the date of the current change again with another format, as
+    /// a customization
+    ChangeFormat,
 }
 
 impl PartialEq for Action {
@@ -45,11 +59,13 @@ impl From<&Confirmation> for Action {
         match conf {
             Confirmation::Accept => Action::Accept,
             Confirmation::Always => Action::Always,
+            Confirmation::AlwaysAll => Action::AlwaysAll,
             Confirmation::Replace(rep) => Action::Replace(rep.clone()),
             Confirmation::Skip => Action::Skip,
             Confirmation::Refuse => Action::Refuse,
             Confirmation::Ignore => Action::Ignore,
             Confirmation::Abort => Action::Abort,
+            Confirmation::Undo => Action::Undo,
         }
     }
 }
@@ -61,6 +77,7 @@ impl TryInto<Confirmation> for Action {
         match self {
             Action::Accept => Ok(Confirmation::Accept),
             Action::Always => Ok(Confirmation::Always),
+            Action::AlwaysAll => Ok(Confirmation::AlwaysAll),
             Action::Replace(rep) => Ok(Confirmation::Replace(rep)),
             Action::Skip => Ok(Confirmation::Skip),
             Action::Refuse => Ok(Confirmation::Refuse),
@@ -70,6 +87,10 @@ impl TryInto<Confirmation> for Action {
             Action::ConfirmCustomization => Err(()),
             Action::ViewAlternatives => Err(()),
             Action::Cancel => Err(()),
+            Action::SkipToRescue => Err(()),
+            Action::Transform(_) => Err(()),
+            Action::Undo => Ok(Confirmation::Undo),
+            Action::ChangeFormat => Err(()),
         }
     }
 }
@@ -78,13 +99,14 @@ impl Action {
     pub fn determine_for(current: &Current) -> Vec<Self> {
         match current {
             Current::Confirm(change) => {
-                let mut actions = vec![Action::Accept, Action::Always];
+                let mut actions =
+                    vec![Action::Accept, Action::Always, Action::AlwaysAll];
                 if !change.alternatives.is_empty() {
                     actions.push(Action::ViewAlternatives);
                 }
                 if change.is_further_customizable() {
                     actions.push(Action::Customize(
-                        change.replacement.new_file_stem.clone(),
+                        change.editable_name(&change.replacement),
                     ));
                 }
                 actions.extend_from_slice(&vec![
@@ -94,14 +116,21 @@ impl Action {
                     Action::Ignore,
                     Action::Abort,
                 ]);
+                if change.replacement.date_format.is_some() {
+                    actions.push(Action::ChangeFormat);
+                }
+                actions.extend(Action::transforms());
 
                 actions
             }
             Current::Rescue(change) => {
                 let mut actions = vec![];
+                if !change.alternatives.is_empty() {
+                    actions.push(Action::ViewAlternatives);
+                }
                 if change.is_further_customizable() {
                     actions.push(Action::Customize(
-                        change.replacement.new_file_stem.clone(),
+                        change.editable_name(&change.replacement),
                     ));
                 }
                 actions.extend_from_slice(&vec![
@@ -110,6 +139,7 @@ impl Action {
                     Action::Refuse,
                     Action::Abort,
                 ]);
+                actions.extend(Action::transforms());
 
                 actions
             }
@@ -121,6 +151,7 @@ impl Action {
         vec![
             Action::Accept,
             Action::Always,
+            Action::AlwaysAll,
             Action::Customize(String::default()),
             Action::Replace(Replacement::default()),
             Action::Skip,
@@ -130,6 +161,20 @@ impl Action {
             Action::ConfirmCustomization,
             Action::ViewAlternatives,
             Action::Cancel,
+            Action::SkipToRescue,
+            Action::Transform(Transform::Lowercase),
+            Action::Undo,
+            Action::ChangeFormat,
+        ]
+    }
+
+    /// Transforms which can be applied to a single change
+    pub fn transforms() -> Vec<Self> {
+        vec![
+            Action::Transform(Transform::Lowercase),
+            Action::Transform(Transform::Uppercase),
+            Action::Transform(Transform::Slugify),
+            Action::Transform(Transform::Ascii),
         ]
     }
 }
@@ -139,6 +184,7 @@ pub fn shortcut_for(action: &Action) -> Option<char> {
     match action {
         Action::Accept => Some('Y'),
         Action::Always => Some('A'),
+        Action::AlwaysAll => Some('E'),
         Action::Customize(_) => Some('C'),
         Action::Skip => Some('S'),
         Action::Refuse => Some('R'),
@@ -148,6 +194,10 @@ pub fn shortcut_for(action: &Action) -> Option<char> {
         Action::ConfirmCustomization => None,
         Action::ViewAlternatives => Some('V'),
         Action::Cancel => None,
+        Action::SkipToRescue => Some('N'),
+        Action::Transform(_) => None,
+        Action::Undo => Some('U'),
+        Action::ChangeFormat => Some('F'),
     }
 }
 
@@ -183,12 +233,34 @@ mod tests {
 
         assert_eq!(actions[0], Action::Accept);
         assert_eq!(actions[1], Action::Always);
-        assert_eq!(actions[2], Action::Customize(String::default()));
-        assert_eq!(actions[3], Action::Replace(Replacement::default()));
-        assert_eq!(actions[4], Action::Skip);
-        assert_eq!(actions[5], Action::Refuse);
-        assert_eq!(actions[6], Action::Ignore);
-        assert_eq!(actions[7], Action::Abort);
+        assert_eq!(actions[2], Action::AlwaysAll);
+        assert_eq!(actions[3], Action::Customize(String::default()));
+        assert_eq!(actions[4], Action::Replace(Replacement::default()));
+        assert_eq!(actions[5], Action::Skip);
+        assert_eq!(actions[6], Action::Refuse);
+        assert_eq!(actions[7], Action::Ignore);
+        assert_eq!(actions[8], Action::Abort);
+        assert_eq!(actions[9..], Action::transforms());
+    }
+
+    #[test]
+    fn actions_from_current_confirm_with_date() {
+        use crate::ui::state::Change;
+
+        let change = Change::new(Replacement {
+            date_format: Some(String::from("%Y-%m-%d")),
+            ..Replacement::default()
+        });
+        let current = Current::Confirm(change.clone());
+        let actions = Action::determine_for(&current);
+
+        assert_eq!(actions[9], Action::ChangeFormat);
+        assert_eq!(actions[10..], Action::transforms());
+
+        let current = Current::Rescue(change);
+        assert!(
+            !Action::determine_for(&current).contains(&Action::ChangeFormat)
+        );
     }
 
     #[test]
@@ -207,11 +279,12 @@ mod tests {
 
         assert_eq!(actions[0], Action::Accept);
         assert_eq!(actions[1], Action::Always);
-        assert_eq!(actions[2], Action::Replace(Replacement::default()));
-        assert_eq!(actions[3], Action::Skip);
-        assert_eq!(actions[4], Action::Refuse);
-        assert_eq!(actions[5], Action::Ignore);
-        assert_eq!(actions[6], Action::Abort);
+        assert_eq!(actions[2], Action::AlwaysAll);
+        assert_eq!(actions[3], Action::Replace(Replacement::default()));
+        assert_eq!(actions[4], Action::Skip);
+        assert_eq!(actions[5], Action::Refuse);
+        assert_eq!(actions[6], Action::Ignore);
+        assert_eq!(actions[7], Action::Abort);
     }
 
     #[test]
@@ -234,13 +307,14 @@ mod tests {
 
         assert_eq!(actions[0], Action::Accept);
         assert_eq!(actions[1], Action::Always);
-        assert_eq!(actions[2], Action::ViewAlternatives);
-        assert_eq!(actions[3], Action::Customize(String::default()));
-        assert_eq!(actions[4], Action::Replace(Replacement::default()));
-        assert_eq!(actions[5], Action::Skip);
-        assert_eq!(actions[6], Action::Refuse);
-        assert_eq!(actions[7], Action::Ignore);
-        assert_eq!(actions[8], Action::Abort);
+        assert_eq!(actions[2], Action::AlwaysAll);
+        assert_eq!(actions[3], Action::ViewAlternatives);
+        assert_eq!(actions[4], Action::Customize(String::default()));
+        assert_eq!(actions[5], Action::Replace(Replacement::default()));
+        assert_eq!(actions[6], Action::Skip);
+        assert_eq!(actions[7], Action::Refuse);
+        assert_eq!(actions[8], Action::Ignore);
+        assert_eq!(actions[9], Action::Abort);
     }
 
     #[test]
@@ -278,6 +352,27 @@ mod tests {
         assert_eq!(actions[2], Action::Skip);
         assert_eq!(actions[3], Action::Refuse);
         assert_eq!(actions[4], Action::Abort);
+        assert_eq!(actions[5..], Action::transforms());
+    }
+
+    #[test]
+    fn actions_from_current_rescue_with_alternatives() {
+        use crate::ui::state::Change;
+        use std::collections::HashMap;
+
+        let change = Change {
+            alternatives: HashMap::from([(
+                String::from("modified (suggestion)"),
+                Replacement::default(),
+            )]),
+            customize: Some(String::from("foo")),
+            ..Change::default()
+        };
+        let current = Current::Rescue(change);
+        let actions = Action::determine_for(&current);
+
+        assert_eq!(actions[0], Action::ViewAlternatives);
+        assert!(actions.contains(&Action::Customize(String::default())));
     }
 
     #[test]
@@ -305,12 +400,13 @@ mod tests {
 
         assert_eq!(actions[0], Action::Accept);
         assert_eq!(actions[1], Action::Always);
-        assert_eq!(actions[2], Action::Customize(String::default()));
-        assert_eq!(actions[3], Action::Replace(Replacement::default()));
-        assert_eq!(actions[4], Action::Skip);
-        assert_eq!(actions[5], Action::Refuse);
-        assert_eq!(actions[6], Action::Ignore);
-        assert_eq!(actions[7], Action::Abort);
+        assert_eq!(actions[2], Action::AlwaysAll);
+        assert_eq!(actions[3], Action::Customize(String::default()));
+        assert_eq!(actions[4], Action::Replace(Replacement::default()));
+        assert_eq!(actions[5], Action::Skip);
+        assert_eq!(actions[6], Action::Refuse);
+        assert_eq!(actions[7], Action::Ignore);
+        assert_eq!(actions[8], Action::Abort);
     }
 
     #[test]
@@ -319,7 +415,7 @@ mod tests {
 
         assert_eq!(
             actions.iter().filter_map(shortcut_for).collect::<Vec<_>>(),
-            vec!['Y', 'A', 'C', 'S', 'R', 'I', 'Q', 'V']
+            vec!['Y', 'A', 'E', 'C', 'S', 'R', 'I', 'Q', 'V', 'N', 'U', 'F']
         );
 
         let func = |action: &Action| match shortcut_for(action) {
@@ -329,7 +425,10 @@ mod tests {
 
         assert_eq!(
             actions.iter().filter_map(func).collect::<Vec<_>>(),
-            vec!['Y', 'A', 'C', '?', 'S', 'R', 'I', 'Q', '?', 'V', '?']
+            vec![
+                'Y', 'A', 'E', 'C', '?', 'S', 'R', 'I', 'Q', '?', 'V', '?',
+                'N', '?', 'U', 'F'
+            ]
         );
     }
 
@@ -337,6 +436,7 @@ mod tests {
     fn from_confirmation() {
         assert_eq!(Action::Accept, Action::from(&Confirmation::Accept));
         assert_eq!(Action::Always, Action::from(&Confirmation::Always));
+        assert_eq!(Action::AlwaysAll, Action::from(&Confirmation::AlwaysAll));
         assert_eq!(
             Action::Replace(Replacement::default()),
             Action::from(&Confirmation::Replace(Replacement::default()))
@@ -345,12 +445,17 @@ mod tests {
         assert_eq!(Action::Refuse, Action::from(&Confirmation::Refuse));
         assert_eq!(Action::Ignore, Action::from(&Confirmation::Ignore));
         assert_eq!(Action::Abort, Action::from(&Confirmation::Abort));
+        assert_eq!(Action::Undo, Action::from(&Confirmation::Undo));
     }
 
     #[test]
     fn try_into_confirmation() {
         assert_eq!(Confirmation::Accept, Action::Accept.try_into().unwrap());
         assert_eq!(Confirmation::Always, Action::Always.try_into().unwrap());
+        assert_eq!(
+            Confirmation::AlwaysAll,
+            Action::AlwaysAll.try_into().unwrap()
+        );
         assert_eq!(
             Confirmation::Replace(Replacement::default()),
             Action::Replace(Replacement::default()).try_into().unwrap()
@@ -359,6 +464,7 @@ mod tests {
         assert_eq!(Confirmation::Refuse, Action::Refuse.try_into().unwrap());
         assert_eq!(Confirmation::Ignore, Action::Ignore.try_into().unwrap());
         assert_eq!(Confirmation::Abort, Action::Abort.try_into().unwrap());
+        assert_eq!(Confirmation::Undo, Action::Undo.try_into().unwrap());
         assert_eq!(
             Err(()),
             TryInto::<Confirmation>::try_into(Action::Customize(
@@ -374,6 +480,10 @@ mod tests {
             TryInto::<Confirmation>::try_into(Action::ViewAlternatives)
         );
         assert_eq!(Err(()), TryInto::<Confirmation>::try_into(Action::Cancel));
+        assert_eq!(
+            Err(()),
+            TryInto::<Confirmation>::try_into(Action::ChangeFormat)
+        );
     }
 
     #[test]
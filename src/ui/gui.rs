@@ -2,9 +2,12 @@
 
 use crate::application::Result;
 use crate::matcher::Matcher;
+use crate::processing::{Options, Outcome};
 use crate::ui;
 
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 mod processing;
 mod window;
@@ -24,16 +27,24 @@ impl ui::Interface for Gui {
         &mut self,
         matchers: &[Box<dyn Matcher>],
         paths: &[PathBuf],
-    ) -> Result<()> {
+        options: &Options,
+    ) -> Result<Outcome> {
         let matchers = matchers.to_owned();
         let paths = paths.to_owned();
+        let options = options.clone();
+        let failures = Arc::new(AtomicBool::new(false));
+        let window_failures = Arc::clone(&failures);
 
         iced::application(Window::title, Window::update, Window::view)
             .window_size((750., 300.))
             .subscription(Window::subscription)
             .theme(Window::theme)
-            .run_with(|| Window::new(matchers, paths))
+            .run_with(|| Window::new(matchers, paths, options, window_failures))
             .expect("Window to start");
-        Ok(())
+
+        match failures.load(Ordering::Relaxed) {
+            true => Ok(Outcome::Failures),
+            false => Ok(Outcome::Success),
+        }
     }
 }
@@ -1,8 +1,9 @@
 use crate::matcher::Matcher;
 use crate::processing::{
-    self, Communication, Confirmation, Error, Processing, Reporter,
+    self, Communication, Confirmation, Error, ErrorResolution, Options,
+    Outcome, Processing, Reporter, Stats,
 };
-use crate::replacement::Replacement;
+use crate::replacement::{self, Replacement};
 
 use std::cell::RefCell;
 use std::path::{Path, PathBuf};
@@ -26,14 +27,15 @@ pub enum Event {
     ProcessingErr(PathBuf, String),
     Confirm(Replacement),
     Rescue(Replacement),
-    Finished,
-    Aborted,
+    Finished(Outcome),
+    Aborted(Outcome),
 }
 
 #[derive(Debug, Clone)]
 pub enum InitializationData {
     Matchers(Vec<Box<dyn Matcher>>),
     Paths(Vec<PathBuf>),
+    Options(Box<Options>),
     Done,
 }
 
@@ -47,11 +49,13 @@ pub fn connect() -> impl Stream<Item = Event> {
 
         let mut matchers = Vec::<Box<dyn Matcher>>::new();
         let mut paths = Vec::<PathBuf>::new();
+        let mut options = Options::default();
 
         loop {
             match gui_rx.next().await {
                 Some(InitializationData::Matchers(m)) => matchers = m,
                 Some(InitializationData::Paths(p)) => paths = p,
+                Some(InitializationData::Options(o)) => options = *o,
                 Some(InitializationData::Done) => break,
                 None => panic!("Connection to UI broke during initialization"),
             }
@@ -72,11 +76,16 @@ pub fn connect() -> impl Stream<Item = Event> {
         // We are ready to receive confirmation messages.
         // Now we can create the processing on another thread
         std::thread::spawn(move || {
-            let front = ProcessingFront::new(&mut gui_rx, worker_tx.clone());
-            let result = match Processing::new(&front, &matchers, &paths).run()
-            {
-                Ok(_) => Event::Finished,
-                Err(_) => Event::Aborted,
+            let front = ProcessingFront::new(
+                &mut gui_rx,
+                worker_tx.clone(),
+                options.replacement.clone(),
+            );
+            let mut processing = Processing::new(&front, &matchers, &paths)
+                .with_options(options);
+            let result = match processing.run() {
+                Ok(_) => Event::Finished(processing.stats().outcome()),
+                Err(_) => Event::Aborted(processing.stats().outcome()),
             };
 
             if !worker_tx.is_closed() {
@@ -122,16 +131,20 @@ impl<T> Connection<T> {
 struct ProcessingFront<'a> {
     gui_rx: Mutex<&'a mut mpsc::Receiver<Confirmation>>,
     worker_tx: RefCell<mpsc::Sender<Event>>,
+    /// Settings of the replacements created to rescue the paths
+    settings: replacement::Settings,
 }
 
 impl<'a> ProcessingFront<'a> {
     pub fn new(
         gui_rx: &'a mut mpsc::Receiver<Confirmation>,
         worker_tx: mpsc::Sender<Event>,
+        settings: replacement::Settings,
     ) -> ProcessingFront<'a> {
         Self {
             gui_rx: Mutex::new(gui_rx),
             worker_tx: RefCell::new(worker_tx),
+            settings,
         }
     }
 
@@ -163,6 +176,8 @@ impl<'a> Reporter for ProcessingFront<'a> {
             format!("{}", error),
         ));
     }
+    fn processing_undone(&self, _replacement: &Replacement) {}
+    fn finish(&self, _stats: &Stats) {}
 }
 
 impl<'a> Communication for ProcessingFront<'a> {
@@ -180,7 +195,10 @@ impl<'a> Communication for ProcessingFront<'a> {
     fn rescue(&self, error: Error) -> processing::Result<Replacement> {
         match &error {
             Error::NoMatch(path) => {
-                let replacement = match Replacement::try_from(path.as_path()) {
+                let replacement = match Replacement::try_from_with(
+                    path.as_path(),
+                    &self.settings,
+                ) {
                     Ok(rep) => rep,
                     Err(_) => return Err(error),
                 };
@@ -217,4 +235,8 @@ impl<'a> Communication for ProcessingFront<'a> {
             }
         }
     }
+    /// Failed renames are reported in the log, without retrying
+    fn on_error(&self, _path: &Path, _error: &Error) -> ErrorResolution {
+        ErrorResolution::Report
+    }
 }
@@ -1,12 +1,15 @@
+use crate::application::walk;
 use crate::matcher::Matcher;
-use crate::processing::Confirmation;
+use crate::processing::{Confirmation, Options, Outcome, Transform};
 use crate::ui::actions::Action;
 use crate::ui::gui::processing;
 use crate::ui::state::{Current, ProcessingResult, State};
 
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use iced::keyboard::{Key, Modifiers};
+use iced::keyboard::{key::Named, Key, Modifiers};
 use iced::{Color, Element, Length, Subscription, Task, Theme};
 
 #[derive(Debug, Clone)]
@@ -18,15 +21,31 @@ pub enum Message {
     ToggleDebug,
     Quit,
     MaybeShortcut(Key<&'static str>),
+    FilesDropped(Vec<PathBuf>),
+    /// Select the alternative with the given name
+    SelectAlternative(String),
+    /// Select the next (true) or previous (false) alternative
+    SelectAdjacentAlternative(bool),
+    /// Use the selected alternative, if any
+    ConfirmAlternative,
 }
 
 pub struct Window {
     matchers: Vec<Box<dyn Matcher>>,
     paths: Vec<PathBuf>,
+    /// Paths dropped onto the window, to process once the current ones are
+    pending: Vec<PathBuf>,
+    /// Number of times the processing was started, to restart it for the
+    /// pending paths
+    batch: usize,
+    options: Options,
     processing_state: ProcessingState,
     state: State,
     log: bool,
     debug: bool,
+    /// Set once some paths failed to be processed, for the interface to
+    /// report the outcome once the window is closed
+    failures: Arc<AtomicBool>,
 }
 
 #[derive(Default)]
@@ -56,13 +75,28 @@ impl Window {
             }
             Accept => self.send_confirmation(Confirmation::Accept),
             Always => self.send_confirmation(Confirmation::Always),
+            AlwaysAll => self.send_confirmation(Confirmation::AlwaysAll),
             Skip => self.send_confirmation(Confirmation::Skip),
             Refuse => self.send_confirmation(Confirmation::Refuse),
             Ignore => self.send_confirmation(Confirmation::Ignore),
             Abort => self.send_confirmation(Confirmation::Abort),
             Replace(rep) => self.send_confirmation(Confirmation::Replace(rep)),
+            SkipToRescue => {
+                if self.state.skip_to_next_rescue() {
+                    self.send_confirmation(Confirmation::Skip)
+                } else {
+                    Task::none()
+                }
+            }
+            Transform(transform) => {
+                self.state.apply_transform(&transform);
+
+                iced::widget::text_input::focus(CUSTOMIZE_INPUT_ID.clone())
+            }
             ViewAlternatives => unimplemented!(),
             Cancel => unimplemented!(),
+            // Only offered by the text UI
+            Undo | ChangeFormat => Task::none(),
         }
     }
 
@@ -99,6 +133,7 @@ impl Window {
             Initialization(mut connection) => {
                 let matchers = self.matchers.clone();
                 let paths = self.paths.clone();
+                let options = self.options.clone();
 
                 use processing::InitializationData::*;
 
@@ -106,6 +141,7 @@ impl Window {
                     async move {
                         connection.send_async(Matchers(matchers)).await;
                         connection.send_async(Paths(paths)).await;
+                        connection.send_async(Options(Box::new(options))).await;
                         connection.send_async(Done).await;
                     },
                     |_| Message::Idle,
@@ -125,11 +161,27 @@ impl Window {
             }
             Confirm(rep) => {
                 self.state.set_current_confirm(rep, &self.matchers);
+
+                if self.state.skipping() {
+                    return self.send_confirmation(Confirmation::Skip);
+                }
             }
             Rescue(rep) => {
                 self.state.set_current_rescue(rep);
             }
-            Finished | Aborted => {
+            Finished(outcome) => {
+                self.record_outcome(outcome);
+
+                if self.pending.is_empty() {
+                    // Stay open to process the files dropped afterwards
+                    self.processing_state = ProcessingState::Finished;
+                } else {
+                    let pending = std::mem::take(&mut self.pending);
+                    self.start_batch(pending);
+                }
+            }
+            Aborted(outcome) => {
+                self.record_outcome(outcome);
                 self.processing_state = ProcessingState::Finished;
 
                 return iced::window::get_latest()
@@ -140,19 +192,65 @@ impl Window {
         Task::none()
     }
 
+    /// Remember the failures of a processing run, whatever the outcome of the
+    /// following ones
+    fn record_outcome(&self, outcome: Outcome) {
+        if outcome == Outcome::Failures {
+            self.failures.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Restart the processing for the given paths
+    fn start_batch(&mut self, paths: Vec<PathBuf>) {
+        self.paths = paths;
+        self.batch += 1;
+        self.processing_state = ProcessingState::Booting;
+    }
+
+    /// Paths to process for the dropped ones, the directories being expanded
+    /// when running recursively, leaving aside the paths filtered out by
+    /// their extension and the ones already queued
+    fn dropped_paths(&self, dropped: Vec<PathBuf>) -> Vec<PathBuf> {
+        let dropped = match &self.options.walk {
+            Some(walk) => walk.expand(&dropped),
+            None => dropped,
+        };
+        let dropped = self
+            .options
+            .extension_filter
+            .apply(dropped, &self.options.replacement.extensions);
+
+        let queued = match self.processing_state {
+            ProcessingState::Finished => Vec::new(),
+            _ => self.paths.iter().chain(&self.pending).cloned().collect(),
+        };
+        let count = queued.len();
+
+        walk::dedup(queued.into_iter().chain(dropped).collect())
+            .split_off(count)
+    }
+
     pub fn new(
         matchers: Vec<Box<dyn Matcher>>,
         paths: Vec<PathBuf>,
+        options: Options,
+        failures: Arc<AtomicBool>,
     ) -> (Self, Task<Message>) {
-        let len = paths.len();
+        let mut state = State::with_plan(&matchers, &paths, &options);
+        state.set_customize_extension(options.customize_extension);
+        state.set_rescue_matchers(options.rescue_alternatives.clone());
         (
             Window {
                 matchers,
                 paths,
+                pending: Vec::default(),
+                batch: 0,
+                options,
                 processing_state: ProcessingState::default(),
-                state: State::new(len),
+                state,
                 log: false,
                 debug: false,
+                failures,
             },
             Task::none(),
         )
@@ -197,18 +295,52 @@ impl Window {
                     Task::none()
                 }
             }
+            Message::FilesDropped(paths) => {
+                let paths = self.dropped_paths(paths);
+                if paths.is_empty() {
+                    return Task::none();
+                }
+                self.state.extend(&self.matchers, &paths);
+
+                match self.processing_state {
+                    ProcessingState::Finished => self.start_batch(paths),
+                    _ => self.pending.extend(paths),
+                }
+
+                Task::none()
+            }
+            Message::SelectAlternative(name) => {
+                self.state.select_alternative(name);
+
+                Task::none()
+            }
+            Message::SelectAdjacentAlternative(forward) => {
+                self.state.select_adjacent_alternative(forward);
+
+                Task::none()
+            }
+            Message::ConfirmAlternative => {
+                match self.state.selected_alternative().cloned() {
+                    Some(rep) => self.execute(Action::Replace(rep)),
+                    None => Task::none(),
+                }
+            }
         }
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
         Subscription::batch(vec![
-            Subscription::run(processing::connect).map(Message::Processing),
+            Subscription::run_with_id(self.batch, processing::connect())
+                .map(Message::Processing),
             iced::keyboard::on_key_press(handle_hotkey),
+            iced::event::listen_with(handle_file_drop),
         ])
     }
 
     pub fn view(&self) -> Element<Message> {
-        use iced::widget::{column, container, progress_bar, row, text, Row};
+        use iced::widget::{
+            column, container, progress_bar, radio, row, text, Row,
+        };
 
         let message: Element<_> = match &self.state.current() {
             Current::None => text("Booting").into(),
@@ -234,6 +366,9 @@ impl Window {
                 ]
                 .into()
             }
+            _ if matches!(self.processing_state, ProcessingState::Finished) => {
+                text("Done, drop files onto the window to process them").into()
+            }
             _ => text("Processing...").into(),
         };
 
@@ -242,7 +377,8 @@ impl Window {
                 match action {
                     Action::Replace(_)
                     | Action::ViewAlternatives
-                    | Action::Cancel => None,
+                    | Action::Cancel
+                    | Action::ChangeFormat => None,
                     _ => Some(action_button(action).into()),
                 }
             }),
@@ -250,30 +386,52 @@ impl Window {
         .spacing(10);
 
         buttons = buttons.push(simple_button("Logs", Message::ToggleLog));
+        if let ProcessingState::Finished = self.processing_state {
+            buttons = buttons.push(simple_button("Quit", Message::Quit));
+        }
 
         let mut content = column![message, buttons,]
             .width(Length::Fill)
             .padding(20)
             .spacing(10);
 
-        if let Current::Confirm(change) = &self.state.current() {
+        if let Current::Confirm(change) | Current::Rescue(change) =
+            &self.state.current()
+        {
             if !change.alternatives.is_empty() {
-                content = content.push(text("Or choose from an alternatives"));
+                let names = change.alternative_names();
+                let selected =
+                    change.selected_alternative.as_ref().and_then(|name| {
+                        names.iter().position(|other| other == name)
+                    });
+
+                content = content.push(text(
+                    "Or choose from an alternatives (Up/Down, Enter to use)",
+                ));
                 content = content.push(
                     container(
                         column(
-                            change
-                                .alternatives
-                                .values()
-                                .map(|rep| {
+                            names
+                                .iter()
+                                .enumerate()
+                                .map(|(index, name)| {
+                                    let rep = &change.alternatives[name];
                                     row![
-                                        action_button(Action::Replace(
-                                            rep.clone()
-                                        )),
+                                        radio(
+                                            format!(
+                                                "{}: {}",
+                                                name,
+                                                rep.new_file_name()
+                                            ),
+                                            index,
+                                            selected,
+                                            |_| Message::SelectAlternative(
+                                                name.clone()
+                                            ),
+                                        ),
                                         action_button(Action::Customize(
-                                            rep.new_file_stem.clone()
+                                            change.editable_name(rep)
                                         )),
-                                        text(rep.new_file_name()),
                                     ]
                                     .spacing(10)
                                 })
@@ -283,6 +441,11 @@ impl Window {
                     )
                     .width(Length::Fill),
                 );
+
+                if let Some(rep) = self.state.selected_alternative() {
+                    content = content
+                        .push(action_button(Action::Replace(rep.clone())));
+                }
             }
         }
 
@@ -299,6 +462,10 @@ impl Window {
             0.0..=(self.state.len() as f32),
             self.state.index() as f32,
         ));
+        content = content.push(
+            text(format!("{} file(s) remaining", self.state.remaining()))
+                .size(12),
+        );
 
         if self.log {
             content = content.push(scrollable_logs(self.state.logs()));
@@ -335,6 +502,15 @@ fn handle_hotkey(key_code: Key, modifiers: Modifiers) -> Option<Message> {
         match key_code {
             Key::Character("l") => return Some(Message::ToggleLog),
             Key::Character("d") => return Some(Message::ToggleDebug),
+            Key::Named(Named::ArrowDown) => {
+                return Some(Message::SelectAdjacentAlternative(true))
+            }
+            Key::Named(Named::ArrowUp) => {
+                return Some(Message::SelectAdjacentAlternative(false))
+            }
+            Key::Named(Named::Enter) => {
+                return Some(Message::ConfirmAlternative)
+            }
             _ => {}
         }
 
@@ -352,6 +528,19 @@ fn handle_hotkey(key_code: Key, modifiers: Modifiers) -> Option<Message> {
     None
 }
 
+fn handle_file_drop(
+    event: iced::Event,
+    _status: iced::event::Status,
+    _id: iced::window::Id,
+) -> Option<Message> {
+    match event {
+        iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+            Some(Message::FilesDropped(vec![path]))
+        }
+        _ => None,
+    }
+}
+
 fn scrollable_logs(
     logs: &[ProcessingResult],
 ) -> iced::widget::Scrollable<'_, Message> {
@@ -393,6 +582,7 @@ fn iced_shortcut_for(action: &Action) -> Option<Key<&'static str>> {
     match action {
         Action::Accept => Some(Key::<&str>::Character("y")),
         Action::Always => Some(Key::<&str>::Character("a")),
+        Action::AlwaysAll => Some(Key::<&str>::Character("e")),
         Action::Customize(_) => Some(Key::<&str>::Character("c")),
         Action::ViewAlternatives => None,
         Action::Replace(_) => None,
@@ -402,13 +592,18 @@ fn iced_shortcut_for(action: &Action) -> Option<Key<&'static str>> {
         Action::Abort => Some(Key::<&str>::Character("q")),
         Action::Cancel => None,
         Action::ConfirmCustomization => None,
+        Action::SkipToRescue => Some(Key::<&str>::Character("n")),
+        Action::Transform(_) => None,
+        Action::Undo => None,
+        Action::ChangeFormat => None,
     }
 }
 
-fn action_button(action: Action) -> iced::widget::Button<'static, Message> {
+fn action_button<'a>(action: Action) -> iced::widget::Button<'a, Message> {
     let label = match action {
         Action::Accept => "Yes",
         Action::Always => "Always",
+        Action::AlwaysAll => "Accept all",
         Action::Customize(_) => "Custom",
         Action::Skip => "Skip",
         Action::Refuse => "Refuse",
@@ -418,7 +613,83 @@ fn action_button(action: Action) -> iced::widget::Button<'static, Message> {
         Action::ConfirmCustomization => "Confirm",
         Action::ViewAlternatives => "Alternatives",
         Action::Cancel => "Cancel",
+        Action::SkipToRescue => "Next unmatched",
+        Action::Undo => "Undo",
+        Action::ChangeFormat => "Change date format",
+        Action::Transform(ref transform) => match transform {
+            Transform::Lowercase => "lowercase",
+            Transform::Uppercase => "UPPERCASE",
+            Transform::Slugify => "slugify",
+            Transform::Ascii => "ASCII",
+            Transform::Append(_) => "Append",
+        },
     };
 
     simple_button(label, Message::Action(action))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{ExtensionFilter, Walk};
+    use crate::test::{assert_eq, assert_fs::*, test, with_temp_dir};
+
+    #[test]
+    fn dropped_paths() {
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo.jpg").unwrap().to_path_buf();
+            let dir = temp.child("dir");
+            let bar = dir.existing_child("bar.jpg").unwrap().to_path_buf();
+            let baz = dir.existing_child("baz.txt").unwrap().to_path_buf();
+            let dropped = vec![foo.clone(), dir.to_path_buf()];
+
+            let (window, _) = Window::new(
+                vec![],
+                vec![],
+                Options::default(),
+                Default::default(),
+            );
+            assert_eq!(dropped, window.dropped_paths(dropped.clone()));
+
+            // Directories expanded when running recursively, duplicates
+            // removed
+            let options = Options {
+                walk: Some(Walk::default()),
+                ..Options::default()
+            };
+            let (window, _) = Window::new(
+                vec![],
+                vec![],
+                options.clone(),
+                Default::default(),
+            );
+            let mut expanded = window
+                .dropped_paths([dropped.clone(), vec![foo.clone()]].concat());
+            expanded[1..].sort();
+            assert_eq!(vec![foo.clone(), bar.clone(), baz], expanded);
+
+            // Filtered by extension, leaving the queued paths aside unless
+            // their processing is finished
+            let options = Options {
+                extension_filter: ExtensionFilter {
+                    include: vec![String::from("jpg")],
+                    exclude: vec![],
+                },
+                ..options
+            };
+            let (mut window, _) = Window::new(
+                vec![],
+                vec![foo.clone()],
+                options,
+                Default::default(),
+            );
+            assert_eq!(
+                vec![bar.clone()],
+                window.dropped_paths(dropped.clone())
+            );
+
+            window.processing_state = ProcessingState::Finished;
+            assert_eq!(vec![foo, bar], window.dropped_paths(dropped));
+        });
+    }
+}
@@ -1,9 +1,10 @@
 use crate::application::{Interactive, Result};
-use crate::matcher::Matcher;
+use crate::matcher::{Matcher, Naming};
 use crate::processing::{
-    self, Communication, Confirmation, Error, Processing, Reporter,
+    self, Communication, Confirmation, Error, ErrorResolution, Options,
+    Outcome, Processing, Reporter, Stats,
 };
-use crate::replacement::Replacement;
+use crate::replacement::{self, Replacement};
 
 use std::boxed::Box;
 use std::path::{Path, PathBuf};
@@ -36,11 +37,13 @@ pub trait Interface: Send {
         &mut self,
         matchers: &[Box<dyn Matcher>],
         paths: &[PathBuf],
-    ) -> Result<()>;
+        options: &Options,
+    ) -> Result<Outcome>;
 }
 
 pub fn from(interactive: Interactive) -> Box<dyn Interface> {
     match interactive {
+        Interactive::Auto => from(auto(Text::available(), display_available())),
         Interactive::Text if cfg!(feature = "text") && Text::available() => {
             Box::new(Text::new())
         }
@@ -49,7 +52,33 @@ pub fn from(interactive: Interactive) -> Box<dyn Interface> {
     }
 }
 
-pub struct NonInteractive {}
+/// Interface to pick given whether we run in a terminal and whether a display
+/// is available
+fn auto(terminal: bool, display: bool) -> Interactive {
+    if terminal && cfg!(feature = "text") {
+        Interactive::Text
+    } else if display && cfg!(feature = "gui") {
+        Interactive::Gui
+    } else {
+        Interactive::Off
+    }
+}
+
+/// Indicate whether a graphical display seems available
+fn display_available() -> bool {
+    cfg!(any(target_os = "windows", target_os = "macos"))
+        || ["DISPLAY", "WAYLAND_DISPLAY"].iter().any(|var| {
+            std::env::var_os(var).is_some_and(|value| !value.is_empty())
+        })
+}
+
+pub struct NonInteractive {
+    rescue: Option<Box<dyn Matcher>>,
+    /// How the rescue matcher composes the new file stems
+    naming: Naming,
+    /// Settings of the replacements created by the rescue matcher
+    settings: replacement::Settings,
+}
 
 impl NonInteractive {
     #[allow(dead_code)]
@@ -59,7 +88,11 @@ impl NonInteractive {
     }
 
     pub fn new() -> Self {
-        NonInteractive {}
+        NonInteractive {
+            rescue: None,
+            naming: Naming::default(),
+            settings: replacement::Settings::default(),
+        }
     }
 }
 
@@ -68,9 +101,16 @@ impl Interface for NonInteractive {
         &mut self,
         matchers: &[Box<dyn Matcher>],
         paths: &[PathBuf],
-    ) -> Result<()> {
-        Processing::new(self, matchers, paths).run()?;
-        Ok(())
+        options: &Options,
+    ) -> Result<Outcome> {
+        self.rescue = options.default_rescue.clone();
+        self.naming = options.naming.clone();
+        self.settings = options.replacement.clone();
+
+        let mut processing = Processing::new(self, matchers, paths)
+            .with_options(options.clone());
+        processing.run()?;
+        Ok(processing.stats().outcome())
     }
 }
 
@@ -79,21 +119,36 @@ impl Reporter for NonInteractive {
     fn processing(&self, _path: &Path) {}
     fn processing_ok(&self, _replacement: &Replacement) {}
     fn processing_err(&self, _path: &Path, _error: &Error) {}
+    fn processing_undone(&self, _replacement: &Replacement) {}
+    fn finish(&self, _stats: &Stats) {}
 }
 
 impl Communication for NonInteractive {
     fn confirm(&self, _replacement: &Replacement) -> Confirmation {
         Confirmation::Accept
     }
+    /// Rescue the path using the default rescue matcher, if any, keeping the
+    /// error if the matcher cannot handle the path either
     fn rescue(&self, error: Error) -> processing::Result<Replacement> {
+        if let (Some(matcher), Error::NoMatch(path)) = (&self.rescue, &error) {
+            if let Some(replacement) =
+                matcher.check_with(path, &self.naming, &self.settings)
+            {
+                return Ok(replacement);
+            }
+        }
+
         Err(error)
     }
+    fn on_error(&self, _path: &Path, _error: &Error) -> ErrorResolution {
+        ErrorResolution::Report
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test::{assert_fs::*, matchers, test, with_temp_dir};
+    use crate::test::{assert_eq, assert_fs::*, matchers, test, with_temp_dir};
     use predicates::prelude::*;
 
     #[test]
@@ -101,6 +156,41 @@ mod tests {
         from(Interactive::Gui);
         from(Interactive::Text);
         from(Interactive::Off);
+        from(Interactive::Auto);
+    }
+
+    #[test]
+    fn auto() {
+        let text = match cfg!(feature = "text") {
+            true => Interactive::Text,
+            false => Interactive::Off,
+        };
+        let gui = match cfg!(feature = "gui") {
+            true => Interactive::Gui,
+            false => Interactive::Off,
+        };
+
+        // A terminal is preferred over a display
+        assert_eq!(text, super::auto(true, true));
+        assert_eq!(text, super::auto(true, false));
+        assert_eq!(gui, super::auto(false, true));
+        assert_eq!(Interactive::Off, super::auto(false, false));
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[test]
+    fn display_available() {
+        let vars = |display, wayland| {
+            temp_env::with_vars(
+                [("DISPLAY", display), ("WAYLAND_DISPLAY", wayland)],
+                super::display_available,
+            )
+        };
+
+        assert!(vars(Some(":0"), None));
+        assert!(vars(None, Some("wayland-0")));
+        assert!(!vars(None, None));
+        assert!(!vars(Some(""), None));
     }
 
     #[test]
@@ -119,7 +209,7 @@ mod tests {
             let paths = [child1.to_path_buf(), child2.to_path_buf()];
             let mut ui = NonInteractive::new();
 
-            assert!(ui.process(&matchers, &paths).is_ok());
+            assert!(ui.process(&matchers, &paths, &Options::default()).is_ok());
 
             child1.assert(predicate::path::missing());
             temp.child("2024-01-20 foo")
@@ -128,4 +218,51 @@ mod tests {
             child2.assert(predicate::path::exists());
         });
     }
+
+    #[test]
+    fn non_interactive_default_rescue() {
+        let matchers = [matchers::weird_boxed()];
+        let options = Options {
+            default_rescue: Some(matchers::ymd_boxed()),
+            ..Options::default()
+        };
+
+        with_temp_dir(|temp| {
+            let child1 = temp.existing_child("foo 20240120").unwrap();
+            let child2 = temp.existing_child("bar").unwrap();
+
+            let paths = [child1.to_path_buf(), child2.to_path_buf()];
+            let mut ui = NonInteractive::new();
+
+            assert!(ui.process(&matchers, &paths, &options).is_ok());
+
+            child1.assert(predicate::path::missing());
+            temp.child("2024-01-20 foo")
+                .assert(predicate::path::exists());
+
+            child2.assert(predicate::path::exists());
+        });
+    }
+
+    #[test]
+    fn non_interactive_rescue() {
+        let mut ui = NonInteractive::new();
+        let path = PathBuf::from("/foo 20240120");
+
+        assert!(matches!(
+            ui.rescue(Error::no_match(&path)),
+            Err(Error::NoMatch(_))
+        ));
+
+        ui.rescue = Some(matchers::ymd_boxed());
+        assert_eq!(
+            "2024-01-20 foo",
+            ui.rescue(Error::no_match(&path)).unwrap().new_file_stem
+        );
+        assert!(matches!(
+            ui.rescue(Error::no_match(Path::new("/bar"))),
+            Err(Error::NoMatch(_))
+        ));
+        assert!(matches!(ui.rescue(Error::Abort), Err(Error::Abort)));
+    }
 }
@@ -1,6 +1,6 @@
-use crate::matcher::Matcher;
-use crate::processing::Confirmation;
-use crate::replacement::Replacement;
+use crate::matcher::{Matcher, Naming};
+use crate::processing::{self, Confirmation, Options, Transform};
+use crate::replacement::{self, Extensions, Replacement};
 use crate::ui::actions::Action;
 
 use std::collections::HashMap;
@@ -17,6 +17,19 @@ pub struct State {
     /// Relevant actions for the current item
     actions: Vec<Action>,
     logs: Vec<ProcessingResult>,
+    /// For each item, whether it is expected to need a rescue, i.e. no
+    /// matcher handles it
+    plan: Vec<bool>,
+    /// Index of the item up to which the items are skipped
+    skip_until: Option<usize>,
+    /// Customize the whole file name instead of only the stem
+    customize_extension: bool,
+    /// Matchers suggesting alternatives when rescuing
+    rescue_matchers: Vec<Box<dyn Matcher>>,
+    /// How the matchers compose the new file stems
+    naming: Naming,
+    /// Settings of the replacements created from the paths
+    settings: replacement::Settings,
 }
 
 impl State {
@@ -27,13 +40,50 @@ impl State {
         }
     }
 
+    /// Create a new state for the given paths, planning ahead which ones will
+    /// need a rescue with the naming and settings of the options
+    pub fn with_plan(
+        matchers: &[Box<dyn Matcher>],
+        paths: &[PathBuf],
+        options: &Options,
+    ) -> Self {
+        let (naming, settings) = (&options.naming, &options.replacement);
+
+        Self {
+            plan: plan(matchers, paths, naming, settings),
+            naming: naming.clone(),
+            settings: settings.clone(),
+            ..Self::new(paths.len())
+        }
+    }
+
+    /// Suggest the alternatives of these matchers when rescuing, whether
+    /// they are enabled or not
+    pub fn set_rescue_matchers(&mut self, matchers: Vec<Box<dyn Matcher>>) {
+        self.rescue_matchers = matchers;
+    }
+
+    /// Add the given paths to process after the current ones, planning ahead
+    /// which ones will need a rescue
+    pub fn extend(&mut self, matchers: &[Box<dyn Matcher>], paths: &[PathBuf]) {
+        self.len += paths.len();
+        self.plan
+            .extend(plan(matchers, paths, &self.naming, &self.settings));
+    }
+
+    /// Customize the whole file name of the following changes, extension
+    /// included, instead of only the stem
+    pub fn set_customize_extension(&mut self, customize_extension: bool) {
+        self.customize_extension = customize_extension;
+    }
+
     /// Transition current to Path
     ///
     /// Only possible from None (default state at the beginning) and Resolved
     pub fn set_current_path(&mut self, path: PathBuf) {
         if matches!(self.current, Current::None | Current::Resolved) {
             self.current = Current::Path(path);
-            self.actions = Action::determine_for(&self.current);
+            self.refresh_actions();
         }
     }
 
@@ -56,7 +106,9 @@ impl State {
         let alternatives = matchers
             .iter()
             .filter_map(|matcher| {
-                matcher.check(path).and_then(|rep| {
+                let rep =
+                    matcher.check_with(path, &self.naming, &self.settings);
+                rep.and_then(|rep| {
                     // Skip alternatives similar to the replacement
                     if rep.new_file_stem == replacement.new_file_stem {
                         None
@@ -69,9 +121,11 @@ impl State {
 
         let mut change = Change::new(replacement);
         change.alternatives = alternatives;
+        change.full_name = self.customize_extension;
+        change.extensions = self.settings.extensions.clone();
 
         self.current = Current::Confirm(change);
-        self.actions = Action::determine_for(&self.current);
+        self.refresh_actions();
     }
 
     /// Transition current from Path to Rescue
@@ -80,9 +134,45 @@ impl State {
             return;
         }
 
-        let change = Change::new(replacement);
+        // We reached an item needing a rescue, whether it was planned or not
+        self.skip_until = None;
+
+        let path = replacement.path();
+        let alternatives = self
+            .rescue_matchers
+            .iter()
+            .filter_map(|matcher| {
+                matcher.check_with(&path, &self.naming, &self.settings).map(
+                    |rep| (format!("{} (suggestion)", matcher.name()), rep),
+                )
+            })
+            .collect();
+
+        let mut change = Change::new(replacement);
+        change.alternatives = alternatives;
+        change.full_name = self.customize_extension;
+        change.extensions = self.settings.extensions.clone();
         self.current = Current::Rescue(change);
-        self.actions = Action::determine_for(&self.current);
+        self.refresh_actions();
+    }
+
+    /// Transition current from Resolving back to Rescue, the rename of the
+    /// resolved replacement having failed with the given error
+    pub fn set_current_retry(
+        &mut self,
+        replacement: Replacement,
+        error: String,
+    ) {
+        if !matches!(self.current, Current::Resolving(_)) {
+            return;
+        }
+
+        let mut change = Change::new(replacement);
+        change.full_name = self.customize_extension;
+        change.extensions = self.settings.extensions.clone();
+        change.error = Some(error);
+        self.current = Current::Rescue(change);
+        self.refresh_actions();
     }
 
     /// Transition current from Confirm or Rescue to Resolving using the given
@@ -94,7 +184,7 @@ impl State {
             && self.actions.contains(&Action::from(&conf))
         {
             self.current = Current::Resolving(conf);
-            self.actions = Action::determine_for(&self.current);
+            self.refresh_actions();
 
             return true;
         }
@@ -112,7 +202,7 @@ impl State {
         self.index += 1;
         self.logs.push(ProcessingResult::Success(replacement));
         self.current = Current::Resolved;
-        self.actions = Action::determine_for(&self.current);
+        self.refresh_actions();
     }
     /// Transition from Resolving to Resolved, incrementing the progress
     /// tracker and logging the failed result
@@ -124,7 +214,7 @@ impl State {
         self.index += 1;
         self.logs.push(ProcessingResult::Failure(path, error));
         self.current = Current::Resolved;
-        self.actions = Action::determine_for(&self.current);
+        self.refresh_actions();
     }
 
     /// Update the customize field of the current change, as returned by
@@ -135,8 +225,52 @@ impl State {
         if let Some(change) = self.change_mut() {
             change.customize = Some(string);
 
-            self.actions = Action::determine_for(&self.current);
+            self.refresh_actions();
+        }
+    }
+
+    /// Apply the transform to the current change, starting from its
+    /// customization if any, and store the result as customization
+    ///
+    /// This also refresh the actions
+    pub fn apply_transform(&mut self, transform: &Transform) {
+        if let Some(change) = self.change_mut() {
+            let mut replacement = match &change.customize {
+                Some(value) => change.customized(value),
+                None => change.replacement.clone(),
+            };
+            replacement.new_file_stem =
+                transform.apply(&replacement.new_file_stem);
+            change.customize = Some(change.editable_name(&replacement));
+
+            self.refresh_actions();
+        }
+    }
+
+    /// Render the date of the current change again with the given format,
+    /// starting from its customization if any, and store the result as
+    /// customization
+    ///
+    /// Return false if the date could not be rendered again, e.g. if the
+    /// format is invalid or the date was edited away
+    ///
+    /// This also refresh the actions
+    pub fn change_date_format(&mut self, format: &str) -> bool {
+        let Some(change) = self.change_mut() else {
+            return false;
+        };
+        let mut replacement = match &change.customize {
+            Some(value) => change.customized(value),
+            None => change.replacement.clone(),
+        };
+        if !replacement.change_date_format(format) {
+            return false;
         }
+        change.customize = Some(change.editable_name(&replacement));
+        change.date_format = Some(format.to_string());
+
+        self.refresh_actions();
+        true
     }
 
     /// Cancel current customization, i.e. sets the customize field of the
@@ -146,21 +280,65 @@ impl State {
     pub fn cancel_customize(&mut self) {
         if let Some(change) = self.change_mut() {
             change.customize = None;
+            change.date_format = None;
 
-            self.actions = Action::determine_for(&self.current);
+            self.refresh_actions();
         }
     }
 
+    /// Select the alternative of the current change with the given name,
+    /// if there is one
+    pub fn select_alternative(&mut self, name: String) {
+        if let Some(change) = self.change_mut() {
+            if change.alternatives.contains_key(&name) {
+                change.selected_alternative = Some(name);
+            }
+        }
+    }
+
+    /// Select the alternative of the current change following (or preceding
+    /// if forward is false) the selected one, wrapping around
+    pub fn select_adjacent_alternative(&mut self, forward: bool) {
+        if let Some(change) = self.change_mut() {
+            let names = change.alternative_names();
+            if names.is_empty() {
+                return;
+            }
+
+            let position = change
+                .selected_alternative
+                .as_ref()
+                .and_then(|name| names.iter().position(|other| other == name));
+            let index = match (position, forward) {
+                (None, true) => 0,
+                (None, false) => names.len() - 1,
+                (Some(index), true) => (index + 1) % names.len(),
+                (Some(index), false) => (index + names.len() - 1) % names.len(),
+            };
+
+            change.selected_alternative = Some(names[index].clone());
+        }
+    }
+
+    /// Replacement of the alternative selected for the current change
+    pub fn selected_alternative(&self) -> Option<&Replacement> {
+        self.change().and_then(|change| {
+            change
+                .selected_alternative
+                .as_ref()
+                .and_then(|name| change.alternatives.get(name))
+        })
+    }
+
     /// Get a Replacement from the customize field of the current change
     ///
     /// Returns None if there is no customization or if change() returns None
     pub fn customized_replacement(&self) -> Option<Replacement> {
         self.change().and_then(|change| {
-            change.customize.clone().map(|value| {
-                let mut replacement = change.replacement.clone();
-                replacement.new_file_stem = value;
-                replacement
-            })
+            change
+                .customize
+                .as_ref()
+                .map(|value| change.customized(value))
         })
     }
 
@@ -178,11 +356,45 @@ impl State {
         }
     }
 
+    /// Start skipping the items up to the next one needing a rescue
+    ///
+    /// Returns false if no upcoming item needs a rescue
+    pub fn skip_to_next_rescue(&mut self) -> bool {
+        self.skip_until = self.next_rescue_index();
+        self.skip_until.is_some()
+    }
+
+    /// Check if the current item should be skipped as we are heading to the
+    /// next item needing a rescue
+    pub fn skipping(&self) -> bool {
+        self.skip_until.is_some_and(|until| self.index < until)
+    }
+
+    /// Index of the next item expected to need a rescue, after the current one
+    pub fn next_rescue_index(&self) -> Option<usize> {
+        next_rescue_index(&self.plan, self.index + 1)
+    }
+
+    fn refresh_actions(&mut self) {
+        self.actions = Action::determine_for(&self.current);
+
+        if matches!(self.current, Current::Confirm(_) | Current::Rescue(_))
+            && self.next_rescue_index().is_some()
+        {
+            self.actions.push(Action::SkipToRescue);
+        }
+    }
+
     /// Index of the current path being processed
     pub fn index(&self) -> usize {
         self.index
     }
 
+    /// Number of paths left to process, including the current one
+    pub fn remaining(&self) -> usize {
+        self.len.saturating_sub(self.index)
+    }
+
     /// Number of paths to process
     pub fn len(&self) -> usize {
         self.len
@@ -204,6 +416,28 @@ impl State {
     }
 }
 
+/// Determine for each path whether it will need a rescue, i.e. if no matcher
+/// handles it
+pub fn plan(
+    matchers: &[Box<dyn Matcher>],
+    paths: &[PathBuf],
+    naming: &Naming,
+    settings: &replacement::Settings,
+) -> Vec<bool> {
+    processing::plan::attribute(matchers, paths, naming, settings)
+        .iter()
+        .map(Option::is_none)
+        .collect()
+}
+
+/// Index of the first item from the given one that needs a rescue
+pub fn next_rescue_index(plan: &[bool], from: usize) -> Option<usize> {
+    plan.iter()
+        .skip(from)
+        .position(|rescue| *rescue)
+        .map(|index| index + from)
+}
+
 /// Element currently being processed
 #[derive(Debug, Clone, Default)]
 pub enum Current {
@@ -218,9 +452,10 @@ pub enum Current {
     ///
     /// From Path and Resolving
     Confirm(Change),
-    /// There were no match for the path, but we can rescue from there
+    /// There were no match for the path, or its rename failed, but we can
+    /// rescue from there
     ///
-    /// From Path
+    /// From Path and Resolving
     Rescue(Change),
     /// A decision has been taken (Confirmation).
     ///
@@ -242,7 +477,17 @@ impl PartialEq for Current {
 pub struct Change {
     pub replacement: Replacement,
     pub alternatives: HashMap<String, Replacement>,
+    /// Name of the alternative selected, to be confirmed
+    pub selected_alternative: Option<String>,
     pub customize: Option<String>,
+    /// Date format chosen to render the date of the customization, if any
+    pub date_format: Option<String>,
+    /// The customization is the whole file name instead of only the stem
+    pub full_name: bool,
+    /// Rules to split the customized file name between stem and extension
+    pub extensions: Extensions,
+    /// Error of the failed rename being retried, if any
+    pub error: Option<String>,
 }
 
 impl Change {
@@ -260,6 +505,41 @@ impl Change {
     pub fn is_further_customizable(&self) -> bool {
         self.customize.is_none() || !self.alternatives.is_empty()
     }
+
+    /// Names of the alternatives, sorted to always list them in the same
+    /// order
+    pub fn alternative_names(&self) -> Vec<String> {
+        let mut names: Vec<String> =
+            self.alternatives.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Part of the new file name of the replacement to edit when customizing
+    pub fn editable_name(&self, replacement: &Replacement) -> String {
+        if self.full_name {
+            replacement.new_file_name()
+        } else {
+            replacement.new_file_stem.clone()
+        }
+    }
+
+    /// Replacement of the change with the given customization
+    pub fn customized(&self, value: &str) -> Replacement {
+        let mut replacement = if self.full_name {
+            self.replacement
+                .clone()
+                .with_new_file_name(value, &self.extensions)
+        } else {
+            let mut replacement = self.replacement.clone();
+            replacement.new_file_stem = value.to_string();
+            replacement
+        };
+        if self.date_format.is_some() {
+            replacement.date_format.clone_from(&self.date_format);
+        }
+        replacement
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -429,6 +709,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn set_current_rescue_with_suggestions() {
+        use crate::application::DEFAULT_DATE_FORMAT;
+        use crate::matcher::Metadata;
+        use crate::test::{assert_fs::*, with_temp_dir};
+
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo").unwrap();
+            let replacement = Replacement::try_from(foo.path()).unwrap();
+
+            let mut state = State::default();
+            state.set_rescue_matchers(vec![Box::new(Metadata::new_modified(
+                DEFAULT_DATE_FORMAT,
+            ))]);
+            state.current = Current::Path(foo.to_path_buf());
+            state.set_current_rescue(replacement);
+
+            let change = state.change().unwrap();
+            assert_eq!(change.alternatives.len(), 1);
+            assert!(change.alternatives.contains_key("modified (suggestion)"));
+            assert!(state.actions.contains(&Action::ViewAlternatives));
+        });
+    }
+
+    #[test]
+    fn set_current_retry() {
+        let replacement = Replacement::default();
+        let error = String::from("Target exists");
+
+        for current in CurrentIterator::default() {
+            let mut state = State::default();
+            state.current = current.clone();
+            state.actions = vec![Action::Cancel];
+            state.set_current_retry(replacement.clone(), error.clone());
+
+            match current {
+                Current::Resolving(_) => {
+                    let change = state.change().unwrap();
+                    assert_eq!(change.error, Some(error.clone()));
+                    assert!(matches!(state.current, Current::Rescue(_)));
+                    assert!(state
+                        .actions
+                        .iter()
+                        .any(|action| action == &Action::Skip));
+                }
+                _ => {
+                    assert_eq!(state.current, current);
+                }
+            }
+        }
+    }
+
     #[test]
     fn set_current_resolving() {
         let conf = Confirmation::Accept;
@@ -618,6 +950,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn customized_replacement_full_name() {
+        let replacement =
+            Replacement::try_from(std::path::Path::new("/tmp/foo.txt"))
+                .unwrap();
+        let mut state = State::new(1);
+        state.set_customize_extension(true);
+        state.set_current_path(replacement.path());
+        state.set_current_rescue(replacement);
+
+        assert!(state
+            .actions()
+            .contains(&Action::Customize(String::from("foo.txt"))));
+
+        state.customize(String::from("foo.md"));
+        let customized = state.customized_replacement().unwrap();
+        assert_eq!("foo", customized.new_file_stem);
+        assert_eq!("foo.md", customized.new_file_name());
+
+        // Transforms leave the extension out
+        state.apply_transform(&Transform::Uppercase);
+        assert_eq!(
+            Some(&String::from("FOO.md")),
+            state.change().unwrap().customize.as_ref()
+        );
+    }
+
+    #[test]
+    fn select_alternative() {
+        let foo = Replacement::default().new_file_stem(String::from("foo"));
+        let bar = Replacement::default().new_file_stem(String::from("bar"));
+        let change = Change {
+            alternatives: HashMap::from([
+                ("b".to_string(), bar),
+                ("a".to_string(), foo),
+            ]),
+            ..Change::default()
+        };
+
+        let mut state = State {
+            current: Current::Confirm(change),
+            ..State::default()
+        };
+        state.refresh_actions();
+        assert!(state.selected_alternative().is_none());
+
+        // Unknown alternatives are ignored
+        state.select_alternative(String::from("c"));
+        assert!(state.selected_alternative().is_none());
+
+        state.select_alternative(String::from("b"));
+        assert_eq!("bar", state.selected_alternative().unwrap().new_file_stem);
+
+        // Adjacent alternatives are selected in the order of their names
+        state.select_adjacent_alternative(true);
+        assert_eq!("foo", state.selected_alternative().unwrap().new_file_stem);
+        state.select_adjacent_alternative(false);
+        assert_eq!("bar", state.selected_alternative().unwrap().new_file_stem);
+
+        // Confirming the selected alternative resolves the change with it
+        let selected = state.selected_alternative().unwrap().clone();
+        assert!(state.set_current_resolving(Confirmation::Replace(selected)));
+        assert!(matches!(
+            state.current(),
+            Current::Resolving(Confirmation::Replace(rep))
+                if rep.new_file_stem == "bar"
+        ));
+        assert!(state.selected_alternative().is_none());
+    }
+
     #[test]
     fn is_further_customizable() {
         let mut change = Change::default();
@@ -630,4 +1032,206 @@ mod tests {
             HashMap::from([("Hello".to_string(), Replacement::default())]);
         assert!(change.is_further_customizable());
     }
+
+    #[test]
+    fn plan() {
+        let matchers = [matchers::ymd_boxed()];
+        let paths = [
+            PathBuf::from("/test/foo 20240120"),
+            PathBuf::from("/test/foo"),
+            PathBuf::from("/test/bar 20240121"),
+        ];
+
+        let plan = |matchers: &[Box<dyn Matcher>]| {
+            super::plan(
+                matchers,
+                &paths,
+                &Naming::default(),
+                &replacement::Settings::default(),
+            )
+        };
+
+        assert_eq!(vec![false, true, false], plan(&matchers));
+        assert_eq!(vec![true, true, true], plan(&[]));
+    }
+
+    #[test]
+    fn next_rescue_index() {
+        let plan = [false, true, false, false, true];
+
+        assert_eq!(Some(1), super::next_rescue_index(&plan, 0));
+        assert_eq!(Some(1), super::next_rescue_index(&plan, 1));
+        assert_eq!(Some(4), super::next_rescue_index(&plan, 2));
+        assert_eq!(None, super::next_rescue_index(&plan, 5));
+        assert_eq!(None, super::next_rescue_index(&plan, 42));
+        assert_eq!(None, super::next_rescue_index(&[false, false], 0));
+    }
+
+    #[test]
+    fn skip_to_next_rescue() {
+        let matchers = [matchers::ymd_boxed()];
+        let paths = [
+            PathBuf::from("/test/foo 20240120"),
+            PathBuf::from("/test/bar 20240121"),
+            PathBuf::from("/test/foo"),
+        ];
+        let mut replacement =
+            Replacement::try_from(paths[0].as_path()).unwrap();
+        replacement.new_file_stem = String::from("2024-01-20 foo");
+
+        let mut state =
+            State::with_plan(&matchers, &paths, &Options::default());
+        assert_eq!(state.remaining(), 3);
+        assert_eq!(state.next_rescue_index(), Some(2));
+        assert!(!state.skipping());
+
+        state.current = Current::Path(paths[0].clone());
+        state.set_current_confirm(replacement.clone(), &[]);
+        assert!(state.actions().contains(&Action::SkipToRescue));
+
+        assert!(state.skip_to_next_rescue());
+        assert!(state.skipping());
+        assert!(state.set_current_resolving(Confirmation::Skip));
+        state.set_current_success(replacement.clone());
+        assert_eq!(state.remaining(), 2);
+        assert!(state.skipping());
+
+        state.index = 2;
+        assert!(!state.skipping());
+        assert_eq!(state.next_rescue_index(), None);
+
+        state.current = Current::Path(paths[2].clone());
+        state.set_current_rescue(replacement);
+        assert!(!state.actions().contains(&Action::SkipToRescue));
+        assert!(!state.skip_to_next_rescue());
+    }
+
+    #[test]
+    fn extend() {
+        let matchers = [matchers::ymd_boxed()];
+        let mut state = State::with_plan(
+            &matchers,
+            &[PathBuf::from("/test/foo 20240120")],
+            &Options::default(),
+        );
+        assert_eq!(state.remaining(), 1);
+        assert_eq!(state.next_rescue_index(), None);
+
+        state.extend(
+            &matchers,
+            &[
+                PathBuf::from("/test/bar 20240121"),
+                PathBuf::from("/test/foo"),
+            ],
+        );
+        assert_eq!(state.len(), 3);
+        assert_eq!(state.remaining(), 3);
+        assert_eq!(state.next_rescue_index(), Some(2));
+    }
+
+    #[test]
+    fn apply_transform() {
+        let replacement = Replacement {
+            new_file_stem: String::from("2024-01-20 Foo Bar"),
+            ..Replacement::default()
+        };
+
+        for current in CurrentIterator::default() {
+            let mut state = State {
+                current: match &current {
+                    Current::Confirm(_) => {
+                        Current::Confirm(Change::new(replacement.clone()))
+                    }
+                    Current::Rescue(_) => {
+                        Current::Rescue(Change::new(replacement.clone()))
+                    }
+                    other => other.clone(),
+                },
+                ..State::default()
+            };
+            state.apply_transform(&Transform::Lowercase);
+
+            match current {
+                Current::Confirm(_) | Current::Rescue(_) => {
+                    assert_eq!(
+                        Some(&String::from("2024-01-20 foo bar")),
+                        state.change().unwrap().customize.as_ref()
+                    );
+
+                    // Transforms are applied over the current customization
+                    state.apply_transform(&Transform::Slugify);
+                    assert_eq!(
+                        Some(&String::from("2024-01-20-foo-bar")),
+                        state.change().unwrap().customize.as_ref()
+                    );
+                    assert!(state
+                        .actions()
+                        .contains(&Action::Transform(Transform::Ascii)));
+                }
+                _ => {
+                    assert!(state.change().is_none());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn change_date_format() {
+        use chrono::DateTime;
+
+        let replacement = Replacement {
+            new_file_stem: String::from("2024-01-20 Foo Bar"),
+            date_time: Some(
+                DateTime::parse_from_rfc3339("2024-01-20T10:30:00+01:00")
+                    .unwrap(),
+            ),
+            date_format: Some(String::from("%Y-%m-%d")),
+            ..Replacement::default()
+        };
+
+        for current in CurrentIterator::default() {
+            let mut state = State {
+                current: match &current {
+                    Current::Confirm(_) => {
+                        Current::Confirm(Change::new(replacement.clone()))
+                    }
+                    Current::Rescue(_) => {
+                        Current::Rescue(Change::new(replacement.clone()))
+                    }
+                    other => other.clone(),
+                },
+                ..State::default()
+            };
+
+            match current {
+                Current::Confirm(_) | Current::Rescue(_) => {
+                    assert!(state.change_date_format("%Y%m%d"));
+                    assert_eq!(
+                        "20240120 Foo Bar",
+                        state.customized_replacement().unwrap().new_file_stem
+                    );
+
+                    // Formats are changed over the current customization
+                    state.apply_transform(&Transform::Lowercase);
+                    assert!(state.change_date_format("%d.%m.%Y"));
+                    assert_eq!(
+                        "20.01.2024 foo bar",
+                        state.customized_replacement().unwrap().new_file_stem
+                    );
+
+                    assert!(!state.change_date_format("%Q"));
+                    assert_eq!(
+                        "20.01.2024 foo bar",
+                        state.customized_replacement().unwrap().new_file_stem
+                    );
+
+                    state.cancel_customize();
+                    assert_eq!(None, state.change().unwrap().date_format);
+                }
+                _ => {
+                    assert!(!state.change_date_format("%Y%m%d"));
+                }
+            }
+        }
+    }
 }
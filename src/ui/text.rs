@@ -3,9 +3,10 @@
 use crate::application::Result;
 use crate::matcher::Matcher;
 use crate::processing::{
-    self, Communication, Confirmation, Error, Processing, Reporter,
+    self, Communication, Confirmation, Error, ErrorResolution, Options,
+    Outcome, Processing, Reporter, Stats, Transform,
 };
-use crate::replacement::Replacement;
+use crate::replacement::{Replacement, Settings};
 use crate::ui::{
     self,
     actions::Action,
@@ -19,18 +20,30 @@ use std::path::{Path, PathBuf};
 
 use env_logger::Builder;
 
+use dialoguer::console::Term;
 use dialoguer::theme::ColorfulTheme;
-use indicatif::{MultiProgress, ProgressBar};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
 type LogResult = std::result::Result<(), log::SetLoggerError>;
 
+/// Progress of the run, followed by the name of the file being processed
+const PROGRESS_TEMPLATE: &str = "{bar:40} {pos}/{len} ETA {eta} {wide_msg}";
+
 pub struct Text {
     state: RefCell<State>,
     theme: ColorfulTheme,
+    term: Term,
     bar: Option<ProgressBar>,
     multi_progress: MultiProgress,
     matcher_name_length: usize,
     matchers: Vec<Box<dyn Matcher>>,
+    /// Last rename performed, which can be reverted at the next prompt
+    last: RefCell<Option<Replacement>>,
+    quiet: bool,
+    /// Print the planned renames without prompting, during a dry run
+    preview: bool,
+    /// Settings of the replacements created from the paths
+    settings: Settings,
 }
 
 struct ReplacementDisplay<'a> {
@@ -63,16 +76,55 @@ impl<'a> From<&'a Replacement> for ReplacementDisplay<'a> {
     }
 }
 
+/// Output stream used to interact with the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    /// Select the stream to interact on given which ones are terminals
+    ///
+    /// Stdout is preferred, but we fallback on stderr so stdout can be piped
+    /// while still interacting with the user
+    fn select(
+        stdout_is_terminal: bool,
+        stderr_is_terminal: bool,
+    ) -> Option<Self> {
+        match (stdout_is_terminal, stderr_is_terminal) {
+            (true, _) => Some(Self::Stdout),
+            (false, true) => Some(Self::Stderr),
+            (false, false) => None,
+        }
+    }
+
+    fn detect() -> Option<Self> {
+        use std::io::IsTerminal;
+
+        Self::select(
+            std::io::stdout().is_terminal(),
+            std::io::stderr().is_terminal(),
+        )
+    }
+
+    fn term(&self) -> Term {
+        match self {
+            Self::Stdout => Term::stdout(),
+            Self::Stderr => Term::stderr(),
+        }
+    }
+}
+
 impl Text {
     /// Inidcate whether or not this interface is available
     pub fn available() -> bool {
-        use std::io::{self, IsTerminal};
-
         // If we are connected_to_journal, it means we're not connected to a
         // standard terminal so we can't really present
         !systemd_journal_logger::connected_to_journal() &&
-            // If stdout is not a terminal, then we probably don't want interaction either
-            std::io::stdout().is_terminal()
+            // If neither stdout nor stderr is a terminal, then we probably
+            // don't want interaction either
+            Stream::detect().is_some()
     }
 
     #[cfg(test)]
@@ -93,18 +145,70 @@ impl Text {
         Self {
             state: RefCell::<State>::default(),
             theme: ColorfulTheme::default(),
+            term: Stream::detect().unwrap_or(Stream::Stderr).term(),
             multi_progress,
             bar: None,
             matcher_name_length: 0,
             matchers: Default::default(),
+            last: Default::default(),
+            quiet: false,
+            preview: false,
+            settings: Settings::default(),
         }
     }
 
+    fn progress_bar(len: u64) -> ProgressBar {
+        ProgressBar::new(len).with_style(
+            ProgressStyle::with_template(PROGRESS_TEMPLATE)
+                .expect("Progress template to be valid"),
+        )
+    }
+
     fn inc_progress(&self) {
         if let Some(bar) = &self.bar {
             bar.inc(1);
         }
     }
+
+    fn write_line(&self, line: &str) {
+        self.term.write_line(line).unwrap();
+    }
+
+    /// Line describing a planned rename, with the changes highlighted
+    fn preview_line(replacement: &Replacement) -> String {
+        let mut line = format!(
+            "In {}: {}",
+            replacement.parent.display(),
+            ReplacementDisplay::from(replacement)
+        );
+        if !replacement.new_extension().is_empty() {
+            line = format!("{}.{}", line, replacement.new_extension());
+        }
+        line
+    }
+
+    /// Replacement to customize in order to retry the failed rename of the
+    /// path, starting from the conflicting name if any
+    fn retry_replacement(
+        path: &Path,
+        error: &Error,
+        settings: &Settings,
+    ) -> Option<Replacement> {
+        let replacement = Replacement::try_from_with(path, settings).ok()?;
+
+        match error {
+            Error::TargetExists(target) => target.file_name().map(|name| {
+                replacement.with_new_file_name(
+                    &name.to_string_lossy(),
+                    &settings.extensions,
+                )
+            }),
+            Error::Io(_) | Error::PermissionDenied(_) | Error::ReadOnly(_) => {
+                Some(replacement)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Drop for Text {
@@ -129,13 +233,25 @@ impl ui::Interface for Text {
         &mut self,
         matchers: &[Box<dyn Matcher>],
         paths: &[PathBuf],
-    ) -> Result<()> {
+        options: &Options,
+    ) -> Result<Outcome> {
         self.matchers = matchers.to_owned();
 
-        self.state = RefCell::new(State::new(paths.len()));
+        let mut state = State::with_plan(matchers, paths, options);
+        state.set_customize_extension(options.customize_extension);
+        state.set_rescue_matchers(options.rescue_alternatives.clone());
+        self.state = RefCell::new(state);
+        self.last = RefCell::default();
+        self.quiet = options.quiet;
+        self.preview = options.dry_run;
+        self.settings = options.replacement.clone();
+        if options.quiet {
+            self.multi_progress
+                .set_draw_target(indicatif::ProgressDrawTarget::hidden());
+        }
         self.bar = Some(
             self.multi_progress
-                .add(ProgressBar::new(paths.len() as u64)),
+                .add(Self::progress_bar(paths.len() as u64)),
         );
 
         if let Some(matcher) = self
@@ -146,8 +262,10 @@ impl ui::Interface for Text {
             self.matcher_name_length = matcher.name().len();
         }
 
-        Processing::new(self, matchers, paths).run()?;
-        Ok(())
+        let mut processing = Processing::new(self, matchers, paths)
+            .with_options(options.clone());
+        processing.run()?;
+        Ok(processing.stats().outcome())
     }
 }
 
@@ -155,25 +273,58 @@ impl Reporter for Text {
     fn setup(&self, _count: usize) {}
     fn processing(&self, path: &Path) {
         self.state.borrow_mut().set_current_path(path.to_path_buf());
+        if let Some(bar) = &self.bar {
+            let name = path.file_name().unwrap_or(path.as_os_str());
+            bar.set_message(name.to_string_lossy().into_owned());
+        }
     }
     fn processing_ok(&self, replacement: &Replacement) {
         self.state
             .borrow_mut()
             .set_current_success(replacement.clone());
+        if !self.preview {
+            self.last.replace(Some(replacement.clone()));
+        }
         self.inc_progress();
     }
     fn processing_err(&self, path: &Path, error: &Error) {
+        self.last.take();
         self.state
             .borrow_mut()
             .set_current_failure(path.to_path_buf(), format!("{}", error));
         self.inc_progress();
     }
+    fn processing_undone(&self, _replacement: &Replacement) {
+        self.last.take();
+    }
+    /// Print the summary once the progress bar is finished
+    fn finish(&self, stats: &Stats) {
+        if let Some(bar) = &self.bar {
+            bar.finish();
+        }
+        if !self.quiet {
+            self.write_line(&stats.to_string());
+        }
+    }
 }
 
 impl Communication for Text {
     fn confirm(&self, replacement: &Replacement) -> Confirmation {
+        if self.preview {
+            if !self.quiet {
+                self.write_line(&Self::preview_line(replacement));
+            }
+            return Confirmation::Accept;
+        }
+
         let mut state = self.state.borrow_mut();
         state.set_current_confirm(replacement.clone(), &self.matchers);
+
+        if state.skipping() {
+            state.set_current_resolving(Confirmation::Skip);
+            return Confirmation::Skip;
+        }
+
         Resolver {
             ui: self,
             state: &mut state,
@@ -182,9 +333,16 @@ impl Communication for Text {
         .resolve()
     }
     fn rescue(&self, error: Error) -> processing::Result<Replacement> {
+        if self.preview {
+            return Err(error);
+        }
+
         match &error {
             Error::NoMatch(path) => {
-                let replacement = match Replacement::try_from(path.as_path()) {
+                let replacement = match Replacement::try_from_with(
+                    path.as_path(),
+                    &self.settings,
+                ) {
                     Ok(rep) => rep,
                     Err(_) => return Err(error),
                 };
@@ -216,6 +374,32 @@ impl Communication for Text {
             }
         }
     }
+    fn on_error(&self, path: &Path, error: &Error) -> ErrorResolution {
+        if self.preview {
+            return ErrorResolution::Report;
+        }
+        let Some(replacement) =
+            Self::retry_replacement(path, error, &self.settings)
+        else {
+            return ErrorResolution::Report;
+        };
+
+        let mut state = self.state.borrow_mut();
+        state.set_current_retry(replacement, format!("{}", error));
+        let resolution = Resolver {
+            ui: self,
+            state: &mut state,
+            action: None,
+        }
+        .resolve();
+        match resolution {
+            Confirmation::Abort => ErrorResolution::Abort,
+            Confirmation::Replace(replacement) => {
+                ErrorResolution::Retry(replacement)
+            }
+            _ => ErrorResolution::Report,
+        }
+    }
 }
 
 struct Resolver<'a> {
@@ -234,20 +418,38 @@ impl<'a> Resolver<'a> {
                     Current::Confirm(change) => {
                         let rep = &change.replacement;
 
-                        println!("In {}", rep.parent.display());
-                        println!(
+                        self.write_remaining();
+                        self.ui.write_line(&format!(
+                            "In {}",
+                            rep.parent.display()
+                        ));
+                        self.ui.write_line(&format!(
                             "Replace {} with {}",
                             rep.file_name(),
                             rep.new_file_name()
-                        );
+                        ));
 
                         self.main_dialog();
                     }
                     Current::Rescue(change) => {
                         let rep = &change.replacement;
 
-                        println!("In {}", rep.parent.display());
-                        println!("No match was found for {}", rep.file_name());
+                        self.write_remaining();
+                        self.ui.write_line(&format!(
+                            "In {}",
+                            rep.parent.display()
+                        ));
+                        match &change.error {
+                            Some(error) => self.ui.write_line(&format!(
+                                "Unable to rename {}: {}",
+                                rep.file_name(),
+                                error
+                            )),
+                            None => self.ui.write_line(&format!(
+                                "No match was found for {}",
+                                rep.file_name()
+                            )),
+                        }
                         self.main_dialog();
                     }
                     Current::Resolving(conf) => return conf.clone(),
@@ -259,6 +461,13 @@ impl<'a> Resolver<'a> {
         }
     }
 
+    fn write_remaining(&self) {
+        self.ui.write_line(&format!(
+            "{} file(s) remaining",
+            self.state.remaining()
+        ));
+    }
+
     fn main_dialog(&mut self) {
         use dialoguer::FuzzySelect;
 
@@ -271,11 +480,18 @@ impl<'a> Resolver<'a> {
                 actions.push(action);
             }
         }
+        // Only a confirmation lets the processing revert the previous rename
+        if self.ui.last.borrow().is_some()
+            && matches!(self.state.current(), Current::Confirm(_))
+        {
+            prompts.push("Undo the previous rename");
+            actions.push(&Action::Undo);
+        }
 
         let selection = FuzzySelect::with_theme(&self.ui.theme)
             .with_prompt("What do you want to do?")
             .items(&prompts)
-            .interact()
+            .interact_on(&self.ui.term)
             .unwrap();
 
         self.action = actions
@@ -287,6 +503,7 @@ impl<'a> Resolver<'a> {
         match action {
             Action::Accept
             | Action::Always
+            | Action::AlwaysAll
             | Action::Skip
             | Action::Refuse
             | Action::Ignore
@@ -314,6 +531,28 @@ impl<'a> Resolver<'a> {
             Action::ConfirmCustomization => {
                 self.confirm_customization();
             }
+            Action::SkipToRescue => {
+                if self.state.skip_to_next_rescue() {
+                    self.state.set_current_resolving(Confirmation::Skip);
+                }
+            }
+            Action::Transform(transform) => {
+                self.state.apply_transform(&transform);
+                self.action = Some(Action::ConfirmCustomization);
+            }
+            Action::ChangeFormat => {
+                self.change_format();
+            }
+            Action::Undo => {
+                if let Some(last) = self.ui.last.borrow().as_ref() {
+                    self.ui.write_line(&format!(
+                        "Reverting {} to {}",
+                        last.new_file_name(),
+                        last.file_name()
+                    ));
+                }
+                self.state.set_current_resolving(Confirmation::Undo);
+            }
         }
     }
 
@@ -321,7 +560,9 @@ impl<'a> Resolver<'a> {
         use dialoguer::console::{pad_str, Alignment};
         use dialoguer::FuzzySelect;
 
-        if let Current::Confirm(change) = self.state.current() {
+        if let Current::Confirm(change) | Current::Rescue(change) =
+            self.state.current()
+        {
             let mut replacements = vec![];
             let mut options = vec![];
 
@@ -345,11 +586,11 @@ impl<'a> Resolver<'a> {
             let selection = FuzzySelect::with_theme(&self.ui.theme)
                 .with_prompt("What do you want to do?")
                 .items(&options)
-                .interact()
+                .interact_on(&self.ui.term)
                 .unwrap();
 
             if let Some(replacement) = replacements.get(selection) {
-                self.state.customize(replacement.new_file_stem.clone());
+                self.state.customize(change.editable_name(replacement));
                 self.action = Some(Action::ConfirmCustomization);
             }
         }
@@ -365,7 +606,7 @@ impl<'a> Resolver<'a> {
             let new_file_stem: String = Input::with_theme(&self.ui.theme)
                 .with_prompt("New file name?")
                 .with_initial_text(file_stem)
-                .interact_text()
+                .interact_text_on(&self.ui.term)
                 .unwrap();
 
             self.state.customize(new_file_stem);
@@ -373,11 +614,39 @@ impl<'a> Resolver<'a> {
         }
     }
 
+    fn change_format(&mut self) {
+        use dialoguer::Input;
+
+        let Some(current) = self.state.change().and_then(|change| {
+            change
+                .date_format
+                .clone()
+                .or_else(|| change.replacement.date_format.clone())
+        }) else {
+            return;
+        };
+
+        let format: String = Input::with_theme(&self.ui.theme)
+            .with_prompt("New date format?")
+            .with_initial_text(current)
+            .interact_text_on(&self.ui.term)
+            .unwrap();
+
+        if self.state.change_date_format(&format) {
+            self.action = Some(Action::ConfirmCustomization);
+        } else {
+            log::error!("Unable to render the date with format {}", format);
+        }
+    }
+
     fn confirm_customization(&mut self) {
         use dialoguer::FuzzySelect;
 
-        if let Some(replacement) = self.state.customized_replacement() {
+        if let (Some(replacement), Some(change)) =
+            (self.state.customized_replacement(), self.state.change())
+        {
             let options = ["Yes", "No", "Customize"];
+            let editable_name = change.editable_name(&replacement);
 
             let selection = FuzzySelect::with_theme(&self.ui.theme)
                 .with_prompt(format!(
@@ -385,13 +654,13 @@ impl<'a> Resolver<'a> {
                     ReplacementDisplay::from(&replacement)
                 ))
                 .items(&options)
-                .interact()
+                .interact_on(&self.ui.term)
                 .unwrap();
 
             self.action = match selection {
                 0 => Some(Action::Replace(replacement)),
                 1 => Some(Action::Cancel),
-                2 => Some(Action::Customize(replacement.new_file_stem.clone())),
+                2 => Some(Action::Customize(editable_name)),
                 _ => None,
             }
         }
@@ -401,6 +670,7 @@ impl<'a> Resolver<'a> {
         match action {
             Action::Accept => Some("Yes, accept the rename and continue"),
             Action::Always => Some("Always accept similar rename and continue"),
+            Action::AlwaysAll => Some("Accept every following rename"),
             Action::Customize(_) => Some("Customize the rename"),
             Action::ViewAlternatives => Some("View other possibilities"),
             Action::Replace(_) => None,
@@ -416,6 +686,165 @@ impl<'a> Resolver<'a> {
             Action::Abort => Some("Quit now, refusing this rename"),
             Action::Cancel => None,
             Action::ConfirmCustomization => None,
+            Action::SkipToRescue => {
+                Some("Skip files up to the next one without match")
+            }
+            Action::Transform(transform) => match transform {
+                Transform::Lowercase => Some("Lowercase this file name"),
+                Transform::Uppercase => Some("Uppercase this file name"),
+                Transform::Slugify => Some("Slugify this file name"),
+                Transform::Ascii => Some("Convert this file name to ASCII"),
+                Transform::Append(_) => None,
+            },
+            Action::Undo => None,
+            Action::ChangeFormat => Some("Change the date format"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{assert_eq, test};
+
+    #[test]
+    fn stream_select() {
+        assert_eq!(Some(Stream::Stdout), Stream::select(true, true));
+        assert_eq!(Some(Stream::Stdout), Stream::select(true, false));
+        // stdout is piped but stderr is still a terminal
+        assert_eq!(Some(Stream::Stderr), Stream::select(false, true));
+        assert_eq!(None, Stream::select(false, false));
+    }
+
+    #[test]
+    fn progress_message() {
+        let mut text = Text::new();
+        text.bar = Some(text.multi_progress.add(Text::progress_bar(2)));
+
+        text.processing(Path::new("/tmp/foo.jpg"));
+        assert_eq!("foo.jpg", text.bar.as_ref().unwrap().message());
+        assert_eq!(2, text.bar.as_ref().unwrap().length().unwrap());
+    }
+
+    #[test]
+    fn preview_line() {
+        use dialoguer::console;
+
+        let mut replacement =
+            Replacement::try_from(Path::new("/tmp/foo.jpg")).unwrap();
+        replacement.new_file_stem = String::from("2024-01-20 foo");
+
+        console::set_colors_enabled(false);
+        assert_eq!(
+            "In /tmp: 2024-01-20 foo.jpg",
+            Text::preview_line(&replacement)
+        );
+
+        // Added characters are highlighted in green
+        console::set_colors_enabled(true);
+        let line = Text::preview_line(&replacement);
+        assert!(line.contains(&console::style('2').green().to_string()));
+        assert!(line.contains(&console::style('f').to_string()));
+    }
+
+    #[test]
+    fn retry_replacement() {
+        use std::io;
+
+        let path = Path::new("/tmp/foo 20240120.jpg");
+
+        let replacement = Text::retry_replacement(
+            path,
+            &Error::TargetExists(PathBuf::from("/tmp/2024-01-20 foo.jpg")),
+            &Settings::default(),
+        )
+        .unwrap();
+        assert_eq!("foo 20240120", replacement.file_stem);
+        assert_eq!("2024-01-20 foo", replacement.new_file_stem);
+        assert_eq!("jpg", replacement.new_extension());
+
+        let replacement = Text::retry_replacement(
+            path,
+            &Error::Io(io::Error::from(io::ErrorKind::PermissionDenied)),
+            &Settings::default(),
+        )
+        .unwrap();
+        assert_eq!("foo 20240120", replacement.new_file_stem);
+
+        assert!(Text::retry_replacement(
+            path,
+            &Error::PermissionDenied(path.to_path_buf()),
+            &Settings::default(),
+        )
+        .is_some());
+
+        assert!(Text::retry_replacement(
+            path,
+            &Error::SourceVanished(path.to_path_buf()),
+            &Settings::default(),
+        )
+        .is_none());
+    }
+
+    // Accepting a rename then undoing it leaves nothing to undo
+    #[test]
+    fn accept_then_undo() {
+        let path = Path::new("/tmp/foo 20240120.txt");
+        let mut replacement = Replacement::try_from(path).unwrap();
+        replacement.new_file_stem = String::from("2024-01-20 foo");
+
+        let text = Text::new();
+        text.processing(path);
+        text.processing_ok(&replacement);
+        assert!(text.last.borrow().is_some());
+
+        text.processing_undone(&replacement);
+        assert!(text.last.borrow().is_none());
+    }
+
+    // Only the rename of the previous path can be undone
+    #[test]
+    fn undo_cleared_on_error() {
+        let path = Path::new("/tmp/foo.txt");
+        let replacement = Replacement::try_from(path).unwrap();
+
+        let text = Text::new();
+        text.processing_ok(&replacement);
+        text.processing_err(path, &Error::Skip(path.to_path_buf()));
+        assert!(text.last.borrow().is_none());
+    }
+
+    // A dry run previews the renames without prompting nor renaming
+    #[test]
+    fn dry_run_preview() -> Result<()> {
+        use crate::test::{assert_fs::*, with_temp_dir};
+        use crate::ui::Interface;
+        use predicates::prelude::*;
+
+        with_temp_dir(|temp| {
+            let foo = temp.existing_child("foo 20240120.txt").unwrap();
+            let bar = temp.existing_child("bar.txt").unwrap();
+            let matchers = [crate::test::matchers::ymd_boxed()];
+            let options = Options {
+                dry_run: true,
+                quiet: true,
+                ..Options::default()
+            };
+
+            let mut text = Text::new();
+            let outcome = text.process(
+                &matchers,
+                &[foo.to_path_buf(), bar.to_path_buf()],
+                &options,
+            )?;
+
+            foo.assert(predicate::path::exists());
+            bar.assert(predicate::path::exists());
+            temp.child("2024-01-20 foo.txt")
+                .assert(predicate::path::missing());
+            assert_eq!(Outcome::Success, outcome);
+
+            Ok(())
+        })
+    }
+}
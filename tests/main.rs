@@ -1,7 +1,13 @@
 use anyhow::Result;
 use assert_cmd::Command;
+use assert_fs::prelude::*;
 use assert_fs::TempDir;
-use predicates::str;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::{path, str};
+use prefix_by_date::matcher::{Matcher, Pattern};
+use prefix_by_date::processing::{self, Confirmation, ErrorResolution};
+use prefix_by_date::replacement::Replacement;
+use std::path::Path;
 
 pub struct Env {
     pub conf_dir: TempDir,
@@ -18,7 +24,13 @@ impl Env {
 
     pub fn command(&self) -> Result<Command> {
         let mut cmd = Command::cargo_bin("prefix-by-date")?;
-        cmd.arg("-C").arg(self.conf_dir.path());
+        // Without terminal nor display, the program is not interactive, and
+        // no local config is found outside of the config directory
+        cmd.arg("-C")
+            .arg(self.conf_dir.path())
+            .current_dir(self.conf_dir.path())
+            .env_remove("DISPLAY")
+            .env_remove("WAYLAND_DISPLAY");
         Ok(cmd)
     }
 }
@@ -31,3 +43,575 @@ fn empty() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn dry_run() -> Result<()> {
+    let env = Env::new()?;
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo.txt");
+    foo.touch()?;
+
+    env.command()?
+        .arg("--today")
+        .arg("--dry-run")
+        .arg(foo.path())
+        .assert()
+        .success();
+
+    foo.assert(path::exists());
+    assert_eq!(1, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+fn prefix_separator() -> Result<()> {
+    let env = Env::new()?;
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo.txt");
+    foo.touch()?;
+
+    env.command()?
+        .arg("--date=2024-01-20")
+        .arg("--prefix-separator=__")
+        .arg(foo.path())
+        .assert()
+        .success();
+
+    foo.assert(path::missing());
+    dir.child("2024-01-20__foo.txt").assert(path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn duplicate_paths() -> Result<()> {
+    let env = Env::new()?;
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo.txt");
+    foo.touch()?;
+
+    env.command()?
+        .arg("--date=2024-01-20")
+        .arg("-vv")
+        .arg(foo.path())
+        .arg(foo.path())
+        .assert()
+        .success()
+        .stderr(str::contains("Path not found").not());
+
+    foo.assert(path::missing());
+    dir.child("2024-01-20 foo.txt").assert(path::exists());
+    assert_eq!(1, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+fn position() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo 20240120.txt");
+    let bar = dir.child("bar 20240121.txt");
+    foo.touch()?;
+    bar.touch()?;
+
+    env.command()?.arg(foo.path()).assert().success();
+    dir.child("2024-01-20 foo.txt").assert(path::exists());
+
+    env.command()?
+        .arg("--position=suffix")
+        .arg(bar.path())
+        .assert()
+        .success();
+    dir.child("bar 2024-01-21.txt").assert(path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn undo() -> Result<()> {
+    let env = Env::new()?;
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo.txt");
+    foo.touch()?;
+
+    env.command()?
+        .arg("--today")
+        .arg(foo.path())
+        .assert()
+        .success();
+    foo.assert(path::missing());
+
+    env.command()?.arg("undo").assert().success();
+    foo.assert(path::exists());
+    assert_eq!(1, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+fn sidecar() -> Result<()> {
+    let env = Env::new()?;
+    let dir = TempDir::new()?;
+    let photo = dir.child("photo.jpg");
+    photo.touch()?;
+    let sidecar = dir.child("photo.jpg.json");
+    sidecar.write_str(r#"{"date_taken": "2024-01-20 10:30:00"}"#)?;
+
+    env.command()?
+        .arg("--sidecar")
+        .arg(photo.path())
+        .assert()
+        .success();
+    photo.assert(path::missing());
+    sidecar.assert(path::missing());
+    dir.child("2024-01-20 photo.jpg").assert(path::exists());
+    dir.child("2024-01-20 photo.jpg.json")
+        .assert(str::contains("date_taken"));
+
+    env.command()?.arg("undo").assert().success();
+    photo.assert(path::exists());
+    sidecar.assert(path::exists());
+    assert_eq!(2, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+fn sample() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    let dir = TempDir::new()?;
+    for name in ["foo 20240120", "bar 20240121", "baz"] {
+        dir.child(name).touch()?;
+    }
+
+    env.command()?
+        .arg("--dry-run")
+        .arg("--sample")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(str::contains("Sample of 3 files"))
+        .stdout(str::is_match(r"(?m)^  ymd +2$")?)
+        .stdout(str::is_match(r"(?m)^  unmatched +1$")?);
+
+    dir.child("baz").assert(path::exists());
+    assert_eq!(3, std::fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+fn list_matchers() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+
+[matchers.patterns.dmy]
+regex = '(?<day>\d{2})-(?<month>\d{2})-(?<year>\d{4})\s+(?<rest>.+)'
+format = "%d.%m.%Y"
+priority = -1
+"#,
+    )?;
+
+    env.command()?
+        .arg("--auto-accept=ymd")
+        .arg("list-matchers")
+        .assert()
+        .success()
+        .stdout(str::is_match(
+            r"(?m)^dmy \(priority -1\)\n  format: +%d\.%m\.%Y$",
+        )?)
+        .stdout(str::is_match(r"(?m)^ymd \(priority 0\)$")?)
+        .stdout(str::is_match(r"(?m)^  auto-accept: +true$")?)
+        .stdout(str::contains(r"(?<start>.+)\s+(?<year>\d{4})"))
+        .stdout(str::contains(r"(?<day>\d{2})-(?<month>\d{2})"));
+
+    Ok(())
+}
+
+#[test]
+fn check_config() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    env.command()?
+        .arg("check-config")
+        .assert()
+        .success()
+        .stdout(str::contains("Config OK"));
+
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.md]
+regex = '(?<start>.+)\s+(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    env.command()?
+        .arg("check-config")
+        .assert()
+        .code(3)
+        .stdout(str::contains("error: Invalid pattern md: no year capture"));
+
+    Ok(())
+}
+
+#[test]
+fn quiet() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo 20240120");
+    let bar = dir.child("bar 20240121");
+    foo.touch()?;
+    bar.touch()?;
+    dir.child("2024-01-21 bar").touch()?;
+
+    env.command()?
+        .arg("--quiet")
+        .arg(foo.path())
+        .assert()
+        .success()
+        .stdout(str::is_empty())
+        .stderr(str::is_empty());
+    dir.child("2024-01-20 foo").assert(path::exists());
+
+    // Failures to process paths are still reported, here because the
+    // target already exists
+    env.command()?
+        .arg("--quiet")
+        .arg(bar.path())
+        .assert()
+        .code(1)
+        .stdout(str::is_empty())
+        .stderr(str::contains("bar"));
+    bar.assert(path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn stdin() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    let dir = TempDir::new()?;
+    let names = ["foo 20240120", "bar\n20240121", "baz 20240122"];
+    let mut input = String::new();
+    for name in names {
+        dir.child(name).touch()?;
+        input.push_str(&format!("{}\0", dir.child(name).path().display()));
+    }
+
+    env.command()?
+        .arg("--stdin")
+        .arg("-0")
+        .write_stdin(input)
+        .assert()
+        .success();
+
+    for name in ["2024-01-20 foo", "2024-01-21 bar", "2024-01-22 baz"] {
+        dir.child(name).assert(path::exists());
+    }
+    assert_eq!(3, std::fs::read_dir(dir.path())?.count());
+
+    // Nothing to do without any path
+    env.command()?
+        .arg("--stdin")
+        .write_stdin("")
+        .assert()
+        .success()
+        .stderr(str::is_empty());
+
+    Ok(())
+}
+
+/// Interface accepting every replacement and rescuing nothing
+struct Accept;
+
+impl processing::Reporter for Accept {
+    fn setup(&self, _count: usize) {}
+    fn processing(&self, _path: &Path) {}
+    fn processing_ok(&self, _replacement: &Replacement) {}
+    fn processing_err(&self, _path: &Path, _error: &processing::Error) {}
+    fn processing_undone(&self, _replacement: &Replacement) {}
+    fn finish(&self, _stats: &processing::Stats) {}
+}
+
+impl processing::Communication for Accept {
+    fn confirm(&self, _replacement: &Replacement) -> Confirmation {
+        Confirmation::Accept
+    }
+    fn rescue(
+        &self,
+        error: processing::Error,
+    ) -> processing::Result<Replacement> {
+        Err(error)
+    }
+    fn on_error(
+        &self,
+        _path: &Path,
+        _error: &processing::Error,
+    ) -> ErrorResolution {
+        ErrorResolution::Report
+    }
+}
+
+#[test]
+fn library() -> Result<()> {
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo 20240120.txt");
+    foo.touch()?;
+    let bar = dir.child("bar.txt");
+    bar.touch()?;
+
+    let matchers: [Box<dyn Matcher>; 1] = [Box::new(
+        Pattern::builder()
+            .name("ymd")
+            .regex(r"(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})")
+            .build()
+            .unwrap(),
+    )];
+    let paths = [foo.to_path_buf(), bar.to_path_buf()];
+
+    prefix_by_date::prefix_paths(&matchers, &paths, &Accept)?;
+
+    foo.assert(path::missing());
+    dir.child("2024-01-20 foo.txt").assert(path::exists());
+    bar.assert(path::exists());
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn no_canonicalize() -> Result<()> {
+    let env = Env::new()?;
+    let dir = TempDir::new()?;
+    let real = dir.child("real");
+    real.create_dir_all()?;
+    let link = dir.child("link");
+    link.symlink_to_dir(real.path())?;
+    real.child("foo.txt").touch()?;
+    real.child("bar.txt").touch()?;
+
+    // The rename is reported inside the real directory by default
+    env.command()?
+        .arg("--today")
+        .arg("-vv")
+        .arg(link.child("foo.txt").path())
+        .assert()
+        .success()
+        .stderr(str::contains(real.path().to_str().unwrap()));
+
+    // But stays within the symlink path when asked to
+    env.command()?
+        .arg("--today")
+        .arg("--no-canonicalize")
+        .arg("-vv")
+        .arg(link.child("bar.txt").path())
+        .assert()
+        .success()
+        .stderr(str::contains(format!("{}/{{", link.path().display())))
+        .stderr(str::contains(real.path().to_str().unwrap()).not());
+    real.child("bar.txt").assert(path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn exit_code() -> Result<()> {
+    let env = Env::new()?;
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo.txt");
+    foo.touch()?;
+
+    // Every path renamed
+    env.command()?
+        .arg("--date=2024-01-20")
+        .arg(foo.path())
+        .assert()
+        .code(0);
+    dir.child("2024-01-20 foo.txt").assert(path::exists());
+
+    // Some paths failed, here because the target already exists
+    foo.touch()?;
+    env.command()?
+        .arg("--date=2024-01-20")
+        .arg(foo.path())
+        .assert()
+        .code(1);
+    foo.assert(path::exists());
+
+    // Invalid arguments
+    env.command()?
+        .arg("--date=yesterday")
+        .arg(foo.path())
+        .assert()
+        .code(3);
+
+    // Invalid config
+    env.conf_dir.child("config.toml").write_str("[matchers")?;
+    env.command()?.arg("check-config").assert().code(3);
+
+    Ok(())
+}
+
+#[test]
+fn preview() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo 20240120.txt");
+    let bar = dir.child("bar.txt");
+    foo.touch()?;
+    bar.touch()?;
+
+    let output = env
+        .command()?
+        .arg("--no-canonicalize")
+        .arg("preview")
+        .arg(foo.path())
+        .arg(bar.path())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let rows = String::from_utf8(output)?
+        .lines()
+        .map(|line| line.split('\t').map(String::from).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            vec!["old_path", "new_path", "matcher_name"],
+            vec![
+                foo.path().to_str().unwrap(),
+                dir.child("2024-01-20 foo.txt").path().to_str().unwrap(),
+                "ymd",
+            ],
+            vec![bar.path().to_str().unwrap(), "", "-"],
+        ],
+        rows
+    );
+
+    // Nothing is renamed
+    foo.assert(path::exists());
+    bar.assert(path::exists());
+    dir.child("2024-01-20 foo.txt").assert(path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn preview_print0() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    let dir = TempDir::new()?;
+    let foo = dir.child("foo 20240120.txt");
+    let bar = dir.child("bar\nbaz.txt");
+    foo.touch()?;
+    bar.touch()?;
+
+    let output = env
+        .command()?
+        .arg("--no-canonicalize")
+        .arg("--print0")
+        .arg("preview")
+        .arg(foo.path())
+        .arg(bar.path())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let output = String::from_utf8(output)?;
+    let records = output.split_terminator('\0').collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            String::from("old_path\tnew_path\tmatcher_name"),
+            format!(
+                "{}\t{}\tymd",
+                foo.path().display(),
+                dir.child("2024-01-20 foo.txt").path().display()
+            ),
+            format!("{}\t\t-", bar.path().display()),
+        ],
+        records
+    );
+
+    Ok(())
+}
+
+#[test]
+fn explain() -> Result<()> {
+    let env = Env::new()?;
+    env.conf_dir.child("config.toml").write_str(
+        r#"
+[matchers.patterns.ymd]
+regex = '(?<start>.+)\s+(?<year>\d{4})(?<month>\d{2})(?<day>\d{2})'
+"#,
+    )?;
+
+    let dir = TempDir::new()?;
+    let bar = dir.child("bar 20241332.txt");
+    bar.touch()?;
+
+    env.command()?
+        .arg("--explain")
+        .arg(bar.path())
+        .assert()
+        .success()
+        .stderr(str::contains("ymd: invalid date"));
+    bar.assert(path::exists());
+
+    Ok(())
+}